@@ -0,0 +1,128 @@
+//! [`rwrc`](https://docs.rs/rwrc) 的派生宏。
+
+#![deny(warnings, missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Field, Fields, GenericParam, Result, parse_macro_input, parse_quote,
+};
+
+/// 为结构体或枚举派生 `rwrc::Trace`。
+///
+/// 依次遍历所有字段，字段类型需要实现 `Trace`。
+/// 标记了 `#[trace(skip)]` 的字段不会被遍历，适用于不包含副本的字段。
+/// 每个类型参数都会被添加 `Trace + 'static` 约束。
+#[proc_macro_derive(Trace, attributes(trace))]
+pub fn derive_trace(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(mut input: DeriveInput) -> Result<TokenStream2> {
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(ty) = param {
+            ty.bounds.push(parse_quote!(::rwrc::Trace));
+            ty.bounds.push(parse_quote!('static))
+        }
+    }
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, calls) = destructure(&data.fields)?;
+            quote! {
+                let Self #pattern = self;
+                #(#calls)*
+            }
+        }
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let name = &variant.ident;
+                let (pattern, calls) = destructure(&variant.fields)?;
+                arms.push(quote!(Self::#name #pattern => { #(#calls)* }))
+            }
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span,
+                "Trace cannot be derived for unions",
+            ));
+        }
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::rwrc::Trace for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn trace(&self, tracer: &mut ::rwrc::Tracer) {
+                #body
+            }
+        }
+    })
+}
+
+/// 生成解构字段的模式和遍历字段的语句。
+fn destructure(fields: &Fields) -> Result<(TokenStream2, Vec<TokenStream2>)> {
+    let mut bindings = Vec::new();
+    let mut calls = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let binding = match &field.ident {
+            Some(ident) => ident.clone(),
+            None => format_ident!("_{i}"),
+        };
+        if is_skipped(field)? {
+            bindings.push(None)
+        } else {
+            calls.push(quote!(::rwrc::Trace::trace(#binding, tracer);));
+            bindings.push(Some(binding))
+        }
+    }
+
+    let pattern = match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().zip(&bindings).map(|(field, binding)| {
+                let name = field.ident.as_ref().unwrap();
+                match binding {
+                    Some(_) => quote!(#name),
+                    None => quote!(#name: _),
+                }
+            });
+            quote!({ #(#names,)* })
+        }
+        Fields::Unnamed(_) => {
+            let names = bindings.iter().map(|binding| match binding {
+                Some(binding) => quote!(#binding),
+                None => quote!(_),
+            });
+            quote!(( #(#names,)* ))
+        }
+        Fields::Unit => quote!(),
+    };
+    Ok((pattern, calls))
+}
+
+/// 字段是否标记了 `#[trace(skip)]`。
+fn is_skipped(field: &Field) -> Result<bool> {
+    let mut skip = false;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("trace")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported trace attribute, expected `skip`"))
+            }
+        })?
+    }
+    Ok(skip)
+}
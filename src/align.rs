@@ -0,0 +1,99 @@
+//! 具有指定对齐的共享对象。
+//!
+//! 共享对象与读写状态位于同一个分配中，它的地址只满足类型本身的对齐要求。
+//! 以对齐标记包装共享对象可以在不增加分配的情况下提高对齐，
+//! 满足 SIMD 或 DMA 对缓冲区地址的要求。
+
+use crate::RwRc;
+use std::ops::{Deref, DerefMut};
+
+/// 以 `A` 的对齐要求存放 `T`。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, align::Align64};
+///
+/// let rc = RwRc::new_aligned::<Align64>([0f32; 16]);
+/// let buf = rc.read();
+/// assert_eq!(buf.as_ptr() as usize % 64, 0);
+/// assert_eq!(buf.len(), 16);
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Aligned<A, T> {
+    /// 不占空间的对齐标记。
+    _align: [A; 0],
+    /// 被包装的对象。
+    pub val: T,
+}
+
+impl<A, T> Aligned<A, T> {
+    /// 包装 `val`。
+    pub const fn new(val: T) -> Self {
+        Self { _align: [], val }
+    }
+
+    /// 取出被包装的对象。
+    pub fn into_inner(self) -> T {
+        self.val
+    }
+}
+
+impl<A, T> Deref for Aligned<A, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.val
+    }
+}
+
+impl<A, T> DerefMut for Aligned<A, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.val
+    }
+}
+
+macro_rules! alignments {
+    ($($name:ident = $align:literal),+ $(,)?) => {
+        $(
+            #[doc = concat!(stringify!($align), " 字节对齐标记。")]
+            #[repr(align($align))]
+            #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+            pub struct $name;
+        )+
+    };
+}
+
+alignments! {
+    Align16 = 16,
+    Align32 = 32,
+    Align64 = 64,
+    Align128 = 128,
+    Align256 = 256,
+    Align4096 = 4096,
+}
+
+impl<T> RwRc<T> {
+    /// 以对齐标记 `A` 的对齐要求创建共享对象，初始为读状态。
+    pub fn new_aligned<A>(val: T) -> RwRc<Aligned<A, T>> {
+        RwRc::new(Aligned::new(val))
+    }
+}
+
+#[test]
+fn test_aligned() {
+    use std::mem::{align_of, size_of};
+
+    assert_eq!(align_of::<Aligned<Align4096, u8>>(), 4096);
+    assert_eq!(size_of::<Aligned<Align16, [u8; 16]>>(), 16);
+
+    let mut rcs = (0..4)
+        .map(|i| RwRc::new_aligned::<Align256>([i as u8; 3]))
+        .collect::<Vec<_>>();
+    for rc in &rcs {
+        assert_eq!(rc.read().as_ptr() as usize % 256, 0)
+    }
+    rcs[1].write()[0] = 9;
+    assert_eq!(rcs[1].read().val, [9, 1, 1]);
+}
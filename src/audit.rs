@@ -0,0 +1,236 @@
+use crate::{Access, Internal, RwRc, RwState};
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    rc::{Rc, Weak},
+};
+
+/// 共享对象上由副本和借用记录的访问权限，独立于共享读写状态维护。
+#[derive(Default)]
+pub(super) struct Ledger {
+    /// 处于读状态或可升级读状态的副本数量。
+    read_handles: Cell<usize>,
+    /// 处于写状态的副本数量。
+    write_handles: Cell<usize>,
+    /// 从持有状态获取的存活只读借用数量。
+    read_guards: Cell<usize>,
+    /// 从持有状态获取的存活可变借用数量。
+    write_guards: Cell<usize>,
+    /// 从读状态获取的存活可变借用数量。
+    upgrade_guards: Cell<usize>,
+}
+
+/// 借用的种类。
+pub(super) enum Guard {
+    /// 从持有状态获取的只读借用。
+    Read,
+    /// 从持有状态获取的可变借用。
+    Write,
+    /// 从读状态获取的可变借用。
+    Upgrade,
+}
+
+impl Ledger {
+    /// 记录一个副本从 `old` 转换到 `new`，`None` 表示副本不存在。
+    pub fn transition(&self, old: Option<RwState>, new: Option<RwState>) {
+        for (state, delta) in [(old, -1), (new, 1)] {
+            let cell = match state {
+                Some(RwState::Read | RwState::Upgradable) => &self.read_handles,
+                Some(RwState::Write) => &self.write_handles,
+                Some(RwState::Hold) | None => continue,
+            };
+            cell.set(cell.get().wrapping_add_signed(delta))
+        }
+    }
+
+    /// 记录获取或注销一个借用。
+    pub fn guard(&self, guard: Guard, acquired: bool) {
+        let cell = match guard {
+            Guard::Read => &self.read_guards,
+            Guard::Write => &self.write_guards,
+            Guard::Upgrade => &self.upgrade_guards,
+        };
+        cell.set(if acquired {
+            cell.get() + 1
+        } else {
+            cell.get() - 1
+        })
+    }
+}
+
+impl<T> RwRc<T> {
+    /// 记录此副本获取或注销一个借用，借用不改变共享读写状态时不记录。
+    pub(super) fn record_guard(&self, mutable: bool, acquired: bool) {
+        let guard = match (self.state.get(), mutable) {
+            (RwState::Hold, false) => Guard::Read,
+            (RwState::Hold, true) => Guard::Write,
+            (RwState::Read | RwState::Upgradable, true) => Guard::Upgrade,
+            (RwState::Read | RwState::Upgradable | RwState::Write, _) => return,
+        };
+        self.rc.ledger.guard(guard, acquired)
+    }
+}
+
+/// 共享读写状态与副本和借用的记录不一致的共享对象，由 [`audit`] 报告。
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub struct Discrepancy {
+    /// 共享对象标识，参见 [`RwRc::id`](crate::RwRc::id)。
+    pub id: usize,
+    /// 共享对象的类型名。
+    pub type_name: &'static str,
+    /// 共享读写状态中的读者数量。
+    pub flag_readers: usize,
+    /// 共享读写状态是否处于写状态。
+    pub flag_writer: bool,
+    /// 由副本、借用、等待者和子对象推算的读者数量。
+    pub expected_readers: usize,
+    /// 由副本、借用和等待者推算的写者数量。
+    pub expected_writers: usize,
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {:#x}: flag has {} reader(s) and {} writer(s), handles and guards account for {} reader(s) and {} writer(s)",
+            self.type_name,
+            self.id,
+            self.flag_readers,
+            self.flag_writer as usize,
+            self.expected_readers,
+            self.expected_writers,
+        )
+    }
+}
+
+/// 可以被检查的共享对象。
+trait Audited {
+    /// 检查共享对象，一致时返回 `None`。
+    fn audit(&self) -> Option<Discrepancy>;
+}
+
+impl<T> Audited for Internal<T> {
+    fn audit(&self) -> Option<Discrepancy> {
+        let ledger = &self.ledger;
+        let mut readers = ledger.read_handles.get() + ledger.read_guards.get();
+        let mut writers =
+            ledger.write_handles.get() + ledger.write_guards.get() + ledger.upgrade_guards.get();
+        // 从读状态升级的副本不再占用读状态
+        readers = readers.wrapping_sub(ledger.upgrade_guards.get());
+        let waiters = self.extras().map(|e| e.waiters.borrow());
+        let granted = waiters
+            .iter()
+            .flat_map(|w| w.iter())
+            .filter(|w| w.granted());
+        for waiter in granted {
+            match (waiter.access(), waiter.upgrade()) {
+                (Access::Read, _) => readers += 1,
+                (Access::Write, false) => writers += 1,
+                (Access::Write, true) => {
+                    readers = readers.wrapping_sub(1);
+                    writers += 1
+                }
+            }
+        }
+        readers += self.locked_children() + self.extras().map_or(0, |e| e.read_pins.get());
+
+        let flag_readers = self.flag.readers();
+        let flag_writer = !self.flag.is_readable();
+        (readers != flag_readers || writers != flag_writer as usize).then(|| Discrepancy {
+            id: self as *const Self as *const () as usize,
+            type_name: std::any::type_name::<T>(),
+            flag_readers,
+            flag_writer,
+            expected_readers: readers,
+            expected_writers: writers,
+        })
+    }
+}
+
+thread_local! {
+    /// 当前线程上创建的所有共享对象。
+    static REGISTRY: RefCell<Vec<Weak<dyn Audited>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 登记新创建的共享对象。
+pub(super) fn register<T>(rc: &Rc<Internal<T>>) {
+    let weak = Rc::downgrade(rc);
+    let weak: Weak<dyn Audited + '_> = weak;
+    // 只在共享对象存活时通过弱引用访问它，此时 `T` 借用的数据一定仍然有效；
+    // 共享对象释放后弱引用只用于释放分配，不会访问 `T`
+    let weak =
+        unsafe { std::mem::transmute::<Weak<dyn Audited + '_>, Weak<dyn Audited + 'static>>(weak) };
+    REGISTRY.with_borrow_mut(|registry| registry.push(weak))
+}
+
+/// 检查当前线程上所有存活的共享对象，返回共享读写状态与副本和借用的记录不一致的对象。
+///
+/// 每个共享对象的读者数量应当等于处于读状态的副本、从持有状态获取的只读借用、
+/// 已经授予但还没有取走的异步等待者、[`RwWeakRead`](crate::RwWeakRead) 以及被锁定的子对象的数量之和；
+/// 写者数量应当等于处于写状态的副本、存活的临时可变借用和已经授予写状态的等待者的数量之和。
+/// 检查同时会移除已经释放的共享对象的登记。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, debug};
+///
+/// let rc = RwRc::new(0);
+/// let _reader = rc.clone();
+/// let _guard = rc.read();
+/// assert!(debug::audit().is_empty());
+/// ```
+pub fn audit() -> Vec<Discrepancy> {
+    let live = REGISTRY.with_borrow_mut(|registry| {
+        registry.retain(|weak| weak.strong_count() > 0);
+        registry
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>()
+    });
+    live.iter().filter_map(|rc| rc.audit()).collect()
+}
+
+#[test]
+fn test_audit() {
+    use crate::RwRc;
+    use std::{
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    let parent = RwRc::new(());
+    let mut rc = RwRc::new(vec![0]);
+    let other = rc.clone();
+    parent.release();
+    assert!(rc.set_parent(&parent));
+    assert!(audit().is_empty());
+
+    // 临时借用和状态转换都被记录
+    other.release();
+    let guard = other.read();
+    assert!(audit().is_empty());
+    drop(guard);
+    rc.write().push(1);
+    assert!(audit().is_empty());
+    rc.release();
+    assert!(other.try_write_global());
+
+    // 已经授予但没有取走的等待者
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut future = pin!(rc.read_async());
+    assert!(future.as_mut().poll(&mut cx).is_pending());
+    other.release();
+    assert!(audit().is_empty());
+    assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(()));
+
+    // 绕过副本修改共享读写状态会被发现
+    assert!(other.rc.flag.hold_to_read());
+    let found = audit();
+    assert_eq!(found.len(), 1);
+    assert_eq!((found[0].flag_readers, found[0].expected_readers), (2, 1));
+    assert_eq!(found[0].id, other.id());
+    other.rc.flag.read_to_hold();
+    assert!(audit().is_empty());
+}
@@ -0,0 +1,165 @@
+use crate::{
+    RwState,
+    flag::{LocalState, RwFlag},
+};
+use std::cell::UnsafeCell;
+
+/// 调用者拥有的共享对象和共享读写状态，通过 [`RwRcRef`] 访问。
+///
+/// 锚点可以放在栈上、竞技场中或者其他结构体里，不分配也不计数。
+/// 所有副本都借用锚点，因此副本存活期间锚点既不能移动也不能释放。
+///
+/// [`RwRc<T>`](crate::RwRc) 的副本之间必须共享读写状态，即使 `T` 是零大小类型也需要分配；
+/// 以零大小类型作为共享的能力令牌时，可以改用锚点避免分配。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::RwAnchor;
+///
+/// let anchor = RwAnchor::new(vec![1]);
+/// let mut writer = anchor.handle();
+/// let mut reader = anchor.handle();
+///
+/// writer.try_write().unwrap().push(2);
+/// // 写者持有写状态时读者无法获取读状态
+/// assert!(!reader.try_read_global());
+/// writer.release();
+/// assert!(reader.try_read_global());
+/// assert_eq!(*reader.try_read().unwrap(), [1, 2]);
+/// drop((writer, reader));
+/// assert_eq!(anchor.into_inner(), [1, 2]);
+/// ```
+pub struct RwAnchor<T> {
+    /// 共享读写状态。
+    flag: RwFlag,
+    /// 共享对象。
+    val: UnsafeCell<T>,
+}
+
+/// 借用 [`RwAnchor`] 的 [`RwRc<T>`](crate::RwRc)。
+///
+/// 副本的读写状态语义与 [`RwRc<T>`](crate::RwRc) 相同，但只能在读状态或写状态下借用共享对象。
+pub struct RwRcRef<'a, T> {
+    /// 共享的对象和状态。
+    anchor: &'a RwAnchor<T>,
+    /// 此副本占用的读写状态。
+    state: LocalState,
+}
+
+impl<T> RwAnchor<T> {
+    /// 创建锚点。
+    pub const fn new(val: T) -> Self {
+        Self {
+            flag: RwFlag::new_hold(),
+            val: UnsafeCell::new(val),
+        }
+    }
+
+    /// 创建一个持有状态的副本。
+    pub fn handle(&self) -> RwRcRef<'_, T> {
+        RwRcRef {
+            anchor: self,
+            state: LocalState::Hold,
+        }
+    }
+
+    /// 直接可变借用共享对象。不存在副本时才能调用。
+    pub fn get_mut(&mut self) -> &mut T {
+        self.val.get_mut()
+    }
+
+    /// 取出共享对象。
+    pub fn into_inner(self) -> T {
+        self.val.into_inner()
+    }
+}
+
+impl<T> Clone for RwRcRef<'_, T> {
+    /// 只有当源对象在读状态时，克隆的对象才会设置读状态，否则设置为持有状态。
+    fn clone(&self) -> Self {
+        Self {
+            anchor: self.anchor,
+            state: self.state.clone_with(&self.anchor.flag),
+        }
+    }
+}
+
+impl<T> Drop for RwRcRef<'_, T> {
+    fn drop(&mut self) {
+        self.release()
+    }
+}
+
+impl<T> RwRcRef<'_, T> {
+    /// 此副本当前的读写状态。
+    pub fn state(&self) -> RwState {
+        self.state.get()
+    }
+
+    /// 判断是否可读。
+    pub fn is_readable(&self) -> bool {
+        self.state.is_readable(&self.anchor.flag)
+    }
+
+    /// 判断是否可写。
+    pub fn is_writeable(&self) -> bool {
+        self.state.is_writeable(&self.anchor.flag)
+    }
+
+    /// 尝试设置到读状态。
+    pub fn try_read_global(&mut self) -> bool {
+        self.state.try_read_global(&self.anchor.flag)
+    }
+
+    /// 尝试设置到写状态。
+    pub fn try_write_global(&mut self) -> bool {
+        self.state.try_write_global(&self.anchor.flag)
+    }
+
+    /// 释放读写状态，回到持有状态。
+    pub fn release(&mut self) {
+        self.state.release(&self.anchor.flag)
+    }
+
+    /// 在读状态或写状态下借用共享对象，持有状态下返回 `None`。
+    pub fn try_read(&self) -> Option<&T> {
+        // 锚点在副本存活期间有效，借用期间无法改变此副本的状态
+        unsafe { self.state.borrow(self.anchor.val.get()) }
+    }
+
+    /// 尝试设置到写状态并可变借用共享对象，无法获取写状态时返回 `None`。
+    pub fn try_write(&mut self) -> Option<&mut T> {
+        // 锚点在副本存活期间有效，借用期间无法改变此副本的状态
+        unsafe {
+            self.state
+                .borrow_mut(&self.anchor.flag, self.anchor.val.get())
+        }
+    }
+}
+
+#[test]
+fn test_anchor() {
+    let mut anchor = RwAnchor::new(0);
+    {
+        let mut a = anchor.handle();
+        assert!(a.try_read_global());
+        let mut b = a.clone();
+        assert_eq!(b.state(), RwState::Read);
+        assert!(!a.is_writeable());
+        assert!(a.try_write().is_none());
+        b.release();
+        *a.try_write().unwrap() += 1;
+
+        // 写状态的副本克隆为持有状态
+        let mut c = a.clone();
+        assert!(c.try_read().is_none());
+        assert!(!c.try_read_global());
+        drop(a);
+        assert!(c.try_read_global());
+        assert_eq!(c.try_read(), Some(&1));
+    }
+    // 副本全部释放后可以直接访问
+    *anchor.get_mut() += 1;
+    assert_eq!(anchor.into_inner(), 2);
+}
@@ -0,0 +1,163 @@
+//! 保留所有事件的广播日志。
+//!
+//! [`Topic<T>`] 在写状态下把事件追加到共享的日志中，
+//! 每个 [`Cursor<T>`] 记录自己读到的位置，在读状态下按自己的节奏读取新的事件。
+//! 与回调式的通知不同，日志保留发布过的每一个事件。
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rwrc::broadcast::Topic;
+//!
+//! let mut topic = Topic::new();
+//! let mut fast = topic.subscribe();
+//! topic.publish("a").unwrap();
+//! topic.publish("b").unwrap();
+//! assert_eq!(&*fast.next_batch().unwrap(), ["a", "b"]);
+//!
+//! let mut slow = topic.subscribe_from_start();
+//! topic.publish("c").unwrap();
+//! assert_eq!(&*fast.next_batch().unwrap(), ["c"]);
+//! assert_eq!(&*slow.next_batch().unwrap(), ["a", "b", "c"]);
+//! ```
+
+use crate::{Access, LocalRef, LockError, MappedLocalRef, RwRc};
+
+/// 广播日志的发布端。
+///
+/// 克隆的发布端共享同一个日志。
+pub struct Topic<T> {
+    /// 共享的日志，此副本只在发布时持有写状态。
+    log: RwRc<Vec<T>>,
+}
+
+/// 广播日志的订阅者，记录读到的位置。
+pub struct Cursor<T> {
+    /// 共享的日志，此副本只在读取时持有读状态。
+    log: RwRc<Vec<T>>,
+    /// 下一个要读取的事件的位置。
+    pos: usize,
+}
+
+impl<T> Default for Topic<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Topic<T> {
+    fn clone(&self) -> Self {
+        Self {
+            log: self.log.clone(),
+        }
+    }
+}
+
+impl<T> Topic<T> {
+    /// 创建空的广播日志。
+    pub fn new() -> Self {
+        let log = RwRc::new(Vec::new());
+        log.release();
+        Self { log }
+    }
+
+    /// 在日志末尾追加 `item`。
+    ///
+    /// 订阅者正在读取时无法获取写状态，返回 [`LockError`]。
+    pub fn publish(&mut self, item: T) -> Result<(), LockError> {
+        self.log
+            .try_write()
+            .map(|mut log| log.push(item))
+            .ok_or(LockError::Blocked(Access::Write))
+    }
+
+    /// 创建从下一个发布的事件开始读取的订阅者。
+    pub fn subscribe(&self) -> Cursor<T> {
+        let pos = self.len();
+        Cursor {
+            log: self.log.clone(),
+            pos,
+        }
+    }
+
+    /// 创建从第一个事件开始读取的订阅者。
+    pub fn subscribe_from_start(&self) -> Cursor<T> {
+        Cursor {
+            log: self.log.clone(),
+            pos: 0,
+        }
+    }
+
+    /// 已经发布的事件数量。
+    pub fn len(&self) -> usize {
+        self.log.read().len()
+    }
+
+    /// 判断是否还没有发布事件。
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Cursor<T> {
+    /// 读取上次读取之后发布的所有事件，并把位置移到日志末尾。
+    ///
+    /// 返回的借用存在期间，发布端无法追加事件。
+    /// 发布端正在追加时返回 [`LockError`]，位置保持不变。
+    pub fn next_batch(&mut self) -> Result<MappedLocalRef<'_, [T]>, LockError> {
+        let log = self.log.try_read().ok_or_else(|| self.log.read_error())?;
+        let pos = std::mem::replace(&mut self.pos, log.len());
+        Ok(LocalRef::map(log, |log| &log[pos..]))
+    }
+
+    /// 还没有读取的事件数量。
+    pub fn pending(&self) -> usize {
+        self.log.read().len() - self.pos
+    }
+
+    /// 下一个要读取的事件的位置。
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<T> Clone for Cursor<T> {
+    /// 克隆的订阅者从相同的位置开始读取。
+    fn clone(&self) -> Self {
+        Self {
+            log: self.log.clone(),
+            pos: self.pos,
+        }
+    }
+}
+
+#[test]
+fn test_broadcast() {
+    let mut topic = Topic::new();
+    topic.publish(1).unwrap();
+    let mut a = topic.subscribe();
+    let mut b = topic.subscribe_from_start();
+    topic.publish(2).unwrap();
+    assert_eq!((a.pending(), b.pending()), (1, 2));
+
+    // 读取期间无法发布
+    {
+        let batch = a.next_batch().unwrap();
+        assert_eq!(&*batch, [2]);
+        assert_eq!(topic.publish(3), Err(LockError::Blocked(Access::Write)));
+    }
+    assert!(a.next_batch().unwrap().is_empty());
+    let mut c = b.clone();
+    topic.publish(3).unwrap();
+    assert_eq!(&*b.next_batch().unwrap(), [1, 2, 3]);
+    assert_eq!(c.position(), 0);
+    assert_eq!(c.pending(), 3);
+
+    // 发布期间无法读取，位置保持不变
+    let mut other = topic.clone();
+    let guard = other.log.write();
+    assert!(c.next_batch().is_err());
+    drop(guard);
+    assert_eq!(c.position(), 0);
+    assert_eq!(topic.len(), 3);
+}
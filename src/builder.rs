@@ -0,0 +1,101 @@
+use crate::{Internal, Preference, RwRc, RwState, flag::RwFlag};
+use std::rc::Rc;
+
+/// 以可选的构造参数创建 [`RwRc<T>`]，由 [`RwRc::builder`] 创建。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{Preference, RwRc, RwState};
+///
+/// let rc = RwRc::builder(vec![0u8; 4])
+///     .initial_state(RwState::Hold)
+///     .preference(Preference::Write)
+///     .build();
+/// assert_eq!(rc.state(), RwState::Hold);
+/// assert!(rc.is_writeable());
+/// ```
+pub struct RwRcBuilder<T> {
+    /// 共享对象。
+    val: T,
+    /// 第一个副本的读写状态。
+    state: RwState,
+    /// 读写偏好策略。
+    preference: Preference,
+    /// 同时持有读状态的副本数量上限。
+    max_readers: Option<usize>,
+}
+
+impl<T> RwRc<T> {
+    /// 创建构造器，默认参数与 [`RwRc::new`] 相同。
+    pub fn builder(val: T) -> RwRcBuilder<T> {
+        RwRcBuilder {
+            val,
+            state: RwState::Read,
+            preference: Preference::Read,
+            max_readers: None,
+        }
+    }
+}
+
+impl<T> RwRcBuilder<T> {
+    /// 设置第一个副本的读写状态，默认为读状态。
+    pub fn initial_state(mut self, state: RwState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// 设置读写偏好策略，默认为读优先。
+    pub fn preference(mut self, preference: Preference) -> Self {
+        self.preference = preference;
+        self
+    }
+
+    /// 设置同时持有读状态的副本数量上限，默认不限制。
+    ///
+    /// 参见 [`RwRc::set_max_readers`]。
+    pub fn max_readers(mut self, max: usize) -> Self {
+        self.max_readers = Some(max);
+        self
+    }
+
+    /// 创建共享对象和它的第一个副本。
+    pub fn build(self) -> RwRc<T> {
+        let flag = RwFlag::new_hold();
+        match self.state {
+            RwState::Hold => {}
+            RwState::Read | RwState::Upgradable => assert!(flag.hold_to_read()),
+            RwState::Write => assert!(flag.hold_to_write()),
+        }
+        let internal = Internal::new(self.val, flag, self.preference);
+        if let Some(max) = self.max_readers {
+            internal.ensure_extras().max_readers.set(max)
+        }
+        let rc = Rc::new(internal);
+        #[cfg(feature = "audit")]
+        crate::audit::register(&rc);
+        RwRc::from_rc(rc, self.state)
+    }
+}
+
+#[test]
+fn test_builder() {
+    let rc = RwRc::builder(1).build();
+    assert_eq!(rc.state(), RwState::Read);
+    assert_eq!(rc.rc.preference, Preference::Read);
+
+    let mut writer = RwRc::builder(2)
+        .initial_state(RwState::Write)
+        .preference(Preference::Write)
+        .build();
+    let other = writer.clone();
+    assert!(!other.try_read_global());
+    *writer.write() += 1;
+    writer.release();
+    assert_eq!(*other.read(), 3);
+    assert_eq!(other.rc.preference, Preference::Write);
+
+    let limited = RwRc::builder(3).max_readers(1).build();
+    assert_eq!(limited.max_readers(), Some(1));
+    assert_eq!(limited.clone().state(), RwState::Hold);
+}
@@ -0,0 +1,109 @@
+use crate::RwRc;
+use std::ops::Deref;
+
+/// 持有读状态的 [`RwRc<T>`]，可以作为 [`yoke::Yoke`] 的载体。
+///
+/// 载体存在期间副本一直保持读状态，共享对象不会被修改，也不会移动，
+/// 因此可以把从共享对象借用出的视图与载体保存在一起。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::RwRc;
+/// use yoke::Yoke;
+///
+/// let rc = RwRc::new(String::from("hello world"));
+/// let cart = rc.into_read_cart().ok().unwrap();
+///
+/// let word: Yoke<&'static str, _> = Yoke::attach_to_cart(cart, |s: &String| &s[6..]);
+/// assert_eq!(*word.get(), "world");
+///
+/// // 载体存在期间共享对象保持读状态
+/// let mut other = word.backing_cart().handle();
+/// other.release();
+/// assert!(other.try_write().is_none());
+/// ```
+pub struct ReadCart<T>(RwRc<T>);
+
+impl<T> RwRc<T> {
+    /// 将副本转换为持有读状态的载体。
+    ///
+    /// 如果无法获取读状态，返回原副本。
+    pub fn into_read_cart(self) -> Result<ReadCart<T>, Self> {
+        if self.try_read_global() {
+            Ok(ReadCart(self))
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<T> ReadCart<T> {
+    /// 克隆载体内的副本，克隆的副本同样处于读状态。
+    ///
+    /// 副本通过共享引用就可以释放读状态，因此不借出载体内的副本本身。
+    pub fn handle(&self) -> RwRc<T> {
+        self.0.clone()
+    }
+
+    /// 取出载体内持有读状态的副本。
+    pub fn into_inner(self) -> RwRc<T> {
+        self.0
+    }
+}
+
+impl<T> Clone for ReadCart<T> {
+    /// 克隆读状态的副本仍然是读状态。
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Deref for ReadCart<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // 载体持有读状态，共享对象不会被修改
+        unsafe { &*self.0.rc.val.as_ptr() }
+    }
+}
+
+// SAFETY: 共享对象保存在 `Rc` 分配中，地址在载体存在期间不变，且读状态保证不会被修改。
+unsafe impl<T> stable_deref_trait::StableDeref for ReadCart<T> {}
+
+// SAFETY: 克隆的载体指向同一个共享对象，同样持有读状态。
+unsafe impl<T> stable_deref_trait::CloneStableDeref for ReadCart<T> {}
+
+// SAFETY: 同上。
+unsafe impl<T> yoke::CloneableCart for ReadCart<T> {}
+
+#[test]
+fn test_read_cart() {
+    use yoke::Yoke;
+
+    let rc = RwRc::new(String::from("a,b,c"));
+    rc.release();
+
+    let mut writer = rc.clone();
+    assert!(writer.try_write_global());
+    // 其他副本持有写状态时无法转换
+    let rc = rc.into_read_cart().err().unwrap();
+    writer.release();
+
+    let cart = rc.into_read_cart().ok().unwrap();
+    let first: Yoke<&'static str, ReadCart<String>> =
+        Yoke::attach_to_cart(cart, |s: &String| s.split(',').next().unwrap());
+    let cloned = first.clone();
+    assert_eq!(*first.get(), "a");
+    assert_eq!(*cloned.get(), "a");
+
+    // 载体存在期间无法写入
+    assert!(writer.try_write().is_none());
+    drop(first);
+    assert!(writer.try_write().is_none());
+    let handle = cloned.into_backing_cart().into_inner();
+    assert!(writer.try_write().is_none());
+    drop(handle);
+    writer.write().push_str(",d");
+    assert_eq!(*writer.read(), "a,b,c,d");
+}
@@ -0,0 +1,92 @@
+use crate::{LocalMut, LocalRef, LockError, MappedLocalMut, MappedLocalRef, RwRc};
+use bytemuck::{Pod, PodCastError};
+use std::{error::Error, fmt};
+
+/// 将共享字节缓冲区借用为其他类型切片时的错误。
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum CastError {
+    /// 无法获取访问权限。
+    Lock(LockError),
+    /// 缓冲区的长度或对齐不满足目标类型的要求。
+    Layout(PodCastError),
+}
+
+impl From<LockError> for CastError {
+    fn from(e: LockError) -> Self {
+        Self::Lock(e)
+    }
+}
+
+impl fmt::Display for CastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lock(e) => e.fmt(f),
+            Self::Layout(e) => write!(f, "cannot cast the shared bytes: {e:?}"),
+        }
+    }
+}
+
+impl Error for CastError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Lock(e) => Some(e),
+            Self::Layout(_) => None,
+        }
+    }
+}
+
+impl RwRc<Vec<u8>> {
+    /// 将共享字节缓冲区借用为 `[P]`。
+    ///
+    /// 缓冲区的长度必须是 `P` 大小的整数倍，地址必须满足 `P` 的对齐要求。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let rc = RwRc::new(vec![0u8; 8]);
+    /// assert_eq!(*rc.cast_read::<[u8; 2]>().unwrap(), [[0; 2]; 4]);
+    /// assert!(rc.cast_read::<[u8; 3]>().is_err());
+    /// ```
+    pub fn cast_read<P: Pod>(&self) -> Result<MappedLocalRef<'_, [P]>, CastError> {
+        let bytes = self.try_read().ok_or_else(|| self.read_error())?;
+        bytemuck::try_cast_slice::<u8, P>(&bytes).map_err(CastError::Layout)?;
+        Ok(LocalRef::map(bytes, |bytes| bytemuck::cast_slice(bytes)))
+    }
+
+    /// 将共享字节缓冲区可变借用为 `[P]`。
+    ///
+    /// 缓冲区的长度必须是 `P` 大小的整数倍，地址必须满足 `P` 的对齐要求。
+    pub fn cast_write<P: Pod>(&mut self) -> Result<MappedLocalMut<'_, [P]>, CastError> {
+        let mut bytes = self
+            .try_write()
+            .ok_or(LockError::Blocked(crate::Access::Write))?;
+        bytemuck::try_cast_slice_mut::<u8, P>(&mut bytes).map_err(CastError::Layout)?;
+        Ok(LocalMut::map(bytes, |bytes| {
+            bytemuck::cast_slice_mut(bytes)
+        }))
+    }
+}
+
+#[test]
+fn test_cast() {
+    use crate::Access;
+
+    let mut rc = RwRc::new(vec![0u8; 3]);
+    assert_eq!(
+        rc.cast_read::<u16>().err(),
+        Some(CastError::Layout(PodCastError::OutputSliceWouldHaveSlop))
+    );
+    rc.write().push(0);
+    rc.cast_write::<[u8; 2]>().unwrap()[1] = [1, 2];
+    assert_eq!(*rc.read(), [0, 0, 1, 2]);
+
+    let other = rc.clone();
+    assert_eq!(
+        rc.cast_write::<u8>().err(),
+        Some(CastError::Lock(LockError::Blocked(Access::Write)))
+    );
+    assert_eq!(other.cast_read::<[u8; 4]>().unwrap()[0], [0, 0, 1, 2]);
+}
@@ -0,0 +1,206 @@
+use crate::{
+    Internal, RwRc,
+    trace::{Node, Trace, Tracer, node_id},
+};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+/// 可以被环回收清空的共享对象。
+trait Collectable: Node {
+    /// 以默认值替换共享对象，返回原对象。
+    fn take(&self) -> Box<dyn Any>;
+}
+
+impl<T: Trace + Default + 'static> Collectable for Internal<T> {
+    fn take(&self) -> Box<dyn Any> {
+        Box::new(self.val.take())
+    }
+}
+
+thread_local! {
+    /// 当前线程上登记的可回收共享对象。
+    static REGISTRY: RefCell<Vec<Weak<dyn Collectable>>> = const { RefCell::new(Vec::new()) };
+}
+
+impl<T: Trace + Default + 'static> RwRc<T> {
+    /// 创建可以被 [`collect_cycles`] 回收的共享对象，初始为读状态。
+    ///
+    /// 共享对象登记在当前线程上，只有当前线程上的 [`collect_cycles`] 会检查它。
+    pub fn new_collectable(val: T) -> Self {
+        let ans = Self::new(val);
+        let node: Weak<Internal<T>> = Rc::downgrade(&ans.rc);
+        REGISTRY.with_borrow_mut(|registry| registry.push(node));
+        ans
+    }
+}
+
+/// 遍历时记录的共享对象信息。
+struct Info {
+    /// 共享对象。
+    node: Weak<dyn Node>,
+    /// 强引用的共享对象标识。
+    children: Vec<usize>,
+    /// 来自已遍历对象的强引用数量。
+    internal: usize,
+    /// 是否成功遍历。
+    traced: bool,
+}
+
+/// 回收当前线程上只被环形引用保持存活的可回收共享对象，返回回收的对象数量。
+///
+/// 从登记的可回收对象出发遍历所有可达的共享对象，
+/// 强引用计数多于遍历到的内部引用数量的对象视为被外部持有，
+/// 被写锁定而无法遍历的对象也视为被外部持有。
+/// 从这些对象出发不可达的可回收对象会被替换为默认值，从而打断环形引用。
+///
+/// 回收依赖 [`Trace`] 实现正确报告所有强引用；漏报引用可能导致仍在使用的对象被清空。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, Trace, Tracer, collect_cycles};
+///
+/// #[derive(Default)]
+/// struct Node(Option<RwRc<Node>>);
+///
+/// impl Trace for Node {
+///     fn trace(&self, tracer: &mut Tracer) {
+///         self.0.trace(tracer)
+///     }
+/// }
+///
+/// let mut a = RwRc::new_collectable(Node::default());
+/// a.release();
+/// let b = RwRc::new_collectable(Node(Some(a.clone())));
+/// a.write().0 = Some(b.clone());
+///
+/// let weak = b.weak();
+/// drop(a);
+/// drop(b);
+/// assert!(weak.hold().is_some());
+/// assert_eq!(collect_cycles(), 2);
+/// assert!(weak.hold().is_none());
+/// ```
+pub fn collect_cycles() -> usize {
+    let registry = REGISTRY.with_borrow_mut(|registry| {
+        registry.retain(|node| node.strong_count() > 0);
+        registry.clone()
+    });
+
+    // 遍历所有可达的共享对象
+    let mut nodes = HashMap::<usize, Info>::new();
+    let mut stack = registry
+        .iter()
+        .map(|node| node.clone() as Weak<dyn Node>)
+        .collect::<Vec<_>>();
+    while let Some(node) = stack.pop() {
+        let id = node_id(&node);
+        if nodes.contains_key(&id) {
+            continue;
+        }
+        let Some(rc) = node.upgrade() else { continue };
+        let mut tracer = Tracer::default();
+        let traced = rc.trace(&mut tracer);
+        drop(rc);
+
+        let mut children = Vec::new();
+        for edge in tracer.edges.into_iter().filter(|e| e.strong) {
+            children.push(node_id(&edge.node));
+            stack.push(edge.node)
+        }
+        nodes.insert(
+            id,
+            Info {
+                node,
+                children,
+                internal: 0,
+                traced,
+            },
+        );
+    }
+
+    // 统计内部引用
+    let edges = nodes
+        .values()
+        .flat_map(|info| info.children.clone())
+        .collect::<Vec<_>>();
+    for child in edges {
+        if let Some(info) = nodes.get_mut(&child) {
+            info.internal += 1
+        }
+    }
+
+    // 从被外部持有的对象出发标记存活对象
+    let mut alive = HashMap::<usize, ()>::new();
+    let mut stack = nodes
+        .iter()
+        .filter(|(_, info)| !info.traced || info.node.strong_count() > info.internal)
+        .map(|(&id, _)| id)
+        .collect::<Vec<_>>();
+    while let Some(id) = stack.pop() {
+        if alive.insert(id, ()).is_none() {
+            stack.extend(nodes[&id].children.iter().copied())
+        }
+    }
+
+    // 先替换所有垃圾对象，再统一丢弃原对象，避免丢弃时的用户代码观察到部分回收的状态
+    let garbage = registry
+        .iter()
+        .filter(|node| !alive.contains_key(&node_id(node)))
+        .filter_map(Weak::upgrade)
+        .collect::<Vec<_>>();
+    let values = garbage.iter().map(|node| node.take()).collect::<Vec<_>>();
+    drop(values);
+    garbage.len()
+}
+
+#[test]
+fn test_collect_cycles() {
+    #[derive(Default)]
+    struct Node(Vec<RwRc<Node>>);
+
+    impl Trace for Node {
+        fn trace(&self, tracer: &mut Tracer) {
+            self.0.trace(tracer)
+        }
+    }
+
+    let mut a = RwRc::new_collectable(Node::default());
+    let mut b = RwRc::new_collectable(Node::default());
+    a.release();
+    b.release();
+    // a -> b -> a 形成环，a 还引用一个不可回收的对象
+    let plain = RwRc::new(Node::default());
+    a.write().0.push(b.clone());
+    a.write().0.push(plain.clone());
+    b.write().0.push(a.clone());
+    let wp = plain.weak();
+    drop(plain);
+    let (wa, wb) = (a.weak(), b.weak());
+
+    // 外部持有环上的对象时不回收
+    drop(b);
+    assert_eq!(collect_cycles(), 0);
+    assert!(wb.hold().is_some());
+
+    // 被写锁定的对象无法遍历，视为被外部持有
+    let held = wb.hold().unwrap();
+    assert!(held.try_write_global());
+    a.write().0.push(held);
+    drop(a);
+    assert_eq!(collect_cycles(), 0);
+    assert!(wa.hold().is_some());
+
+    // 写状态副本被丢弃后回收整个环和环上引用的对象
+    let mut a = wa.hold().unwrap();
+    drop(a.write().0.pop());
+    drop(a);
+    assert_eq!(collect_cycles(), 2);
+    assert!(wa.hold().is_none());
+    assert!(wb.hold().is_none());
+    assert!(wp.hold().is_none());
+}
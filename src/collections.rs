@@ -0,0 +1,479 @@
+//! 保存共享对象副本的容器。
+
+use crate::{RwRc, RwWeak};
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+/// 带过期时间的弱引用缓存。
+///
+/// 缓存只保存 [`RwWeak<T>`] 和插入时间，不会让共享对象保持存活。
+/// 查找时，插入后超过存活时间或者共享对象已经释放的条目会被移除并返回 `None`；
+/// 否则升级为持有状态的 [`RwRc<T>`]。
+/// 过期只取决于插入时间，查找不会延长条目的存活时间。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, collections::TtlCache};
+/// use std::time::Duration;
+///
+/// let mut cache = TtlCache::new(Duration::from_secs(60));
+/// let texture = cache.get_or_insert_with("grass.png", || RwRc::new(vec![0u8; 16]));
+/// // 共享对象存活时命中缓存
+/// assert_eq!(cache.get("grass.png").unwrap().id(), texture.id());
+///
+/// // 共享对象释放后条目失效
+/// drop(texture);
+/// assert!(cache.get("grass.png").is_none());
+/// assert!(cache.is_empty());
+/// ```
+pub struct TtlCache<K, T> {
+    /// 条目的存活时间。
+    ttl: Duration,
+    /// 弱引用和插入时间。
+    map: HashMap<K, (RwWeak<T>, Instant)>,
+}
+
+impl<K: Hash + Eq, T> TtlCache<K, T> {
+    /// 创建条目存活时间为 `ttl` 的空缓存。
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            map: HashMap::new(),
+        }
+    }
+
+    /// 条目的存活时间。
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// 以 `rc` 的弱引用插入或替换 `key` 的条目，插入时间为当前时间。
+    pub fn insert(&mut self, key: K, rc: &RwRc<T>) {
+        self.map.insert(key, (rc.weak(), Instant::now()));
+    }
+
+    /// 查找 `key` 对应的共享对象，返回持有状态的副本。
+    ///
+    /// 条目过期或共享对象已经释放时移除条目并返回 `None`。
+    pub fn get<Q>(&mut self, key: &Q) -> Option<RwRc<T>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (weak, time) = self.map.get(key)?;
+        let ans = if time.elapsed() < self.ttl {
+            weak.hold()
+        } else {
+            None
+        };
+        if ans.is_none() {
+            self.map.remove(key);
+        }
+        ans
+    }
+
+    /// 查找 `key` 对应的共享对象，未命中时以 `f` 创建共享对象并插入。
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce() -> RwRc<T>) -> RwRc<T> {
+        if let Some(rc) = self.get(&key) {
+            return rc;
+        }
+        let rc = f();
+        self.insert(key, &rc);
+        rc
+    }
+
+    /// 移除 `key` 的条目，返回条目是否仍然有效。
+    pub fn remove<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map
+            .remove(key)
+            .is_some_and(|(weak, time)| time.elapsed() < self.ttl && weak.hold().is_some())
+    }
+
+    /// 移除所有过期或共享对象已经释放的条目。
+    pub fn purge(&mut self) {
+        let ttl = self.ttl;
+        self.map
+            .retain(|_, (weak, time)| time.elapsed() < ttl && weak.hold().is_some())
+    }
+
+    /// 缓存中的条目数量，包括还没有被移除的失效条目。
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// 判断缓存是否为空。
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+/// 保存强引用、按最近使用顺序淘汰的缓存。
+///
+/// 缓存保存持有状态的 [`RwRc<T>`]，让条目在淘汰前一直存活。
+/// 插入后条目数量超过容量时，淘汰最久没有使用的条目；
+/// 正被其他副本读写锁定的条目不会被淘汰，此时缓存暂时超过容量，在之后的插入中继续淘汰。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, collections::LruCache};
+///
+/// let mut cache = LruCache::new(2);
+/// cache.insert("a", &RwRc::new(1));
+/// cache.insert("b", &RwRc::new(2));
+/// let mut a = cache.get("a").unwrap();
+///
+/// // 淘汰最久没有使用的 "b"
+/// cache.insert("c", &RwRc::new(3));
+/// assert!(cache.get("b").is_none());
+///
+/// // 最久没有使用的 "a" 被锁定，改为淘汰 "c"
+/// assert!(a.try_read_global());
+/// cache.get("c");
+/// cache.insert("d", &RwRc::new(4));
+/// assert!(cache.get("a").is_some());
+/// assert!(cache.get("c").is_none());
+/// ```
+pub struct LruCache<K, T> {
+    /// 容量。
+    capacity: usize,
+    /// 单调递增的使用计数。
+    tick: u64,
+    /// 副本和最近一次使用时的计数。
+    map: HashMap<K, (RwRc<T>, u64)>,
+}
+
+impl<K: Hash + Eq, T> LruCache<K, T> {
+    /// 创建容量为 `capacity` 的空缓存。
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            map: HashMap::new(),
+        }
+    }
+
+    /// 缓存的容量。
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 以 `rc` 的副本插入或替换 `key` 的条目，并淘汰超过容量的条目。
+    pub fn insert(&mut self, key: K, rc: &RwRc<T>) {
+        let rc = rc.clone();
+        rc.release();
+        let tick = self.next_tick();
+        self.map.insert(key, (rc, tick));
+        self.evict()
+    }
+
+    /// 查找 `key` 对应的共享对象，返回持有状态的副本，并标记为最近使用。
+    pub fn get<Q>(&mut self, key: &Q) -> Option<RwRc<T>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let tick = self.next_tick();
+        let (rc, last) = self.map.get_mut(key)?;
+        *last = tick;
+        Some(rc.clone())
+    }
+
+    /// 移除 `key` 的条目，返回缓存保存的副本。
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<RwRc<T>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.remove(key).map(|(rc, _)| rc)
+    }
+
+    /// 缓存中的条目数量。
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// 判断缓存是否为空。
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// 推进使用计数。
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// 从最久没有使用的条目开始淘汰，直到不超过容量或没有可以淘汰的条目。
+    fn evict(&mut self) {
+        while self.map.len() > self.capacity {
+            let Some(oldest) = self
+                .map
+                .values()
+                .filter(|(rc, _)| rc.rc.flag.is_writeable())
+                .map(|&(_, tick)| tick)
+                .min()
+            else {
+                break;
+            };
+            self.map.retain(|_, &mut (_, tick)| tick != oldest)
+        }
+    }
+}
+
+/// 保存共享对象副本的分代竞技场。
+///
+/// 竞技场拥有插入的共享对象，并返回可以复制的 [`RwHandle<T>`] 作为键。
+/// 移除条目后槽位的代数递增，旧的键不会解析到之后插入同一槽位的共享对象。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::collections::RwArena;
+///
+/// let mut arena = RwArena::new();
+/// let player = arena.insert(100);
+/// let copy = player;
+/// *arena.get_mut(copy).unwrap().write() -= 10;
+/// assert_eq!(*arena.get(player).unwrap().read(), 90);
+///
+/// // 移除后旧的键失效
+/// arena.remove(player);
+/// let enemy = arena.insert(50);
+/// assert!(arena.get(player).is_none());
+/// assert_eq!(*arena.get(enemy).unwrap().read(), 50);
+/// ```
+pub struct RwArena<T> {
+    /// 槽位。
+    slots: Vec<Slot<T>>,
+    /// 空闲槽位的序号。
+    free: Vec<u32>,
+}
+
+/// 竞技场的一个槽位。
+struct Slot<T> {
+    /// 槽位的代数，每次移除后递增。
+    generation: u32,
+    /// 持有状态的副本。
+    rc: Option<RwRc<T>>,
+}
+
+/// [`RwArena<T>`] 中条目的键。
+///
+/// 键只包含槽位序号和代数，可以复制，也不借用竞技场。
+pub struct RwHandle<T> {
+    /// 槽位序号。
+    index: u32,
+    /// 插入时槽位的代数。
+    generation: u32,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> RwArena<T> {
+    /// 创建空的竞技场。
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// 以 `val` 创建共享对象并插入竞技场，返回它的键。
+    pub fn insert(&mut self, val: T) -> RwHandle<T> {
+        let rc = RwRc::new(val);
+        rc.release();
+        self.insert_rc(rc)
+    }
+
+    /// 把副本 `rc` 插入竞技场，返回它的键。
+    ///
+    /// 竞技场中的副本总是处于持有状态，`rc` 持有的读写状态会被释放。
+    pub fn insert_rc(&mut self, rc: RwRc<T>) -> RwHandle<T> {
+        rc.release();
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index as usize].rc = Some(rc);
+                index
+            }
+            None => {
+                let index = u32::try_from(self.slots.len()).expect("RwArena is full");
+                self.slots.push(Slot {
+                    generation: 0,
+                    rc: Some(rc),
+                });
+                index
+            }
+        };
+        RwHandle {
+            index,
+            generation: self.slots[index as usize].generation,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// 解析键，键已经失效时返回 `None`。
+    pub fn get(&self, handle: RwHandle<T>) -> Option<&RwRc<T>> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)?
+            .rc
+            .as_ref()
+    }
+
+    /// 以可变引用解析键，键已经失效时返回 `None`。
+    pub fn get_mut(&mut self, handle: RwHandle<T>) -> Option<&mut RwRc<T>> {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)?
+            .rc
+            .as_mut()
+    }
+
+    /// 判断键是否仍然有效。
+    pub fn contains(&self, handle: RwHandle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// 移除键对应的条目，返回竞技场保存的副本。之后这个键失效。
+    pub fn remove(&mut self, handle: RwHandle<T>) -> Option<RwRc<T>> {
+        let slot = self
+            .slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)?;
+        let rc = slot.rc.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        Some(rc)
+    }
+
+    /// 竞技场中的条目数量。
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    /// 判断竞技场是否为空。
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for RwArena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for RwHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RwHandle<T> {}
+
+impl<T> PartialEq for RwHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for RwHandle<T> {}
+
+impl<T> Hash for RwHandle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state)
+    }
+}
+
+impl<T> fmt::Debug for RwHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RwHandle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+#[test]
+fn test_ttl_cache() {
+    let mut cache = TtlCache::new(Duration::from_secs(3600));
+    let a = RwRc::new(1);
+    let b = RwRc::new(2);
+    cache.insert("a", &a);
+    cache.insert("b", &b);
+    // 缓存不让共享对象保持存活
+    assert!(cache.get("a").is_some());
+    drop(b);
+    assert_eq!(cache.len(), 2);
+    cache.purge();
+    assert_eq!(cache.len(), 1);
+    assert!(cache.remove("a"));
+    assert!(!cache.remove("a"));
+
+    // 存活时间为零时条目立即过期，即使共享对象仍然存活
+    let mut cache = TtlCache::new(Duration::ZERO);
+    cache.insert("a", &a);
+    assert!(cache.get("a").is_none());
+    assert!(cache.is_empty());
+    let c = cache.get_or_insert_with("a", || RwRc::new(3));
+    assert_ne!(c.id(), a.id());
+}
+
+#[test]
+fn test_lru_cache() {
+    let mut cache = LruCache::new(2);
+    cache.insert(1, &RwRc::new('a'));
+    cache.insert(2, &RwRc::new('b'));
+    cache.get(&1);
+    cache.insert(3, &RwRc::new('c'));
+    assert!(cache.get(&2).is_none());
+
+    // 所有条目都被锁定时暂时超过容量
+    let r1 = cache.get(&1).unwrap();
+    let r3 = cache.get(&3).unwrap();
+    assert!(r1.try_read_global());
+    assert!(r3.try_write_global());
+    cache.insert(4, &RwRc::new('d'));
+    cache.insert(5, &RwRc::new('e'));
+    assert_eq!(cache.len(), 3);
+    assert!(cache.get(&4).is_none());
+
+    // 解锁后在下一次插入时继续淘汰
+    r1.release();
+    r3.release();
+    cache.insert(6, &RwRc::new('f'));
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(&5).is_some() && cache.get(&6).is_some());
+    assert_eq!(cache.remove(&6).map(|rc| *rc.read()), Some('f'));
+}
+
+#[test]
+fn test_arena() {
+    let mut arena = RwArena::new();
+    let a = arena.insert('a');
+    let b = arena.insert_rc(RwRc::new('b'));
+    assert_eq!(arena.len(), 2);
+    assert_eq!(arena.get(b).unwrap().state(), crate::RwState::Hold);
+
+    // 移除后槽位被复用，旧的键失效
+    assert_eq!(arena.remove(a).map(|rc| *rc.read()), Some('a'));
+    assert!(arena.remove(a).is_none());
+    let c = arena.insert('c');
+    assert_ne!(a, c);
+    assert!(!arena.contains(a));
+    assert!(arena.get_mut(a).is_none());
+    assert_eq!(*arena.get(c).unwrap().read(), 'c');
+    assert_eq!(arena.len(), 2);
+}
@@ -0,0 +1,100 @@
+use crate::{Internal, RwRc};
+use std::{
+    cell::RefCell,
+    sync::mpsc::{self, Receiver},
+};
+
+/// 写状态结束时接收共享对象的观察者，返回 `false` 时被移除。
+type Observer<T> = Box<dyn FnMut(&T) -> bool>;
+
+/// 共享对象上的观察者。
+pub(super) struct Observers<T>(RefCell<Vec<Observer<T>>>);
+
+impl<T> Default for Observers<T> {
+    fn default() -> Self {
+        Self(RefCell::new(Vec::new()))
+    }
+}
+
+impl<T> Observers<T> {
+    /// 登记一个观察者。
+    pub(super) fn push(&self, f: impl FnMut(&T) -> bool + 'static) {
+        self.0.borrow_mut().push(Box::new(f))
+    }
+}
+
+impl<T> Internal<T> {
+    /// 在写状态结束前通知观察者并更新版本和散列值。调用时共享读写状态仍处于写状态。
+    pub(super) fn commit(&self) {
+        #[cfg(feature = "integrity-check")]
+        self.update_checksum();
+        let Some(extras) = self.extras() else { return };
+        extras.version.set(extras.version.get() + 1);
+        let mut observers = extras.committed.0.borrow_mut();
+        if !observers.is_empty() {
+            // 写状态下没有其他副本能访问共享对象
+            let val = unsafe { &*self.val.as_ptr() };
+            observers.retain_mut(|f| f(val))
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> RwRc<T> {
+    /// 创建一个通道，每次共享对象的写状态结束时把共享对象的克隆发送给接收端。
+    ///
+    /// 写状态无论通过可变借用的释放、[`release`](Self::release)、副本的丢弃还是其他方式结束，
+    /// 都会在释放写状态之前克隆一次，因此其他线程总是观察到一致的提交结果。
+    /// 接收端被丢弃后，下一次提交时自动停止发送。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let mut rc = RwRc::new(vec![1]);
+    /// let snapshots = rc.snapshot_channel();
+    /// let worker = std::thread::spawn(move || snapshots.iter().map(|v| v.len()).collect::<Vec<_>>());
+    ///
+    /// rc.write().push(2);
+    /// rc.write().push(3);
+    /// drop(rc);
+    /// assert_eq!(worker.join().unwrap(), [2, 3]);
+    /// ```
+    pub fn snapshot_channel(&self) -> Receiver<T> {
+        self.rc.check_thread();
+        let (sender, receiver) = mpsc::channel();
+        self.rc
+            .ensure_extras()
+            .committed
+            .push(move |val: &T| sender.send(val.clone()).is_ok());
+        receiver
+    }
+}
+
+#[test]
+fn test_snapshot_channel() {
+    let mut rc = RwRc::new(0);
+    let other = rc.clone();
+    other.release();
+    let snapshots = rc.snapshot_channel();
+    let stopped = rc.snapshot_channel();
+    drop(stopped);
+
+    // 从读状态升级的可变借用
+    *rc.write() = 1;
+    // 从持有状态获取的可变借用
+    rc.release();
+    *rc.write() = 2;
+    // 全局写状态在释放时提交
+    assert!(rc.try_write_global());
+    *rc.write() = 3;
+    *rc.write() = 4;
+    rc.release();
+    assert_eq!(snapshots.try_iter().collect::<Vec<_>>(), [1, 2, 4]);
+    // 已经断开的接收端被移除
+    assert_eq!(rc.rc.extras().unwrap().committed.0.borrow().len(), 1);
+
+    // 读取不会发送
+    drop(other.read());
+    assert!(snapshots.try_recv().is_err());
+}
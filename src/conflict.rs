@@ -0,0 +1,258 @@
+use crate::{Access, RwRc, RwState, policy::misuse};
+use std::fmt;
+
+/// 获取访问权限失败的原因，由 [`RwRc::conflicts`] 生成。
+///
+/// 只统计阻止此副本获取访问权限的因素，所有计数为零时获取会成功。
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct ConflictReport {
+    /// 请求的访问权限。
+    pub access: Access,
+    /// 持有读状态的其他副本数量。
+    pub readers: usize,
+    /// 是否有其他副本持有写状态。
+    pub writer: bool,
+    /// 登记了写意图的其他副本数量。
+    pub pending_writers: usize,
+    /// 是否被父对象的写状态阻止。
+    pub parent: bool,
+    /// 是否被读状态的数量上限阻止。
+    pub reader_limit: bool,
+}
+
+impl ConflictReport {
+    /// 判断是否没有任何冲突。
+    pub fn is_clear(&self) -> bool {
+        self.readers == 0
+            && !self.writer
+            && self.pending_writers == 0
+            && !self.parent
+            && !self.reader_limit
+    }
+}
+
+impl fmt::Display for ConflictReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = match self.access {
+            Access::Read => "read",
+            Access::Write => "write",
+        };
+        if self.is_clear() {
+            return write!(f, "{access} access is available");
+        }
+        write!(f, "{access} access is blocked by")?;
+        let mut sep = " ";
+        if self.readers > 0 {
+            write!(f, "{sep}{} reader(s)", self.readers)?;
+            sep = ", "
+        }
+        if self.writer {
+            write!(f, "{sep}a writer")?;
+            sep = ", "
+        }
+        if self.pending_writers > 0 {
+            write!(f, "{sep}{} pending writer(s)", self.pending_writers)?;
+            sep = ", "
+        }
+        if self.parent {
+            write!(f, "{sep}a write-locked parent")?;
+            sep = ", "
+        }
+        if self.reader_limit {
+            write!(f, "{sep}the reader limit")?
+        }
+        Ok(())
+    }
+}
+
+impl<T> RwRc<T> {
+    /// 描述此副本获取 `access` 时的冲突。
+    ///
+    /// 统计结果与 [`try_read_global`](Self::try_read_global) 和
+    /// [`try_write_global`](Self::try_write_global) 的判断一致，不修改任何状态。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{Access, RwRc};
+    ///
+    /// let mut rc = RwRc::new(0);
+    /// let _readers = [rc.clone(), rc.clone()];
+    /// let report = rc.conflicts(Access::Write);
+    /// assert_eq!(report.readers, 2);
+    /// assert_eq!(report.to_string(), "write access is blocked by 2 reader(s)");
+    /// ```
+    pub fn conflicts(&self, access: Access) -> ConflictReport {
+        self.rc.check_thread();
+        let flag = &self.rc.flag;
+        let mut report = ConflictReport {
+            access,
+            readers: 0,
+            writer: false,
+            pending_writers: 0,
+            parent: false,
+            reader_limit: false,
+        };
+        match (access, self.state.get()) {
+            (Access::Read, RwState::Hold) => {
+                report.writer = !flag.is_readable();
+                report.pending_writers = self.rc.pending_writers() - self.pending.get() as usize;
+                report.parent = flag.is_writeable() && !self.rc.can_enter_parent();
+                report.reader_limit = flag.is_readable() && self.rc.is_reader_limited()
+            }
+            (Access::Write, RwState::Hold) => {
+                report.readers = flag.readers();
+                report.writer = !flag.is_readable();
+                report.parent = flag.is_writeable() && !self.rc.can_enter_parent()
+            }
+            (Access::Write, RwState::Read | RwState::Upgradable) => {
+                report.readers = flag.readers() - 1
+            }
+            (Access::Read, _) | (Access::Write, RwState::Write) => {}
+        }
+        report
+    }
+
+    /// 设置到读状态，无法获取时 panic。
+    ///
+    /// # Panic
+    ///
+    /// 无法获取读状态时 panic，panic 信息包含 [`conflicts`](Self::conflicts) 描述的冲突。
+    ///
+    /// # 示例
+    ///
+    /// ```rust,should_panic
+    /// use rwrc::RwRc;
+    ///
+    /// let writer = RwRc::new(0);
+    /// let reader = writer.clone();
+    /// reader.release();
+    /// writer.write_global();
+    /// // panic: cannot set RwRc to read state: read access is blocked by a writer
+    /// reader.read_global();
+    /// ```
+    #[track_caller]
+    pub fn read_global(&self) {
+        if !self.try_read_global() {
+            misuse(format_args!(
+                "cannot set RwRc to read state: {}",
+                self.conflicts(Access::Read)
+            ))
+        }
+    }
+
+    /// 设置到写状态，无法获取时 panic。
+    ///
+    /// 获取失败时不保留写意图。
+    ///
+    /// # Panic
+    ///
+    /// 无法获取写状态时 panic，panic 信息包含 [`conflicts`](Self::conflicts) 描述的冲突。
+    #[track_caller]
+    pub fn write_global(&self) {
+        if !self.try_write_global() {
+            self.clear_intent();
+            misuse(format_args!(
+                "cannot set RwRc to write state: {}",
+                self.conflicts(Access::Write)
+            ))
+        }
+    }
+
+    /// 判断是否有其他副本持有读状态，不计入此副本自己的读状态。
+    pub fn is_read_by_others(&self) -> bool {
+        self.rc.check_thread();
+        let own = matches!(self.state.get(), RwState::Read | RwState::Upgradable) as usize;
+        self.rc.flag.readers() > own
+    }
+
+    /// 判断是否有其他副本持有写状态。
+    pub fn is_write_locked_elsewhere(&self) -> bool {
+        self.rc.check_thread();
+        !matches!(self.state.get(), RwState::Write) && !self.rc.flag.is_readable()
+    }
+}
+
+#[test]
+fn test_conflicts() {
+    use crate::Preference;
+
+    let writer = RwRc::with_preference(0, Preference::Write);
+    assert!(writer.conflicts(Access::Write).is_clear());
+
+    // 读状态下升级只受其他读者阻止
+    let reader = writer.clone();
+    let other = reader.clone();
+    assert_eq!(writer.conflicts(Access::Write).readers, 2);
+    drop(other);
+    writer.release();
+    assert!(!writer.try_write_global());
+    reader.release();
+    let report = reader.conflicts(Access::Read);
+    assert_eq!(report.pending_writers, 1);
+    assert_eq!(report.is_clear(), reader.try_read_global());
+
+    assert!(writer.try_write_global());
+    let report = reader.conflicts(Access::Read);
+    assert!(report.writer && report.pending_writers == 0);
+    assert_eq!(report.to_string(), "read access is blocked by a writer");
+    assert!(writer.conflicts(Access::Read).is_clear());
+}
+
+#[test]
+fn test_locked_by_others() {
+    let a = RwRc::new(0);
+    let b = a.clone();
+    assert!(a.is_read_by_others());
+    b.release();
+    // 只有自己持有读状态
+    assert!(!a.is_read_by_others());
+    assert!(b.is_read_by_others());
+
+    assert!(a.try_write_global());
+    assert!(!a.is_write_locked_elsewhere());
+    assert!(b.is_write_locked_elsewhere());
+    assert!(!b.is_read_by_others());
+}
+
+#[test]
+#[should_panic(expected = "cannot set RwRc to write state: write access is blocked by 1 reader(s)")]
+fn test_write_global_panics() {
+    let rc = RwRc::with_preference(0, crate::Preference::Write);
+    rc.read_global();
+    let _reader = rc.clone();
+    rc.write_global();
+}
+
+#[test]
+fn test_reader_limit() {
+    use crate::LockError;
+
+    let rc = RwRc::new(0);
+    rc.set_max_readers(Some(2));
+    assert_eq!(rc.max_readers(), Some(2));
+    let a = rc.clone();
+    // 达到上限后克隆的副本处于持有状态
+    let b = a.clone();
+    assert_eq!(b.state(), RwState::Hold);
+    assert!(!b.is_readable());
+    assert!(!b.try_read_global());
+    let report = b.conflicts(Access::Read);
+    assert!(report.reader_limit);
+    assert_eq!(
+        report.to_string(),
+        "read access is blocked by the reader limit"
+    );
+    assert_eq!(b.read_error(), LockError::ReaderLimit);
+    assert_eq!(LockError::ReaderLimit.access(), Access::Read);
+
+    // 有读者释放后可以获取
+    a.release();
+    assert!(b.try_read_global());
+    b.release();
+    rc.set_max_readers(None);
+    assert_eq!(rc.max_readers(), None);
+    assert!(a.try_read_global() && b.try_read_global());
+}
@@ -0,0 +1,180 @@
+use crate::{RwRc, policy::misuse};
+use std::{cell::RefCell, collections::HashMap, fmt::Write, task::Waker};
+
+/// 等待中的任务。
+struct Waiting {
+    /// 等待的任务。
+    task: usize,
+    /// 等待的共享对象的标识。
+    object: usize,
+    /// 等待的共享对象的类型名。
+    type_name: &'static str,
+}
+
+thread_local! {
+    /// 当前线程上等待共享对象的任务。
+    static WAITING: RefCell<Vec<Waiting>> = const { RefCell::new(Vec::new()) };
+    /// 当前线程上通过异步获取持有共享对象的任务，以共享对象的标识为键。
+    static HOLDERS: RefCell<HashMap<usize, Vec<usize>>> = RefCell::new(HashMap::new());
+}
+
+/// 以唤醒器的数据指针标识任务，无法区分任务的唤醒器（如 [`Waker::noop`]）返回 `None`。
+pub(super) fn task_of(waker: &Waker) -> Option<usize> {
+    Some(waker.data() as usize).filter(|&task| task != 0)
+}
+
+impl<T> RwRc<T> {
+    /// 记录此副本通过异步获取由 `waker` 所在的任务持有。
+    pub(super) fn hold_by(&self, waker: &Waker) {
+        let Some(task) = task_of(waker) else { return };
+        if self.task.replace(Some(task)).is_none() {
+            HOLDERS.with_borrow_mut(|holders| holders.entry(self.id()).or_default().push(task))
+        }
+    }
+
+    /// 副本回到持有状态时撤销任务的持有记录。
+    pub(super) fn unhold(&self) {
+        let Some(task) = self.task.take() else { return };
+        let id = self.id();
+        HOLDERS.with_borrow_mut(|holders| {
+            let tasks = holders.get_mut(&id).unwrap();
+            let i = tasks.iter().position(|&t| t == task).unwrap();
+            tasks.swap_remove(i);
+            if tasks.is_empty() {
+                holders.remove(&id);
+            }
+        })
+    }
+
+    /// 登记 `task` 开始等待此共享对象，形成等待环时按违反使用约定处理。
+    #[track_caller]
+    pub(super) fn wait_by(&self, task: usize) {
+        let object = self.id();
+        WAITING.with_borrow_mut(|waiting| {
+            waiting.push(Waiting {
+                task,
+                object,
+                type_name: std::any::type_name::<T>(),
+            })
+        });
+        if let Some(report) = find_cycle(task) {
+            misuse(format_args!("deadlock detected: {report}"))
+        }
+    }
+}
+
+/// 撤销 `task` 对共享对象 `object` 的等待。
+pub(super) fn stop_waiting(task: usize, object: usize) {
+    WAITING.with_borrow_mut(|waiting| {
+        if let Some(i) = waiting
+            .iter()
+            .position(|w| w.task == task && w.object == object)
+        {
+            waiting.swap_remove(i);
+        }
+    })
+}
+
+/// 从 `start` 出发沿“等待的对象被哪些任务持有”查找回到 `start` 的环，返回环的描述。
+fn find_cycle(start: usize) -> Option<String> {
+    WAITING.with_borrow(|waiting| {
+        HOLDERS.with_borrow(|holders| {
+            // 深度优先搜索，路径上是依次等待的条目
+            let mut path = Vec::<&Waiting>::new();
+            let mut visited = Vec::new();
+            let mut stack = waiting
+                .iter()
+                .filter(|w| w.task == start)
+                .map(|w| (0, w))
+                .collect::<Vec<_>>();
+            while let Some((depth, edge)) = stack.pop() {
+                path.truncate(depth);
+                path.push(edge);
+                for &holder in holders.get(&edge.object).into_iter().flatten() {
+                    if holder == start {
+                        return Some(describe(&path));
+                    }
+                    if visited.contains(&holder) {
+                        continue;
+                    }
+                    visited.push(holder);
+                    stack.extend(
+                        waiting
+                            .iter()
+                            .filter(|w| w.task == holder)
+                            .map(|w| (depth + 1, w)),
+                    )
+                }
+            }
+            None
+        })
+    })
+}
+
+/// 描述等待环，每个任务持有前一个任务等待的对象。
+fn describe(path: &[&Waiting]) -> String {
+    let mut report = String::new();
+    for (i, edge) in path.iter().enumerate() {
+        let held = path[(i + path.len() - 1) % path.len()];
+        if i > 0 {
+            report.push_str("; ")
+        }
+        write!(
+            report,
+            "task {:#x} holds {} at {:#x} and waits for {} at {:#x}",
+            edge.task, held.type_name, held.object, edge.type_name, edge.object
+        )
+        .unwrap()
+    }
+    report
+}
+
+#[test]
+fn test_deadlock_detection() {
+    use std::{
+        pin::pin,
+        sync::Arc,
+        task::{Context, Wake},
+    };
+
+    struct Task;
+    impl Wake for Task {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let a = Waker::from(Arc::new(Task));
+    let b = Waker::from(Arc::new(Task));
+    let (mut cx_a, mut cx_b) = (Context::from_waker(&a), Context::from_waker(&b));
+
+    let mut x_a = RwRc::new(1);
+    let mut y_a = RwRc::new("y");
+    let mut x_b = x_a.clone();
+    let mut y_b = y_a.clone();
+    for rc in [&x_a, &x_b] {
+        rc.release()
+    }
+    for rc in [&y_a, &y_b] {
+        rc.release()
+    }
+
+    // 任务 A 持有 X，任务 B 持有 Y
+    assert!(pin!(x_a.write_async()).poll(&mut cx_a).is_ready());
+    assert!(pin!(y_b.write_async()).poll(&mut cx_b).is_ready());
+    {
+        // A 等待 Y
+        let mut wait_y = pin!(y_a.read_async());
+        assert!(wait_y.as_mut().poll(&mut cx_a).is_pending());
+        // B 等待 X 时形成环
+        let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = pin!(x_b.read_async()).poll(&mut cx_b);
+        }))
+        .unwrap_err();
+        let msg = err.downcast_ref::<String>().unwrap();
+        assert!(msg.starts_with("deadlock detected: task"), "{msg}");
+        assert!(msg.contains("; task"));
+    }
+    // 释放后不再有持有和等待的记录
+    drop((x_a, y_b));
+    assert!(HOLDERS.with_borrow(HashMap::is_empty));
+    assert!(WAITING.with_borrow(Vec::is_empty));
+}
@@ -0,0 +1,138 @@
+//! 调试共享对象之间的引用关系。
+
+#[cfg(feature = "audit")]
+pub use crate::audit::{Discrepancy, audit};
+
+use crate::{
+    Trace,
+    trace::{Edge, Node, Tracer, node_id},
+};
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Write,
+    rc::Weak,
+};
+
+/// 以 Graphviz 的 DOT 格式渲染从 `roots` 可达的共享对象图。
+///
+/// 每个存活的共享对象是一个节点，标签是对象的类型和当前的锁定状态；
+/// 强引用渲染为实线，弱引用渲染为虚线，`roots` 直接报告的引用从 `roots` 节点出发。
+/// 节点名是共享对象标识（即 [`RwRc::id`](crate::RwRc::id)）。
+/// 被写锁定的对象无法读取，不会渲染它的引用。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, RwWeak, Trace, Tracer, debug};
+///
+/// struct Node(Vec<RwRc<Node>>, Option<RwWeak<Node>>);
+///
+/// impl Trace for Node {
+///     fn trace(&self, tracer: &mut Tracer) {
+///         self.0.trace(tracer);
+///         self.1.trace(tracer)
+///     }
+/// }
+///
+/// let leaf = RwRc::new(Node(vec![], None));
+/// let root = RwRc::new(Node(vec![leaf.clone()], Some(leaf.weak())));
+/// let dot = debug::to_dot(&root);
+/// assert!(dot.starts_with("digraph rwrc {"));
+/// assert_eq!(dot.matches("style=dashed").count(), 1);
+/// ```
+pub fn to_dot(roots: &(impl Trace + ?Sized)) -> String {
+    let mut tracer = Tracer::default();
+    roots.trace(&mut tracer);
+
+    let mut dot = String::from("digraph rwrc {\n    roots [shape=point];\n");
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    edges(&mut dot, "roots", tracer.edges, &mut visited, &mut queue);
+    while let Some(weak) = queue.pop_front() {
+        let Some(node) = weak.upgrade() else { continue };
+        let name = format!("n{}", node_id(&weak));
+        let flag = node.flag();
+        let state = if !flag.is_readable() {
+            "write".to_string()
+        } else if flag.readers() > 0 {
+            format!("read x{}", flag.readers())
+        } else {
+            "hold".to_string()
+        };
+        writeln!(
+            dot,
+            "    {name} [label=\"{}\\n{state}\"];",
+            escape(node.type_name())
+        )
+        .unwrap();
+        let mut tracer = Tracer::default();
+        node.trace(&mut tracer);
+        edges(&mut dot, &name, tracer.edges, &mut visited, &mut queue)
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// 渲染从 `from` 出发的引用，并登记还没有访问过的共享对象。
+fn edges(
+    dot: &mut String,
+    from: &str,
+    edges: Vec<Edge>,
+    visited: &mut HashSet<usize>,
+    queue: &mut VecDeque<Weak<dyn Node>>,
+) {
+    for edge in edges {
+        // 已经释放的弱引用不渲染
+        if edge.node.strong_count() == 0 {
+            continue;
+        }
+        let id = node_id(&edge.node);
+        let style = if edge.strong { "" } else { " [style=dashed]" };
+        writeln!(dot, "    {from} -> n{id}{style};").unwrap();
+        if visited.insert(id) {
+            queue.push_back(edge.node)
+        }
+    }
+}
+
+/// 转义 DOT 字符串中的特殊字符。
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[test]
+fn test_to_dot() {
+    use crate::{RwRc, RwWeak};
+
+    struct Node(Vec<RwRc<Node>>, Option<RwWeak<Node>>);
+
+    impl Trace for Node {
+        fn trace(&self, tracer: &mut Tracer) {
+            self.0.trace(tracer);
+            self.1.trace(tracer)
+        }
+    }
+
+    let mut leaf = RwRc::new(Node(vec![], None));
+    leaf.release();
+    let root = RwRc::new(Node(vec![leaf.clone()], None));
+    leaf.write().1 = Some(root.weak());
+    root.release();
+    assert!(leaf.try_read_global());
+    let (l, r) = (leaf.id(), root.id());
+
+    let dot = to_dot(&root);
+    let lines = dot.lines().collect::<Vec<_>>();
+    assert!(lines.contains(&format!("    roots -> n{r};").as_str()));
+    assert!(lines.contains(&format!("    n{r} -> n{l};").as_str()));
+    assert!(lines.contains(&format!("    n{l} -> n{r} [style=dashed];").as_str()));
+    assert!(dot.contains("\\nread x1\"];"));
+    assert!(dot.contains("\\nhold\"];"));
+    assert_eq!(dot.matches("label=").count(), 2);
+
+    // 被写锁定的对象没有出边
+    assert!(root.try_write_global());
+    let dot = to_dot(&root);
+    assert!(dot.contains("\\nwrite\"];"));
+    assert!(!dot.contains(&format!("n{r} -> ")));
+}
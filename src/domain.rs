@@ -0,0 +1,181 @@
+use crate::{RwRc, RwState, RwWeak};
+use std::{cell::Cell, rc::Rc};
+
+/// 统一控制一组成员的读写状态。
+///
+/// 锁域本身是一个共享读写状态的副本，语义与 [`RwRc`] 相同。
+/// 锁域的成员 [`DomainRc<T>`] 没有自己的读写状态，
+/// 通过处于读状态的锁域副本可以读取所有成员，通过处于写状态的锁域副本可以修改所有成员。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{DomainRc, LockDomain};
+///
+/// let mut domain = LockDomain::new();
+/// let a = DomainRc::new(&domain, 1);
+/// let b = DomainRc::new(&domain, 2);
+///
+/// let mut other = domain.clone();
+/// assert_eq!(*a.get(&other).unwrap() + *b.get(&other).unwrap(), 3);
+///
+/// // 其他副本持有读状态时无法获取写状态
+/// assert!(!domain.try_write_global());
+/// other.release();
+/// assert!(domain.try_write_global());
+/// *a.get_mut(&mut domain).unwrap() += 10;
+/// *b.get_mut(&mut domain).unwrap() += 10;
+/// assert!(a.get(&other).is_none());
+/// ```
+pub struct LockDomain(RwRc<()>);
+
+/// 锁域的成员。
+///
+/// 成员可以克隆，所有克隆共享同一个对象。
+pub struct DomainRc<T>(Rc<Member<T>>);
+
+/// 锁域成员共享的对象。
+struct Member<T> {
+    /// 所属的锁域。
+    domain: RwWeak<()>,
+    /// 共享对象。
+    val: Cell<T>,
+}
+
+impl Default for LockDomain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for LockDomain {
+    /// 只有当源副本在读状态时，克隆的副本才会设置读状态，否则设置为持有状态。
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl LockDomain {
+    /// 创建锁域，初始为读状态。
+    pub fn new() -> Self {
+        Self(RwRc::new(()))
+    }
+
+    /// 判断是否可以读取成员。
+    pub fn is_readable(&self) -> bool {
+        self.0.is_readable()
+    }
+
+    /// 判断是否可以修改成员。
+    pub fn is_writeable(&self) -> bool {
+        self.0.is_writeable()
+    }
+
+    /// 尝试设置到读状态。
+    pub fn try_read_global(&mut self) -> bool {
+        self.0.try_read_global()
+    }
+
+    /// 尝试设置到写状态。
+    pub fn try_write_global(&mut self) -> bool {
+        self.0.try_write_global()
+    }
+
+    /// 释放读写状态。
+    pub fn release(&mut self) {
+        self.0.release()
+    }
+
+    /// 检查 `member` 是否属于此锁域。
+    fn check<T>(&self, member: &DomainRc<T>) {
+        if !std::ptr::eq(member.0.domain.0.as_ptr(), Rc::as_ptr(&self.0.rc)) {
+            crate::policy::misuse(format_args!(
+                "DomainRc accessed through a foreign LockDomain"
+            ))
+        }
+    }
+}
+
+impl<T> Clone for DomainRc<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> DomainRc<T> {
+    /// 在 `domain` 所在的锁域中创建成员。
+    pub fn new(domain: &LockDomain, val: T) -> Self {
+        Self(Rc::new(Member {
+            domain: domain.0.weak(),
+            val: Cell::new(val),
+        }))
+    }
+
+    /// 通过锁域副本读取成员，副本不在读状态或写状态时返回 `None`。
+    ///
+    /// # Panic
+    ///
+    /// `domain` 不是成员所属的锁域时 panic。
+    pub fn get<'a>(&'a self, domain: &'a LockDomain) -> Option<&'a T> {
+        domain.check(self);
+        match domain.0.state.get() {
+            RwState::Hold => None,
+            // 锁域副本被共享借用期间状态不变：读状态保证没有副本在写入，
+            // 写状态下修改成员需要可变借用锁域副本
+            RwState::Read | RwState::Upgradable | RwState::Write => {
+                Some(unsafe { &*self.0.val.as_ptr() })
+            }
+        }
+    }
+
+    /// 通过锁域副本修改成员，副本不在写状态时返回 `None`。
+    ///
+    /// # Panic
+    ///
+    /// `domain` 不是成员所属的锁域时 panic。
+    pub fn get_mut<'a>(&'a self, domain: &'a mut LockDomain) -> Option<&'a mut T> {
+        domain.check(self);
+        match domain.0.state.get() {
+            RwState::Hold | RwState::Read | RwState::Upgradable => None,
+            // 写状态保证其他锁域副本无法访问；锁域副本被可变借用期间，不存在其他引用
+            RwState::Write => Some(unsafe { &mut *self.0.val.as_ptr() }),
+        }
+    }
+}
+
+#[test]
+fn test_lock_domain() {
+    let mut domain = LockDomain::new();
+    let members = (0..4)
+        .map(|i| DomainRc::new(&domain, i))
+        .collect::<Vec<_>>();
+    let mut other = domain.clone();
+    domain.release();
+    assert!(members[0].get(&domain).is_none());
+    assert_eq!(
+        members.iter().map(|m| *m.get(&other).unwrap()).sum::<i32>(),
+        6
+    );
+
+    // 写状态下修改所有成员
+    assert!(!domain.try_write_global());
+    other.release();
+    assert!(domain.try_write_global());
+    for m in &members {
+        *m.get_mut(&mut domain).unwrap() *= 2
+    }
+    assert!(!other.try_read_global());
+    assert!(members[1].clone().get_mut(&mut other).is_none());
+
+    domain.release();
+    assert!(other.try_read_global());
+    assert_eq!(*members[3].get(&other).unwrap(), 6);
+}
+
+#[test]
+#[should_panic(expected = "foreign LockDomain")]
+fn test_lock_domain_foreign() {
+    let domain = LockDomain::new();
+    let member = DomainRc::new(&domain, 0);
+    let _ = member.get(&LockDomain::new());
+}
@@ -0,0 +1,80 @@
+use crate::{LocalMut, LockError, RwRc};
+
+/// 由两个共享对象组成的双缓冲。
+///
+/// 读者通过 [`front`](Self::front) 获取前台缓冲区的副本，可以长期保存并按需读取；
+/// 生产者通过 [`back`](Self::back) 修改只有双缓冲自己持有的后台缓冲区，不受读者影响。
+/// [`swap`](Self::swap) 交换两个缓冲区的内容，读者的副本之后读到新的内容。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::DoubleBuffer;
+///
+/// let mut frames = DoubleBuffer::new(vec![0; 4], vec![0; 4]);
+/// let mut reader = frames.front();
+///
+/// frames.back().fill(1);
+/// // 读者持有读状态时无法交换
+/// assert!(reader.try_read_global());
+/// assert!(frames.swap().is_err());
+/// reader.release();
+///
+/// frames.swap().unwrap();
+/// assert_eq!(*reader.read(), [1; 4]);
+/// ```
+pub struct DoubleBuffer<T> {
+    /// 读者可见的前台缓冲区。
+    front: RwRc<T>,
+    /// 生产者写入的后台缓冲区。
+    back: RwRc<T>,
+}
+
+impl<T> DoubleBuffer<T> {
+    /// 以前台和后台缓冲区的初始内容创建双缓冲。
+    pub fn new(front: T, back: T) -> Self {
+        let front = RwRc::new(front);
+        let back = RwRc::new(back);
+        front.release();
+        back.release();
+        Self { front, back }
+    }
+
+    /// 获取前台缓冲区的副本，初始为持有状态。
+    pub fn front(&self) -> RwRc<T> {
+        self.front.clone()
+    }
+
+    /// 可变借用后台缓冲区。
+    pub fn back(&mut self) -> LocalMut<'_, T> {
+        // 后台缓冲区只有这一个副本，总是可以获取写状态
+        self.back.write()
+    }
+
+    /// 交换前台和后台缓冲区的内容。
+    ///
+    /// 有读者持有前台缓冲区的读写状态时返回错误，两个缓冲区保持不变。
+    pub fn swap(&mut self) -> Result<(), LockError> {
+        self.front.swap_contents(&mut self.back)
+    }
+}
+
+#[test]
+fn test_double_buffer() {
+    let mut buf = DoubleBuffer::new(String::from("a"), String::new());
+    let r1 = buf.front();
+    let r2 = buf.front();
+    buf.back().push('b');
+
+    let guard = r2.read();
+    assert!(buf.swap().is_err());
+    assert_eq!(*guard, "a");
+    drop(guard);
+    buf.swap().unwrap();
+    assert_eq!(*r1.read(), "b");
+    assert_eq!(*buf.back(), "a");
+
+    // 写状态的副本同样阻止交换
+    assert!(r1.try_write_global());
+    assert!(buf.swap().is_err());
+}
@@ -0,0 +1,37 @@
+use crate::Access;
+use std::{error::Error, fmt};
+
+/// 无法获取访问权限的错误。
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum LockError {
+    /// 其他副本持有的读写状态阻止了请求的访问。
+    Blocked(Access),
+    /// 持有读状态的副本数量达到了共享对象的上限。
+    ReaderLimit,
+}
+
+impl LockError {
+    /// 请求的访问权限。
+    pub fn access(&self) -> Access {
+        match self {
+            Self::Blocked(access) => *access,
+            Self::ReaderLimit => Access::Read,
+        }
+    }
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Blocked(Access::Read) => write!(f, "read access is blocked by a writer"),
+            Self::Blocked(Access::Write) => {
+                write!(f, "write access is blocked by other readers or a writer")
+            }
+            Self::ReaderLimit => write!(f, "read access is blocked by the reader limit"),
+        }
+    }
+}
+
+impl Error for LockError {}
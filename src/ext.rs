@@ -0,0 +1,322 @@
+use crate::{Access, LocalMut, LocalRef, LockError, MappedLocalMut, MappedLocalRef, RwRc};
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
+/// [`RwRc<String>`] 的便捷方法。
+///
+/// 每个方法自行获取需要的访问权限，无法获取时返回 [`LockError`]。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{Access, LockError, RwRc, RwRcStringExt};
+///
+/// let mut log = RwRc::new(String::new());
+/// log.push_str("hello").unwrap();
+///
+/// let reader = log.clone();
+/// assert_eq!(&*reader.as_str_read().unwrap(), "hello");
+/// assert_eq!(log.push_str("!"), Err(LockError::Blocked(Access::Write)));
+/// ```
+pub trait RwRcStringExt {
+    /// 以 `str` 读取共享字符串。
+    fn as_str_read(&self) -> Result<MappedLocalRef<'_, str>, LockError>;
+
+    /// 在共享字符串末尾追加 `s`。
+    fn push_str(&mut self, s: &str) -> Result<(), LockError>;
+
+    /// 清空共享字符串。
+    fn clear(&mut self) -> Result<(), LockError>;
+}
+
+/// [`RwRc<Vec<u8>>`] 的便捷方法。
+///
+/// 每个方法自行获取需要的访问权限，无法获取时返回 [`LockError`]。
+pub trait RwRcBytesExt {
+    /// 以切片读取共享字节缓冲区。
+    fn as_slice_read(&self) -> Result<MappedLocalRef<'_, [u8]>, LockError>;
+
+    /// 在共享字节缓冲区末尾追加 `bytes`。
+    fn extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), LockError>;
+
+    /// 清空共享字节缓冲区。
+    fn clear(&mut self) -> Result<(), LockError>;
+}
+
+/// [`RwRc<Vec<T>>`] 的便捷方法。
+///
+/// 每个方法自行获取需要的访问权限，无法获取时返回 [`LockError`]。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, RwRcVecExt};
+///
+/// let mut queue = RwRc::new(Vec::new());
+/// queue.push(1).unwrap();
+/// queue.push(2).unwrap();
+/// assert_eq!(queue.pop(), Ok(Some(2)));
+/// assert_eq!(queue.len_read(), Ok(1));
+/// ```
+pub trait RwRcVecExt<T> {
+    /// 在共享向量末尾追加 `val`。
+    fn push(&mut self, val: T) -> Result<(), LockError>;
+
+    /// 移出共享向量的最后一个元素。
+    fn pop(&mut self) -> Result<Option<T>, LockError>;
+
+    /// 读取共享向量的长度。
+    fn len_read(&self) -> Result<usize, LockError>;
+}
+
+/// [`RwRc<HashMap<K, V>>`] 的便捷方法。
+///
+/// 每个方法自行获取需要的访问权限，无法获取时返回 [`LockError`]。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, RwRcMapExt};
+/// use std::collections::HashMap;
+///
+/// let mut map = RwRc::new(HashMap::new());
+/// assert_eq!(map.insert("a", 1), Ok(None));
+/// assert_eq!(map.contains_key_read("a"), Ok(true));
+/// assert_eq!(map.remove("a"), Ok(Some(1)));
+/// ```
+pub trait RwRcMapExt<K, V, S> {
+    /// 在共享映射中插入 `key` 和 `val`，返回原来的值。
+    fn insert(&mut self, key: K, val: V) -> Result<Option<V>, LockError>;
+
+    /// 从共享映射中移出 `key` 对应的值。
+    fn remove<Q>(&mut self, key: &Q) -> Result<Option<V>, LockError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized;
+
+    /// 判断共享映射是否包含 `key`。
+    fn contains_key_read<Q>(&self, key: &Q) -> Result<bool, LockError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized;
+
+    /// 获取共享映射的写状态，返回 `key` 对应的条目。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{RwRc, RwRcMapExt};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut counts = RwRc::new(HashMap::new());
+    /// for word in ["a", "b", "a"] {
+    ///     *counts.entry_write(word).unwrap().or_insert(0) += 1;
+    /// }
+    /// let n = counts.entry_write("a").unwrap().and_modify(|n| *n *= 10).or_default();
+    /// assert_eq!(*n, 20);
+    /// ```
+    fn entry_write(&mut self, key: K) -> Result<MapEntry<'_, K, V, S>, LockError>;
+}
+
+/// 共享映射中的一个条目，由 [`RwRcMapExt::entry_write`] 创建。
+///
+/// 条目存在期间占用共享映射的可变借用，取得值的引用后只保留映射到值的借用。
+pub struct MapEntry<'a, K, V, S> {
+    /// 共享映射的可变借用。
+    map: LocalMut<'a, HashMap<K, V, S>>,
+    /// 条目的键。
+    key: K,
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> MapEntry<'a, K, V, S> {
+    /// 条目的键。
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// 条目存在时以 `f` 修改它的值。
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        if let Some(val) = self.map.get_mut(&self.key) {
+            f(val)
+        }
+        self
+    }
+
+    /// 条目不存在时插入 `default`，返回值的可变借用。
+    pub fn or_insert(self, default: V) -> MappedLocalMut<'a, V> {
+        self.or_insert_with(|| default)
+    }
+
+    /// 条目不存在时插入 `f` 的结果，返回值的可变借用。
+    pub fn or_insert_with(self, f: impl FnOnce() -> V) -> MappedLocalMut<'a, V> {
+        let Self { map, key } = self;
+        LocalMut::map(map, |map| map.entry(key).or_insert_with(f))
+    }
+
+    /// 条目不存在时插入默认值，返回值的可变借用。
+    pub fn or_default(self) -> MappedLocalMut<'a, V>
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl RwRcStringExt for RwRc<String> {
+    fn as_str_read(&self) -> Result<MappedLocalRef<'_, str>, LockError> {
+        read(self, String::as_str)
+    }
+
+    fn push_str(&mut self, s: &str) -> Result<(), LockError> {
+        write(self, |buf| buf.push_str(s))
+    }
+
+    fn clear(&mut self) -> Result<(), LockError> {
+        write(self, String::clear)
+    }
+}
+
+impl RwRcBytesExt for RwRc<Vec<u8>> {
+    fn as_slice_read(&self) -> Result<MappedLocalRef<'_, [u8]>, LockError> {
+        read(self, Vec::as_slice)
+    }
+
+    fn extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), LockError> {
+        write(self, |buf| buf.extend_from_slice(bytes))
+    }
+
+    fn clear(&mut self) -> Result<(), LockError> {
+        write(self, Vec::clear)
+    }
+}
+
+impl<T> RwRcVecExt<T> for RwRc<Vec<T>> {
+    fn push(&mut self, val: T) -> Result<(), LockError> {
+        write(self, |vec| vec.push(val))
+    }
+
+    fn pop(&mut self) -> Result<Option<T>, LockError> {
+        write(self, Vec::pop)
+    }
+
+    fn len_read(&self) -> Result<usize, LockError> {
+        read(self, |vec| vec).map(|vec| vec.len())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> RwRcMapExt<K, V, S> for RwRc<HashMap<K, V, S>> {
+    fn insert(&mut self, key: K, val: V) -> Result<Option<V>, LockError> {
+        write(self, |map| map.insert(key, val))
+    }
+
+    fn remove<Q>(&mut self, key: &Q) -> Result<Option<V>, LockError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        write(self, |map| map.remove(key))
+    }
+
+    fn contains_key_read<Q>(&self, key: &Q) -> Result<bool, LockError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        read(self, |map| map).map(|map| map.contains_key(key))
+    }
+
+    fn entry_write(&mut self, key: K) -> Result<MapEntry<'_, K, V, S>, LockError> {
+        let map = self.try_write().ok_or(LockError::Blocked(Access::Write))?;
+        Ok(MapEntry { map, key })
+    }
+}
+
+fn read<T, U: ?Sized>(
+    rc: &RwRc<T>,
+    f: impl FnOnce(&T) -> &U,
+) -> Result<MappedLocalRef<'_, U>, LockError> {
+    rc.try_read()
+        .map(|r| LocalRef::map(r, f))
+        .ok_or_else(|| rc.read_error())
+}
+
+fn write<T, R>(rc: &mut RwRc<T>, f: impl FnOnce(&mut T) -> R) -> Result<R, LockError> {
+    rc.try_write()
+        .map(|mut w| f(&mut w))
+        .ok_or(LockError::Blocked(Access::Write))
+}
+
+#[test]
+fn test_string_ext() {
+    let mut rc = RwRc::new(String::from("ab"));
+    rc.push_str("cd").unwrap();
+    assert_eq!(&*rc.as_str_read().unwrap(), "abcd");
+
+    let mut other = rc.clone();
+    assert_eq!(other.clear(), Err(LockError::Blocked(Access::Write)));
+    rc.release();
+    RwRcStringExt::clear(&mut other).unwrap();
+    assert!(other.try_write_global());
+    assert_eq!(
+        rc.as_str_read().err(),
+        Some(LockError::Blocked(Access::Read))
+    );
+}
+
+#[test]
+fn test_bytes_ext() {
+    let mut rc = RwRc::new(vec![1u8]);
+    rc.extend_from_slice(&[2, 3]).unwrap();
+    assert_eq!(&*rc.as_slice_read().unwrap(), [1, 2, 3]);
+    RwRcBytesExt::clear(&mut rc).unwrap();
+    assert!(rc.as_slice_read().unwrap().is_empty());
+}
+
+#[test]
+fn test_vec_ext() {
+    let mut rc = RwRc::new(vec![1]);
+    rc.push(2).unwrap();
+    let reader = rc.clone();
+    assert_eq!(reader.len_read(), Ok(2));
+    assert_eq!(rc.pop(), Err(LockError::Blocked(Access::Write)));
+    drop(reader);
+    assert_eq!(rc.pop(), Ok(Some(2)));
+}
+
+#[test]
+fn test_map_ext() {
+    let mut rc = RwRc::new(HashMap::new());
+    assert_eq!(rc.insert(String::from("a"), 1), Ok(None));
+    assert_eq!(rc.insert(String::from("a"), 2), Ok(Some(1)));
+    assert_eq!(rc.contains_key_read("a"), Ok(true));
+
+    let writer = rc.clone();
+    rc.release();
+    assert!(writer.try_write_global());
+    assert_eq!(
+        rc.contains_key_read("a"),
+        Err(LockError::Blocked(Access::Read))
+    );
+    writer.release();
+    assert_eq!(rc.remove("a"), Ok(Some(2)));
+}
+
+#[test]
+fn test_map_entry() {
+    let mut rc = RwRc::new(HashMap::new());
+    let mut v = rc.entry_write("a").unwrap().or_insert_with(Vec::new);
+    v.push(1);
+    drop(v);
+    let entry = rc.entry_write("a").unwrap();
+    assert_eq!(*entry.key(), "a");
+    entry.and_modify(|v| v.push(2)).or_default().push(3);
+    assert_eq!(rc.read()["a"], [1, 2, 3]);
+
+    let reader = rc.clone();
+    assert!(rc.entry_write("b").is_err());
+    drop(reader);
+    assert!(rc.entry_write("b").unwrap().or_default().is_empty());
+}
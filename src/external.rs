@@ -0,0 +1,110 @@
+use crate::{LocalMut, LocalRef, MappedLocalMut, MappedLocalRef, RwRc};
+use std::ptr::NonNull;
+
+/// 指向用户管理的内存的句柄。
+///
+/// 以 [`RwRc<S>`] 共享句柄时，共享读写状态保护的是句柄本身，
+/// [`read_external`](RwRc::read_external) 和 [`write_external`](RwRc::write_external)
+/// 在相应的读写状态下借用句柄指向的外部内存（竞技场槽位、共享内存段、锁页的主机缓冲区等）。
+///
+/// 只有被指向的对象位于外部内存中，
+/// 句柄、共享读写状态和引用计数仍然与其他共享对象一样分配在堆上，
+/// 共享对象的存储方式不可替换。
+///
+/// # Safety
+///
+/// [`as_ptr`](Self::as_ptr) 返回的指针在句柄存活期间必须始终指向同一个有效且对齐的对象，
+/// 并且这块内存只能通过这个句柄访问，副本的读写状态才能保护它。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, External};
+/// use std::ptr::NonNull;
+///
+/// /// 从用户管理的缓冲区中划出的一段。
+/// struct Region(NonNull<[u8]>);
+///
+/// unsafe impl External for Region {
+///     type Target = [u8];
+///
+///     fn as_ptr(&self) -> NonNull<[u8]> {
+///         self.0
+///     }
+/// }
+///
+/// let pool: &'static mut [u8] = Box::leak(vec![0u8; 64].into_boxed_slice());
+/// let mut rc = RwRc::new(Region(NonNull::from(&mut pool[..16])));
+/// rc.write_external().fill(7);
+/// assert_eq!(rc.read_external()[15], 7);
+/// ```
+pub unsafe trait External {
+    /// 句柄指向的对象类型。
+    type Target: ?Sized;
+
+    /// 句柄指向的对象。
+    fn as_ptr(&self) -> NonNull<Self::Target>;
+}
+
+impl<S: External> RwRc<S> {
+    /// 尝试借用句柄指向的对象，无法获取读状态时返回 `None`。
+    pub fn try_read_external(&self) -> Option<MappedLocalRef<'_, S::Target>> {
+        // 读状态下没有其他副本能可变借用这块内存
+        self.try_read()
+            .map(|r| LocalRef::map(r, |s| unsafe { s.as_ptr().as_ref() }))
+    }
+
+    /// 尝试可变借用句柄指向的对象，无法获取写状态时返回 `None`。
+    pub fn try_write_external(&mut self) -> Option<MappedLocalMut<'_, S::Target>> {
+        // 写状态下这块内存只能通过此副本访问
+        self.try_write()
+            .map(|w| LocalMut::map(w, |s| unsafe { s.as_ptr().as_mut() }))
+    }
+
+    /// 借用句柄指向的对象。
+    ///
+    /// # Panic
+    ///
+    /// 无法获取读状态时 panic。
+    #[track_caller]
+    pub fn read_external(&self) -> MappedLocalRef<'_, S::Target> {
+        self.try_read_external().unwrap()
+    }
+
+    /// 可变借用句柄指向的对象。
+    ///
+    /// # Panic
+    ///
+    /// 无法获取写状态时 panic。
+    #[track_caller]
+    pub fn write_external(&mut self) -> MappedLocalMut<'_, S::Target> {
+        self.try_write_external().unwrap()
+    }
+}
+
+#[test]
+fn test_external() {
+    struct Slot(NonNull<u32>);
+
+    unsafe impl External for Slot {
+        type Target = u32;
+
+        fn as_ptr(&self) -> NonNull<u32> {
+            self.0
+        }
+    }
+
+    let mut slots = [0u32; 2];
+    let [a, b] = &mut slots;
+    let mut first = RwRc::new(Slot(NonNull::from(a)));
+    let second = RwRc::new(Slot(NonNull::from(b)));
+    *first.write_external() = 3;
+    let reader = first.clone();
+    assert!(first.try_write_external().is_none());
+    assert_eq!(*reader.read_external(), 3);
+    drop(reader);
+    *first.write_external() += 1;
+    assert_eq!(*second.read_external(), 0);
+    drop((first, second));
+    assert_eq!(slots, [4, 0]);
+}
@@ -0,0 +1,81 @@
+use crate::{
+    Internal, commit, finalize::Finalizer, hierarchy::LockLink, notify::Releasable, wait::Waiter,
+    weak,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    rc::Weak,
+};
+
+/// 共享对象上不常用的状态，第一次使用时才分配。
+///
+/// 只使用基本读写操作的共享对象不需要这些状态，
+/// 集中保存在单独的分配中可以使每个共享对象的分配只多出一个指针。
+pub(super) struct Extras<T> {
+    /// 同时持有读状态的副本数量上限。
+    pub max_readers: Cell<usize>,
+    /// 登记了写意图的副本数量。
+    pub pending_writers: Cell<usize>,
+    /// 等待访问权限可用的回调。
+    pub releasable: RefCell<Vec<Releasable<T>>>,
+    /// 最后一个强引用释放时执行的回调。
+    pub finalizers: RefCell<VecDeque<Finalizer<T>>>,
+    /// 异步获取访问权限的等待队列。
+    pub waiters: RefCell<VecDeque<Waiter>>,
+    /// 下一个等待者的标识。
+    pub next_waiter: Cell<usize>,
+    /// 父对象。共享读写状态被锁定期间占用父对象的一份读状态。
+    pub parent: RefCell<Option<Weak<dyn LockLink>>>,
+    /// 子对象。
+    pub children: RefCell<Vec<Weak<dyn LockLink>>>,
+    /// 写状态结束时接收共享对象的观察者。
+    pub committed: commit::Observers<T>,
+    /// 分配此状态之后写状态结束的次数。
+    pub version: Cell<u64>,
+    /// [`RwWeakRead`](crate::RwWeakRead) 占用的读状态数量。
+    pub read_pins: Cell<usize>,
+    /// 是否拒绝写状态，用于只读的内存映射。
+    #[cfg(feature = "mmap")]
+    pub read_only: Cell<bool>,
+    /// 共享对象释放后通知弱引用的观察者的回调，在其他状态之后释放。
+    pub invalidated: weak::Invalidation,
+}
+
+impl<T> Default for Extras<T> {
+    fn default() -> Self {
+        Self {
+            max_readers: Cell::new(usize::MAX),
+            pending_writers: Cell::new(0),
+            releasable: RefCell::new(Vec::new()),
+            finalizers: RefCell::new(VecDeque::new()),
+            waiters: RefCell::new(VecDeque::new()),
+            next_waiter: Cell::new(0),
+            parent: RefCell::new(None),
+            children: RefCell::new(Vec::new()),
+            committed: Default::default(),
+            version: Cell::new(0),
+            read_pins: Cell::new(0),
+            #[cfg(feature = "mmap")]
+            read_only: Cell::new(false),
+            invalidated: Default::default(),
+        }
+    }
+}
+
+impl<T> Internal<T> {
+    /// 已经分配的不常用状态。
+    pub(super) fn extras(&self) -> Option<&Extras<T>> {
+        self.extras.get().map(|e| &**e)
+    }
+
+    /// 不常用的状态，尚未分配时分配。
+    pub(super) fn ensure_extras(&self) -> &Extras<T> {
+        self.extras.get_or_init(Default::default)
+    }
+
+    /// 登记了写意图的副本数量。
+    pub(super) fn pending_writers(&self) -> usize {
+        self.extras().map_or(0, |e| e.pending_writers.get())
+    }
+}
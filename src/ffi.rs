@@ -0,0 +1,311 @@
+use crate::{RwState, flag::RwFlag};
+use std::{
+    cell::{Cell, UnsafeCell},
+    ffi::c_void,
+    marker::PhantomData,
+    ptr::NonNull,
+};
+
+/// 布局稳定、可以跨越分别编译的二进制边界的 [`RwRc<T>`](crate::RwRc)。
+///
+/// 副本是 `#[repr(C)]` 的，除共享对象的指针外还携带创建方的函数表，
+/// 引用计数、共享读写状态的转换和释放都通过函数表在创建方的代码中完成，
+/// 因此插件与宿主即使以不同的编译器版本或特性编译，也遵守同一套共享读写状态语义。
+/// 副本的读写状态语义与 [`RwRc<T>`](crate::RwRc) 相同，但只能在读状态或写状态下借用共享对象。
+///
+/// 共享对象以 `T` 的布局直接访问，跨越边界时 `T` 本身也应当是布局稳定的类型；
+/// 函数表位于创建方的二进制中，副本存活期间不能卸载创建方。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::FfiRwRc;
+///
+/// // 插件导出的入口，宿主只知道它的签名
+/// extern "C" fn plugin_entry(mut counter: FfiRwRc<u32>) -> bool {
+///     match counter.try_write() {
+///         Some(val) => {
+///             *val += 1;
+///             true
+///         }
+///         None => false,
+///     }
+/// }
+///
+/// let mut counter = FfiRwRc::new(0u32);
+/// counter.release();
+/// assert!(plugin_entry(counter.clone()));
+/// assert!(counter.try_read_global());
+/// assert_eq!(counter.try_read(), Some(&1));
+/// // 宿主持有读状态时插件无法写入
+/// assert!(!plugin_entry(counter.clone()));
+/// ```
+#[repr(C)]
+pub struct FfiRwRc<T> {
+    /// 创建方分配的共享对象和状态。
+    inner: NonNull<c_void>,
+    /// 共享对象。
+    val: NonNull<T>,
+    /// 创建方的函数表。
+    vtable: &'static VTable,
+    /// 此副本占用的读写状态，是 [`State`] 的值。
+    state: u8,
+    _phantom: PhantomData<T>,
+}
+
+/// 创建方提供的操作。
+#[repr(C)]
+struct VTable {
+    /// 增加一个副本。
+    retain: unsafe extern "C" fn(NonNull<c_void>),
+    /// 减少一个副本，最后一个副本释放共享对象。
+    release: unsafe extern "C" fn(NonNull<c_void>),
+    /// 查询或转换共享读写状态。
+    flag: unsafe extern "C" fn(NonNull<c_void>, FlagOp) -> bool,
+}
+
+/// 副本读写状态的稳定编码。
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Hold = 0,
+    Read = 1,
+    Write = 2,
+}
+
+/// 共享读写状态上的操作，转换操作返回是否成功，释放操作总是返回 `true`。
+#[repr(u8)]
+#[derive(Clone, Copy)]
+enum FlagOp {
+    IsReadable,
+    IsWriteable,
+    IsThisWriteable,
+    HoldToRead,
+    HoldToWrite,
+    ReadToWrite,
+    ReadToHold,
+    WriteToHold,
+}
+
+/// 创建方分配的共享对象和状态。
+struct Internal<T> {
+    /// 引用计数。
+    count: Cell<usize>,
+    /// 共享读写状态。
+    flag: RwFlag,
+    /// 共享对象。
+    val: UnsafeCell<T>,
+}
+
+/// `T` 的函数表。
+struct VTableOf<T>(PhantomData<T>);
+
+impl<T> VTableOf<T> {
+    const VTABLE: &'static VTable = &VTable {
+        retain: retain::<T>,
+        release: release::<T>,
+        flag: flag::<T>,
+    };
+}
+
+unsafe extern "C" fn retain<T>(inner: NonNull<c_void>) {
+    let internal = unsafe { inner.cast::<Internal<T>>().as_ref() };
+    internal.count.set(internal.count.get() + 1)
+}
+
+unsafe extern "C" fn release<T>(inner: NonNull<c_void>) {
+    let ptr = inner.cast::<Internal<T>>();
+    let internal = unsafe { ptr.as_ref() };
+    let count = internal.count.get() - 1;
+    internal.count.set(count);
+    if count == 0 {
+        // 最后一个副本，没有其他引用
+        drop(unsafe { Box::from_raw(ptr.as_ptr()) })
+    }
+}
+
+unsafe extern "C" fn flag<T>(inner: NonNull<c_void>, op: FlagOp) -> bool {
+    let flag = unsafe { &inner.cast::<Internal<T>>().as_ref().flag };
+    match op {
+        FlagOp::IsReadable => flag.is_readable(),
+        FlagOp::IsWriteable => flag.is_writeable(),
+        FlagOp::IsThisWriteable => flag.is_this_writeable(),
+        FlagOp::HoldToRead => flag.hold_to_read(),
+        FlagOp::HoldToWrite => flag.hold_to_write(),
+        FlagOp::ReadToWrite => flag.read_to_write(),
+        FlagOp::ReadToHold => {
+            flag.read_to_hold();
+            true
+        }
+        FlagOp::WriteToHold => {
+            flag.write_to_hold();
+            true
+        }
+    }
+}
+
+impl<T> From<T> for FfiRwRc<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Clone for FfiRwRc<T> {
+    /// 只有当源对象在读状态时，克隆的对象才会设置读状态，否则设置为持有状态。
+    fn clone(&self) -> Self {
+        unsafe { (self.vtable.retain)(self.inner) };
+        let state = match self.state() {
+            RwState::Read => {
+                assert!(self.flag(FlagOp::HoldToRead));
+                State::Read
+            }
+            RwState::Hold | RwState::Upgradable | RwState::Write => State::Hold,
+        };
+        Self {
+            state: state as _,
+            ..*self
+        }
+    }
+}
+
+impl<T> Drop for FfiRwRc<T> {
+    fn drop(&mut self) {
+        self.release();
+        unsafe { (self.vtable.release)(self.inner) }
+    }
+}
+
+impl<T> FfiRwRc<T> {
+    /// 从对象初始化，直接设置到读状态。
+    pub fn new(val: T) -> Self {
+        let internal = Box::leak(Box::new(Internal {
+            count: Cell::new(1),
+            flag: RwFlag::new_read(),
+            val: UnsafeCell::new(val),
+        }));
+        Self {
+            val: NonNull::new(internal.val.get()).unwrap(),
+            inner: NonNull::from(internal).cast(),
+            vtable: VTableOf::<T>::VTABLE,
+            state: State::Read as _,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// 此副本当前的读写状态。
+    pub fn state(&self) -> RwState {
+        match self.state {
+            0 => RwState::Hold,
+            1 => RwState::Read,
+            _ => RwState::Write,
+        }
+    }
+
+    /// 判断是否可读。
+    pub fn is_readable(&self) -> bool {
+        match self.state() {
+            RwState::Hold => self.flag(FlagOp::IsReadable),
+            RwState::Read | RwState::Upgradable | RwState::Write => true,
+        }
+    }
+
+    /// 判断是否可写。
+    pub fn is_writeable(&self) -> bool {
+        match self.state() {
+            RwState::Hold => self.flag(FlagOp::IsWriteable),
+            RwState::Read | RwState::Upgradable => self.flag(FlagOp::IsThisWriteable),
+            RwState::Write => true,
+        }
+    }
+
+    /// 尝试设置到读状态。
+    pub fn try_read_global(&mut self) -> bool {
+        match self.state() {
+            RwState::Hold => {
+                if !self.flag(FlagOp::HoldToRead) {
+                    return false;
+                }
+                self.state = State::Read as _
+            }
+            RwState::Read | RwState::Upgradable | RwState::Write => {}
+        }
+        true
+    }
+
+    /// 尝试设置到写状态。
+    pub fn try_write_global(&mut self) -> bool {
+        let ok = match self.state() {
+            RwState::Hold => self.flag(FlagOp::HoldToWrite),
+            RwState::Read | RwState::Upgradable => self.flag(FlagOp::ReadToWrite),
+            RwState::Write => true,
+        };
+        if ok {
+            self.state = State::Write as _
+        }
+        ok
+    }
+
+    /// 释放读写状态，回到持有状态。
+    pub fn release(&mut self) {
+        let op = match self.state() {
+            RwState::Hold => return,
+            RwState::Read | RwState::Upgradable => FlagOp::ReadToHold,
+            RwState::Write => FlagOp::WriteToHold,
+        };
+        self.flag(op);
+        self.state = State::Hold as _
+    }
+
+    /// 在读状态或写状态下借用共享对象，持有状态下返回 `None`。
+    pub fn try_read(&self) -> Option<&T> {
+        match self.state() {
+            RwState::Hold => None,
+            // 读状态保证没有副本在写入；写状态下可变借用需要独占此副本
+            RwState::Read | RwState::Upgradable | RwState::Write => {
+                Some(unsafe { self.val.as_ref() })
+            }
+        }
+    }
+
+    /// 尝试设置到写状态并可变借用共享对象，无法获取写状态时返回 `None`。
+    pub fn try_write(&mut self) -> Option<&mut T> {
+        if !self.try_write_global() {
+            return None;
+        }
+        // 写状态保证其他副本无法访问
+        Some(unsafe { self.val.as_mut() })
+    }
+
+    /// 通过创建方的函数表操作共享读写状态。
+    fn flag(&self, op: FlagOp) -> bool {
+        unsafe { (self.vtable.flag)(self.inner, op) }
+    }
+}
+
+#[test]
+fn test_ffi_rwrc() {
+    use std::rc::Rc;
+
+    let mut a = FfiRwRc::new(Rc::new(1));
+    let weak = Rc::downgrade(a.try_read().unwrap());
+    let mut b = a.clone();
+    assert_eq!(b.state(), RwState::Read);
+    assert!(!a.is_writeable());
+    assert!(a.try_write().is_none());
+    b.release();
+    *a.try_write().unwrap() = Rc::new(2);
+
+    // 写状态的副本克隆为持有状态
+    let mut c = a.clone();
+    assert!(c.try_read().is_none());
+    assert!(!c.try_read_global());
+    drop(a);
+    assert!(c.try_read_global());
+    assert_eq!(**c.try_read().unwrap(), 2);
+    assert!(weak.upgrade().is_none());
+
+    // 最后一个副本释放共享对象
+    let weak = Rc::downgrade(c.try_read().unwrap());
+    drop((b, c));
+    assert!(weak.upgrade().is_none());
+}
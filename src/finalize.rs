@@ -0,0 +1,136 @@
+use crate::{Internal, RwRc};
+use std::rc::Rc;
+
+/// 最后一个强引用释放时执行的回调。
+pub(super) type Finalizer<T> = Box<dyn FnOnce(&mut T)>;
+
+impl<T> RwRc<T> {
+    /// 登记一个在共享对象的最后一个强引用释放时执行的回调。
+    ///
+    /// 回调按登记顺序执行，每个回调只执行一次。
+    /// 执行回调时共享对象被写锁定，此时从弱引用升级的副本无法访问共享对象。
+    /// 通过 [`zip`](Self::zip) 或 [`drop_deep`](Self::drop_deep) 移出共享对象时也会执行回调。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    /// use std::{cell::RefCell, rc::Rc};
+    ///
+    /// let disk = Rc::new(RefCell::new(Vec::new()));
+    /// let rc = RwRc::new(vec![1, 2]);
+    ///
+    /// let sink = disk.clone();
+    /// rc.on_last_drop(move |buf| sink.borrow_mut().append(buf));
+    /// let other = rc.clone();
+    /// drop(rc);
+    /// assert!(disk.borrow().is_empty());
+    /// drop(other);
+    /// assert_eq!(*disk.borrow(), [1, 2]);
+    /// ```
+    pub fn on_last_drop(&self, f: impl FnOnce(&mut T) + 'static) {
+        self.rc.check_thread();
+        self.rc
+            .ensure_extras()
+            .finalizers
+            .borrow_mut()
+            .push_back(Box::new(f))
+    }
+
+    /// 如果此副本是最后一个强引用，执行登记的回调。此副本必须处于持有状态。
+    pub(super) fn finalize(&self) {
+        if Rc::strong_count(&self.rc) == 1 {
+            self.rc.unpin_readers();
+            Internal::finalize(&self.rc)
+        }
+    }
+}
+
+impl<T> Internal<T> {
+    fn finalize(&self) {
+        let Some(extras) = self.extras() else { return };
+        if extras.finalizers.borrow().is_empty() {
+            return;
+        }
+        // 被锁定的子对象占用此对象的读状态，共享对象即将释放，先解除子对象
+        self.detach_children();
+        // 没有其他强引用，此时共享读写状态应当可以直接锁定；
+        // 即使无法锁定，也没有副本能访问共享对象，不锁定也可以执行回调
+        let locked = self.flag.hold_to_write();
+        loop {
+            let Some(f) = extras.finalizers.borrow_mut().pop_front() else {
+                break;
+            };
+            // 写锁定期间没有其他副本能访问共享对象
+            f(unsafe { &mut *self.val.as_ptr() })
+        }
+        if locked {
+            self.flag.write_to_hold()
+        }
+    }
+}
+
+#[test]
+fn test_finalizers() {
+    use std::cell::RefCell;
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let (rc, weak) = RwRc::new_with_weak(0);
+    for i in 0..2 {
+        let log = log.clone();
+        let weak = weak.clone();
+        rc.on_last_drop(move |val| {
+            // 回调执行期间从弱引用升级的副本无法访问
+            assert!(weak.hold().unwrap().try_read().is_none());
+            *val += 1;
+            log.borrow_mut().push((i, *val))
+        })
+    }
+    drop(rc.clone());
+    assert!(log.borrow().is_empty());
+    drop(rc);
+    assert_eq!(*log.borrow(), [(0, 1), (1, 2)]);
+
+    // 移出共享对象时也会执行
+    let a = RwRc::new(1);
+    let flag = log.clone();
+    a.on_last_drop(move |_| flag.borrow_mut().clear());
+    let _ = a.zip(RwRc::new(2)).ok().unwrap();
+    assert!(log.borrow().is_empty());
+}
+
+#[test]
+fn test_finalize_with_locked_child() {
+    use std::cell::Cell;
+
+    let parent = RwRc::new(0);
+    let child = RwRc::new(1);
+    parent.release();
+    assert!(child.set_parent(&parent));
+    // 被锁定的子对象占用父对象的读状态
+    assert!(child.try_read_global());
+    let done = Rc::new(Cell::new(false));
+    let flag = done.clone();
+    parent.on_last_drop(move |val| {
+        *val += 1;
+        flag.set(true)
+    });
+    drop(parent);
+    assert!(done.get());
+
+    // 解除后子对象不再受约束
+    child.release();
+    assert!(child.try_write_global());
+    child.release();
+
+    // 移出共享对象时同样执行
+    let parent = RwRc::new(0);
+    parent.release();
+    assert!(child.set_parent(&parent));
+    assert!(child.try_read_global());
+    let flag = done.clone();
+    done.set(false);
+    parent.on_last_drop(move |_| flag.set(true));
+    let _ = parent.zip(RwRc::new(())).ok().unwrap();
+    assert!(done.get());
+}
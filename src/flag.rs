@@ -1,8 +1,37 @@
-﻿use std::cell::Cell;
+﻿use crate::RwState;
+use std::cell::Cell;
+
+/// 检查状态转换的前提条件。
+///
+/// 调试模式下总是检查；启用 `strict` 特性时发布模式下也会检查。
+macro_rules! check {
+    ($cond:expr, $($arg:tt)+) => {
+        if cfg!(any(debug_assertions, feature = "strict")) && !$cond {
+            crate::policy::misuse(format_args!($($arg)+))
+        }
+    };
+}
+
+/// 读写状态计数器的类型。
+///
+/// 启用 `compact-flag` 特性时使用 `u32`，使共享读写状态在 64 位目标上只占 4 字节。
+#[cfg(not(feature = "compact-flag"))]
+type Counter = usize;
+#[cfg(feature = "compact-flag")]
+type Counter = u32;
+
+/// 表示写状态的计数值。
+const WRITE: Counter = Counter::MAX;
 
 /// 共享读写状态。
+///
+/// [`WRITE`] 表示写状态，其他值表示持有读状态的副本数量。
+/// 读者数量达到 `WRITE - 1` 后无法再增加，否则会与写状态混淆。
 #[repr(transparent)]
-pub(super) struct RwFlag(Cell<usize>);
+pub(super) struct RwFlag(Cell<Counter>);
+
+/// 读者数量的上限。
+const MAX_READERS: Counter = WRITE - 1;
 
 impl RwFlag {
     /// 初始化状态变量。
@@ -10,9 +39,14 @@ impl RwFlag {
         Self(Cell::new(1))
     }
 
+    /// 初始化无锁定的状态变量。
+    pub const fn new_hold() -> Self {
+        Self(Cell::new(0))
+    }
+
     /// 判断是否可读。
     pub fn is_readable(&self) -> bool {
-        self.0.get() != usize::MAX
+        self.0.get() != WRITE
     }
 
     /// 判断是否可写。
@@ -25,9 +59,23 @@ impl RwFlag {
         self.0.get() == 0
     }
 
+    /// 持有读状态的副本数量。
+    pub fn readers(&self) -> usize {
+        match self.0.get() {
+            WRITE => 0,
+            n => n as _,
+        }
+    }
+
+    /// 获取一份读状态。
+    ///
+    /// # Panic
+    ///
+    /// 读者数量溢出时 panic。
     pub fn hold_to_read(&self) -> bool {
         match self.0.get() {
-            usize::MAX => false,
+            WRITE => false,
+            MAX_READERS => crate::policy::misuse(format_args!("too many readers on a RwFlag")),
             n => {
                 self.0.set(n + 1);
                 true
@@ -38,7 +86,7 @@ impl RwFlag {
     pub fn hold_to_write(&self) -> bool {
         match self.0.get() {
             0 => {
-                self.0.set(usize::MAX);
+                self.0.set(WRITE);
                 true
             }
             _ => false,
@@ -48,7 +96,7 @@ impl RwFlag {
     pub fn read_to_write(&self) -> bool {
         match self.0.get() {
             1 => {
-                self.0.set(usize::MAX);
+                self.0.set(WRITE);
                 true
             }
             _ => false,
@@ -57,23 +105,157 @@ impl RwFlag {
 
     pub fn read_to_hold(&self) {
         let current = self.0.get();
-        debug_assert!((1..usize::MAX).contains(&current));
+        check!(
+            (1..=MAX_READERS).contains(&current),
+            "read_to_hold on a flag that is not read-locked (counter = {current:#x})"
+        );
         self.0.set(current - 1)
     }
 
     pub fn write_to_hold(&self) {
         let current = self.0.get();
-        debug_assert_eq!(current, usize::MAX);
+        check!(
+            current == WRITE,
+            "write_to_hold on a flag that is not write-locked (counter = {current:#x})"
+        );
         self.0.set(0)
     }
 
     pub fn write_to_read(&self) {
         let current = self.0.get();
-        debug_assert_eq!(current, usize::MAX);
+        check!(
+            current == WRITE,
+            "write_to_read on a flag that is not write-locked (counter = {current:#x})"
+        );
         self.0.set(1)
     }
 }
 
+/// 只在读状态或写状态下借用共享对象的副本的读写状态。
+///
+/// [`RwRcRef`](crate::RwRcRef)、[`StaticRwRc`](crate::StaticRwRc) 和
+/// [`SlimRwRc`](crate::SlimRwRc) 只是共享对象的存放方式不同，
+/// 它们以此状态和各自的共享读写状态实现副本的状态转换和借用。
+/// 这些副本没有可升级的读状态。
+#[derive(Clone, Copy)]
+pub(super) enum LocalState {
+    /// 持有（不关心读写）。
+    Hold,
+    /// 预期读，禁止修改。
+    Read,
+    /// 预期写，限制读写。
+    Write,
+}
+
+impl LocalState {
+    /// 对应的副本读写状态。
+    pub fn get(self) -> RwState {
+        match self {
+            Self::Hold => RwState::Hold,
+            Self::Read => RwState::Read,
+            Self::Write => RwState::Write,
+        }
+    }
+
+    /// 克隆副本的读写状态。
+    ///
+    /// 只有当源副本在读状态时，克隆的副本才会设置读状态，否则设置为持有状态。
+    pub fn clone_with(self, flag: &RwFlag) -> Self {
+        match self {
+            Self::Read => {
+                assert!(flag.hold_to_read());
+                Self::Read
+            }
+            Self::Hold | Self::Write => Self::Hold,
+        }
+    }
+
+    /// 判断是否可读。
+    pub fn is_readable(self, flag: &RwFlag) -> bool {
+        match self {
+            Self::Hold => flag.is_readable(),
+            Self::Read | Self::Write => true,
+        }
+    }
+
+    /// 判断是否可写。
+    pub fn is_writeable(self, flag: &RwFlag) -> bool {
+        match self {
+            Self::Hold => flag.is_writeable(),
+            Self::Read => flag.is_this_writeable(),
+            Self::Write => true,
+        }
+    }
+
+    /// 尝试设置到读状态。
+    pub fn try_read_global(&mut self, flag: &RwFlag) -> bool {
+        match self {
+            Self::Hold => {
+                if !flag.hold_to_read() {
+                    return false;
+                }
+                *self = Self::Read
+            }
+            Self::Read | Self::Write => {}
+        }
+        true
+    }
+
+    /// 尝试设置到写状态。
+    pub fn try_write_global(&mut self, flag: &RwFlag) -> bool {
+        let ok = match self {
+            Self::Hold => flag.hold_to_write(),
+            Self::Read => flag.read_to_write(),
+            Self::Write => true,
+        };
+        if ok {
+            *self = Self::Write
+        }
+        ok
+    }
+
+    /// 释放读写状态，回到持有状态。
+    pub fn release(&mut self, flag: &RwFlag) {
+        match std::mem::replace(self, Self::Hold) {
+            Self::Hold => {}
+            Self::Read => flag.read_to_hold(),
+            Self::Write => flag.write_to_hold(),
+        }
+    }
+
+    /// 在读状态或写状态下借用 `val`，持有状态下返回 `None`。
+    ///
+    /// # Safety
+    ///
+    /// `val` 必须指向受 `flag` 保护的共享对象，并且在 `'a` 期间有效，
+    /// `'a` 期间此状态不能改变。
+    pub unsafe fn borrow<'a, T: ?Sized>(self, val: *const T) -> Option<&'a T> {
+        match self {
+            Self::Hold => None,
+            // 读状态保证没有副本在写入；写状态下可变借用需要独占此副本
+            Self::Read | Self::Write => Some(unsafe { &*val }),
+        }
+    }
+
+    /// 尝试设置到写状态并可变借用 `val`，无法获取写状态时返回 `None`。
+    ///
+    /// # Safety
+    ///
+    /// `val` 必须指向受 `flag` 保护的共享对象，并且在 `'a` 期间有效，
+    /// `'a` 期间此状态不能改变。
+    pub unsafe fn borrow_mut<'a, T: ?Sized>(
+        &mut self,
+        flag: &RwFlag,
+        val: *mut T,
+    ) -> Option<&'a mut T> {
+        if !self.try_write_global(flag) {
+            return None;
+        }
+        // 写状态保证其他副本无法访问
+        Some(unsafe { &mut *val })
+    }
+}
+
 #[test]
 fn test_new_read() {
     let flag = RwFlag::new_read();
@@ -91,6 +273,15 @@ fn test_hold_to_read() {
     assert!(!flag.is_this_writeable());
 }
 
+#[test]
+#[should_panic(expected = "too many readers")]
+fn test_reader_overflow() {
+    let flag = RwFlag(Cell::new(MAX_READERS - 1));
+    assert!(flag.hold_to_read());
+    assert!(flag.is_readable());
+    flag.hold_to_read();
+}
+
 #[test]
 fn test_read_to_hold() {
     let flag = RwFlag::new_read();
@@ -131,6 +322,14 @@ fn test_write_to_hold() {
     assert!(flag.is_this_writeable());
 }
 
+#[test]
+fn test_flag_size() {
+    use std::mem::size_of;
+    assert_eq!(size_of::<RwFlag>(), size_of::<Counter>());
+    #[cfg(feature = "compact-flag")]
+    assert_eq!(size_of::<RwFlag>(), 4);
+}
+
 #[test]
 fn test_write_to_read() {
     let flag = RwFlag::new_read();
@@ -140,3 +339,36 @@ fn test_write_to_read() {
     assert!(!flag.is_writeable());
     assert!(flag.is_this_writeable());
 }
+
+#[cfg(any(debug_assertions, feature = "strict"))]
+#[test]
+#[should_panic(expected = "not read-locked")]
+fn test_check_read_to_hold() {
+    let flag = RwFlag(Cell::new(0));
+    flag.read_to_hold()
+}
+
+#[cfg(any(debug_assertions, feature = "strict"))]
+#[test]
+#[should_panic(expected = "not write-locked")]
+fn test_check_write_to_hold() {
+    let flag = RwFlag::new_read();
+    flag.write_to_hold()
+}
+
+#[test]
+fn test_local_state() {
+    let flag = RwFlag::new_hold();
+    let mut a = LocalState::Hold;
+    assert!(a.try_read_global(&flag));
+    let mut b = a.clone_with(&flag);
+    assert!(matches!(b.get(), RwState::Read));
+    assert!(!a.is_writeable(&flag));
+    assert!(!a.try_write_global(&flag));
+    b.release(&flag);
+    assert!(a.try_write_global(&flag));
+    assert!(matches!(a.clone_with(&flag), LocalState::Hold));
+    assert!(!b.is_readable(&flag));
+    a.release(&flag);
+    assert!(flag.is_writeable());
+}
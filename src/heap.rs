@@ -0,0 +1,161 @@
+use crate::{Internal, RwRc, RwWeak};
+use std::{
+    alloc::Layout,
+    collections::{HashMap, VecDeque},
+    mem::size_of,
+};
+
+/// 统计对象拥有的堆内存。
+///
+/// 只统计对象独占的堆内存，不包括对象本身的大小。
+/// 共享的 [`RwRc`] 分配由各自的副本统计，因此副本和弱引用本身返回 0，避免重复计算。
+pub trait HeapSize {
+    /// 对象拥有的堆内存字节数。
+    fn heap_size(&self) -> usize;
+}
+
+/// 共享对象分配的字节数，包括引用计数。
+pub(crate) const fn allocation_size<T>() -> usize {
+    match Layout::new::<[usize; 2]>().extend(Layout::new::<Internal<T>>()) {
+        Ok((layout, _)) => layout.pad_to_align().size(),
+        Err(_) => unreachable!(),
+    }
+}
+
+impl<T: HeapSize> RwRc<T> {
+    /// 共享对象的分配大小加上共享对象拥有的堆内存字节数。
+    ///
+    /// 需要读取共享对象，其他副本持有写状态时返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let rc = RwRc::new(Vec::<u8>::with_capacity(1024));
+    /// assert!(rc.heap_size().unwrap() >= 1024);
+    /// ```
+    pub fn heap_size(&self) -> Option<usize> {
+        if !self.is_readable() {
+            return None;
+        }
+        // 可读时不存在其他副本的可变借用；此副本的可变借用需要独占此副本，也不存在
+        let val = unsafe { &*self.rc.val.as_ptr() };
+        Some(allocation_size::<T>() + val.heap_size())
+    }
+}
+
+impl<T> HeapSize for RwRc<T> {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T> HeapSize for RwWeak<T> {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+macro_rules! heap_nothing {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl HeapSize for $ty {
+                #[inline]
+                fn heap_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+heap_nothing!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    &'static str,
+);
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_size(&self) -> usize {
+        size_of::<T>() + (**self).heap_size()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, HeapSize::heap_size)
+    }
+}
+
+impl<T: HeapSize, const N: usize> HeapSize for [T; N] {
+    fn heap_size(&self) -> usize {
+        self.iter().map(HeapSize::heap_size).sum()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for VecDeque<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize, S> HeapSize for HashMap<K, V, S> {
+    /// 按容量估计，不包括哈希表的控制字节。
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<(K, V)>()
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+#[test]
+fn test_heap_size() {
+    let base = allocation_size::<Vec<String>>();
+    assert!(base >= size_of::<Internal<Vec<String>>>() + 2 * size_of::<usize>());
+
+    let mut rc = RwRc::new(Vec::<String>::with_capacity(4));
+    rc.release();
+    assert_eq!(rc.heap_size(), Some(base + 4 * size_of::<String>()));
+
+    rc.write().push(String::with_capacity(10));
+    let expected = base + rc.read().capacity() * size_of::<String>() + 10;
+    assert_eq!(rc.heap_size(), Some(expected));
+
+    // 其他副本持有写状态时无法读取
+    let writer = rc.clone();
+    assert!(writer.try_write_global());
+    assert_eq!(rc.heap_size(), None);
+    assert_eq!(writer.heap_size(), Some(expected));
+}
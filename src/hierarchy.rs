@@ -0,0 +1,278 @@
+use crate::{Internal, RwRc};
+use std::rc::{Rc, Weak};
+
+/// 类型擦除的父对象或子对象。
+pub(super) trait LockLink {
+    /// 占用一份读状态。
+    fn enter(&self) -> bool;
+
+    /// 释放一份读状态，并执行可以获取访问权限的回调。
+    fn leave(self: Rc<Self>);
+
+    /// 判断能否占用一份读状态。
+    fn can_enter(&self) -> bool;
+
+    /// 判断 `id` 是否是此对象或此对象的祖先。
+    fn is_or_descends_from(&self, id: *const ()) -> bool;
+
+    /// 从子对象列表中移除 `id`。
+    fn remove_child(&self, id: *const ());
+
+    /// 执行可以获取访问权限的回调。
+    fn notify(self: Rc<Self>);
+
+    /// 解除与父对象的关系而不释放占用的父对象读状态，返回是否占用了父对象的读状态。
+    fn detach_parent(&self) -> bool;
+
+    /// 判断共享读写状态是否被锁定。
+    #[cfg(feature = "audit")]
+    fn is_locked(&self) -> bool;
+}
+
+impl<P> LockLink for Internal<P> {
+    fn enter(&self) -> bool {
+        self.hold_to_read()
+    }
+
+    fn leave(self: Rc<Self>) {
+        self.read_to_hold();
+        Internal::notify_released(&self)
+    }
+
+    fn can_enter(&self) -> bool {
+        self.can_read()
+    }
+
+    fn is_or_descends_from(&self, id: *const ()) -> bool {
+        std::ptr::eq(self as *const Self as *const (), id)
+            || self.parent().is_some_and(|p| p.is_or_descends_from(id))
+    }
+
+    fn notify(self: Rc<Self>) {
+        Internal::notify_released(&self)
+    }
+
+    fn detach_parent(&self) -> bool {
+        if let Some(e) = self.extras() {
+            e.parent.take();
+        }
+        !self.flag.is_writeable()
+    }
+
+    #[cfg(feature = "audit")]
+    fn is_locked(&self) -> bool {
+        !self.flag.is_writeable()
+    }
+
+    fn remove_child(&self, id: *const ()) {
+        let Some(e) = self.extras() else { return };
+        e.children
+            .borrow_mut()
+            .retain(|c| !std::ptr::eq(c.as_ptr() as *const (), id))
+    }
+}
+
+impl<T> Internal<T> {
+    /// 存活的父对象。
+    fn parent(&self) -> Option<Rc<dyn LockLink>> {
+        let parent = self.extras()?.parent.borrow();
+        parent.as_ref().and_then(Weak::upgrade)
+    }
+
+    /// 占用父对象的一份读状态，没有父对象时总是成功。
+    pub(super) fn enter_parent(&self) -> bool {
+        self.parent().is_none_or(|p| p.enter())
+    }
+
+    /// 释放占用的父对象的读状态。
+    pub(super) fn leave_parent(&self) {
+        if let Some(p) = self.parent() {
+            p.leave()
+        }
+    }
+
+    /// 判断能否占用父对象的一份读状态。
+    pub(super) fn can_enter_parent(&self) -> bool {
+        self.parent().is_none_or(|p| p.can_enter())
+    }
+
+    /// 被锁定的子对象数量，每个被锁定的子对象占用此对象的一份读状态。
+    #[cfg(feature = "audit")]
+    pub(super) fn locked_children(&self) -> usize {
+        let Some(e) = self.extras() else { return 0 };
+        let children = e.children.borrow();
+        let children = children.iter().filter_map(Weak::upgrade);
+        children.filter(|c| c.is_locked()).count()
+    }
+
+    /// 解除所有子对象，并释放被锁定的子对象占用的读状态。
+    ///
+    /// 最后一个强引用释放时调用，此后父对象不再约束子对象。
+    pub(super) fn detach_children(&self) {
+        let Some(e) = self.extras() else { return };
+        let children = std::mem::take(&mut *e.children.borrow_mut());
+        for child in children.iter().filter_map(Weak::upgrade) {
+            if child.detach_parent() {
+                self.read_to_hold()
+            }
+        }
+    }
+
+    /// 执行所有子对象上可以获取访问权限的回调。
+    pub(super) fn notify_children(&self) {
+        let Some(e) = self.extras() else { return };
+        let children = {
+            let mut children = e.children.borrow_mut();
+            children.retain(|c| c.strong_count() > 0);
+            children.clone()
+        };
+        for child in children.iter().filter_map(Weak::upgrade) {
+            child.notify()
+        }
+    }
+}
+
+impl<T: 'static> RwRc<T> {
+    /// 将 `parent` 设置为此共享对象的父对象，替换原有的父对象。
+    ///
+    /// 此共享对象的共享读写状态被锁定（任何副本持有读状态或写状态）期间，
+    /// 会占用父对象的一份读状态：父对象持有写状态时，此共享对象的所有副本都无法获取读写状态；
+    /// 此共享对象被锁定时，父对象无法获取写状态。
+    /// 父对象以弱引用保存，父对象释放后不再有约束。
+    /// 占用父对象的读状态时不考虑父对象的读写偏好策略。
+    ///
+    /// 如果此共享对象正被锁定而父对象持有写状态，或者设置后父子关系会成环，返回 `false`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let mut container = RwRc::new(vec!["part"]);
+    /// let mut part = RwRc::new(1);
+    /// container.release();
+    /// part.release();
+    /// assert!(part.set_parent(&container));
+    ///
+    /// // 修改容器时无法访问部件
+    /// assert!(container.try_write_global());
+    /// assert!(part.try_read().is_none());
+    /// container.release();
+    ///
+    /// // 访问部件时无法修改容器
+    /// let value = part.read();
+    /// assert!(container.try_write().is_none());
+    /// assert_eq!(container.read().len(), *value);
+    /// ```
+    pub fn set_parent<P: 'static>(&self, parent: &RwRc<P>) -> bool {
+        self.rc.check_thread();
+        let id = Rc::as_ptr(&self.rc) as *const ();
+        if parent.rc.is_or_descends_from(id) {
+            return false;
+        }
+        let locked = !self.rc.flag.is_writeable();
+        if locked && !parent.rc.enter() {
+            return false;
+        }
+        let link: Weak<Internal<P>> = Rc::downgrade(&parent.rc);
+        let old = self.rc.ensure_extras().parent.replace(Some(link));
+        self.detach(old, locked);
+        let child: Weak<Internal<T>> = Rc::downgrade(&self.rc);
+        parent.rc.ensure_extras().children.borrow_mut().push(child);
+        true
+    }
+
+    /// 解除此共享对象的父对象。
+    pub fn clear_parent(&self) {
+        self.rc.check_thread();
+        let locked = !self.rc.flag.is_writeable();
+        let old = self.rc.extras().and_then(|e| e.parent.take());
+        self.detach(old, locked)
+    }
+
+    /// 从原父对象上解除，`locked` 表示是否占用了原父对象的读状态。
+    fn detach(&self, old: Option<Weak<dyn LockLink>>, locked: bool) {
+        let Some(old) = old.as_ref().and_then(Weak::upgrade) else {
+            return;
+        };
+        old.remove_child(Rc::as_ptr(&self.rc) as *const ());
+        if locked {
+            old.leave()
+        }
+    }
+}
+
+#[test]
+fn test_parent_blocks_children() {
+    let mut parent = RwRc::new(0);
+    let mut a = RwRc::new(1);
+    let b = RwRc::new(2);
+    parent.release();
+    // a 处于读状态，设置父对象时立即占用父对象的读状态
+    assert!(a.set_parent(&parent));
+    b.release();
+    assert!(b.set_parent(&parent));
+    assert!(!parent.try_write_global());
+
+    // 子对象解锁后父对象可以写入，此时子对象无法访问
+    a.release();
+    assert!(parent.try_write_global());
+    assert!(a.try_read().is_none());
+    assert!(!b.try_write_global());
+    assert!(!a.is_readable());
+    parent.release();
+
+    // 子对象之间互不影响，父对象的读者不受子对象写者影响
+    assert!(a.try_write_global());
+    assert!(b.try_write_global());
+    assert_eq!(*parent.read(), 0);
+    assert!(parent.try_write().is_none());
+
+    // 解除父子关系后不再约束
+    a.clear_parent();
+    b.release();
+    assert!(parent.try_write_global());
+    assert!(a.try_write().is_some());
+    assert!(!b.try_read_global());
+}
+
+#[test]
+fn test_parent_chain() {
+    use crate::Access;
+    use std::{cell::Cell, rc::Rc};
+
+    let root = RwRc::new(());
+    let mid = RwRc::new(());
+    let leaf = RwRc::new(());
+    root.release();
+    mid.release();
+    leaf.release();
+    assert!(mid.set_parent(&root));
+    assert!(leaf.set_parent(&mid));
+    // 父子关系不能成环
+    assert!(!root.set_parent(&leaf));
+    assert!(!leaf.set_parent(&leaf));
+
+    // 叶子的读者经由中间对象阻止根对象的写者
+    let guard = leaf.read();
+    assert!(!root.try_write_global());
+    drop(guard);
+    assert!(root.try_write_global());
+    assert!(leaf.try_read().is_none());
+
+    // 根对象释放写状态时执行叶子上等待的回调
+    let done = Rc::new(Cell::new(false));
+    let flag = done.clone();
+    leaf.on_releasable(Access::Write, move |rc| {
+        assert!(rc.try_write().is_some());
+        flag.set(true)
+    });
+    assert!(!done.get());
+    root.release();
+    assert!(done.get());
+
+    // 父对象释放后不再约束
+    drop(root);
+    assert!(mid.try_read_global());
+    assert!(leaf.try_write_global());
+}
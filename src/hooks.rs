@@ -0,0 +1,98 @@
+use crate::RwRc;
+use std::{cell::RefCell, rc::Rc};
+
+/// 接收强引用计数的钩子函数。
+type HookFn = Box<dyn Fn(usize)>;
+
+/// 副本克隆和丢弃时调用的钩子。
+#[derive(Default)]
+pub(super) struct Hooks {
+    /// 产生新副本时调用。
+    on_clone: RefCell<Option<HookFn>>,
+    /// 丢弃副本时调用。
+    on_drop_handle: RefCell<Option<HookFn>>,
+}
+
+impl Hooks {
+    /// 产生了新副本，`strong` 是产生后的强引用计数。
+    pub fn cloned(&self, strong: usize) {
+        if let Some(f) = &*self.on_clone.borrow() {
+            f(strong)
+        }
+    }
+
+    /// 丢弃了副本，`strong` 是丢弃后的强引用计数。
+    pub fn dropped(&self, strong: usize) {
+        if let Some(f) = &*self.on_drop_handle.borrow() {
+            f(strong)
+        }
+    }
+}
+
+impl<T> RwRc<T> {
+    /// 设置产生新副本时调用的钩子，替换之前设置的钩子。
+    ///
+    /// 克隆副本和从弱引用升级都会调用钩子，参数是产生新副本后的强引用计数。
+    /// 钩子由共享对象的所有副本共用。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    /// use std::{cell::Cell, rc::Rc};
+    ///
+    /// let rc = RwRc::new(42);
+    /// let count = Rc::new(Cell::new(0));
+    /// let c = count.clone();
+    /// rc.set_on_clone(move |strong| c.set(strong));
+    ///
+    /// let _rc2 = rc.clone();
+    /// assert_eq!(count.get(), 2);
+    /// ```
+    pub fn set_on_clone(&self, f: impl Fn(usize) + 'static) {
+        *self.rc.hooks.on_clone.borrow_mut() = Some(Box::new(f))
+    }
+
+    /// 设置丢弃副本时调用的钩子，替换之前设置的钩子。
+    ///
+    /// 参数是丢弃此副本后的强引用计数，为 0 表示共享对象随之释放。
+    /// 钩子由共享对象的所有副本共用。
+    pub fn set_on_drop_handle(&self, f: impl Fn(usize) + 'static) {
+        *self.rc.hooks.on_drop_handle.borrow_mut() = Some(Box::new(f))
+    }
+
+    /// 通知钩子产生了新副本。
+    pub(super) fn hook_cloned(rc: &Rc<crate::Internal<T>>) {
+        rc.hooks.cloned(Rc::strong_count(rc))
+    }
+}
+
+#[test]
+fn test_hooks() {
+    use std::cell::RefCell;
+
+    let rc = RwRc::new(42);
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let l = log.clone();
+    rc.set_on_clone(move |n| l.borrow_mut().push(("clone", n)));
+    let l = log.clone();
+    rc.set_on_drop_handle(move |n| l.borrow_mut().push(("drop", n)));
+
+    let rc2 = rc.clone();
+    let rc3 = rc.weak().hold().unwrap();
+    drop(rc2);
+    drop(rc3);
+    drop(rc);
+
+    assert_eq!(
+        *log.borrow(),
+        [
+            ("clone", 2),
+            ("clone", 3),
+            ("drop", 2),
+            ("drop", 1),
+            ("drop", 0)
+        ]
+    );
+}
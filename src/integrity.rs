@@ -0,0 +1,142 @@
+use crate::{Internal, RwRc, policy::misuse};
+use std::{
+    cell::Cell,
+    hash::{BuildHasher, Hash, RandomState},
+};
+
+/// 计算共享对象散列值的函数。
+type Hasher<T> = fn(&T) -> u64;
+
+/// 共享对象在最近一次写状态结束时的散列值。
+pub(super) struct Checksum<T> {
+    /// 计算散列值的函数，启用检查后设置。
+    hasher: Cell<Option<Hasher<T>>>,
+    /// 最近一次提交时的散列值，启用时无法读取的共享对象在下一次提交时才有值。
+    value: Cell<Option<u64>>,
+}
+
+impl<T> Default for Checksum<T> {
+    fn default() -> Self {
+        Self {
+            hasher: Cell::new(None),
+            value: Cell::new(None),
+        }
+    }
+}
+
+/// 以进程内固定的种子计算散列值。
+fn hash_of<T: Hash>(val: &T) -> u64 {
+    thread_local! {
+        static STATE: RandomState = RandomState::new();
+    }
+    STATE.with(|state| state.hash_one(val))
+}
+
+impl<T> Internal<T> {
+    /// 写状态结束前更新散列值。调用时共享读写状态仍处于写状态。
+    pub(super) fn update_checksum(&self) {
+        if let Some(hasher) = self.checksum.hasher.get() {
+            // 写状态下没有其他副本能访问共享对象
+            let val = unsafe { &*self.val.as_ptr() };
+            self.checksum.value.set(Some(hasher(val)))
+        }
+    }
+
+    /// 获取读取权限后检查共享对象是否在写状态之外被修改。
+    ///
+    /// 调用时共享读写状态处于此次获取的读状态，发现修改时先撤销这份读状态，再按违反使用约定处理。
+    #[track_caller]
+    pub(super) fn verify_checksum(&self) {
+        let checksum = &self.checksum;
+        let (Some(hasher), Some(expected)) = (checksum.hasher.get(), checksum.value.get()) else {
+            return;
+        };
+        // 读状态下没有副本能修改共享对象
+        if hasher(unsafe { &*self.val.as_ptr() }) != expected {
+            self.read_to_hold();
+            misuse(format_args!(
+                "{} at {:#x} was modified outside of any write access",
+                std::any::type_name::<T>(),
+                self as *const Self as *const () as usize,
+            ))
+        }
+    }
+}
+
+impl<T: Hash> RwRc<T> {
+    /// 检查共享对象是否在写状态之外被修改，例如通过泄漏的原始指针。
+    ///
+    /// 启用后每次写状态结束时记录共享对象的散列值，此后每次获取读取权限时重新计算并比较，
+    /// 不一致时按违反使用约定处理。检查需要对共享对象反复求散列值，只适合调试。
+    /// 共享对象当前无法读取时，从下一次写状态结束开始检查。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let mut rc = RwRc::new(vec![1]);
+    /// rc.check_integrity();
+    /// rc.write().push(2);
+    /// assert_eq!(*rc.read(), [1, 2]);
+    ///
+    /// // 绕过写状态修改共享对象
+    /// let ptr = {
+    ///     let mut guard = rc.write();
+    ///     &mut *guard as *mut Vec<i32>
+    /// };
+    /// rc.release();
+    /// unsafe { (*ptr).push(3) };
+    /// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| rc.try_read_global()));
+    /// assert!(result.is_err());
+    /// ```
+    pub fn check_integrity(&self) {
+        self.rc.check_thread();
+        let checksum = &self.rc.checksum;
+        checksum.hasher.set(Some(hash_of::<T>));
+        checksum
+            .value
+            .set(self.try_read().map(|val| hash_of::<T>(&val)))
+    }
+}
+
+#[test]
+fn test_check_integrity() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    let mut rc = RwRc::new(1);
+    let other = rc.clone();
+    other.release();
+    rc.check_integrity();
+    *rc.write() = 2;
+    assert!(other.try_read_global());
+    other.release();
+
+    // 写状态之外的修改在下一次获取读取权限时被发现
+    let ptr = rc.rc.val.as_ptr();
+    rc.release();
+    unsafe { *ptr = 3 };
+    let err = catch_unwind(AssertUnwindSafe(|| other.try_read_global())).unwrap_err();
+    let msg = err.downcast_ref::<String>().unwrap();
+    assert!(
+        msg.contains("modified outside of any write access"),
+        "{msg}"
+    );
+
+    // 写状态结束时重新记录
+    *rc.write() = 4;
+    assert!(other.try_read_global());
+    assert_eq!(*rc.read(), 4);
+
+    // 启用时被写锁定的共享对象从下一次提交开始检查
+    let writer = RwRc::new(0);
+    let reader = writer.clone();
+    reader.release();
+    assert!(writer.try_write_global());
+    reader.check_integrity();
+    assert!(reader.rc.checksum.value.get().is_none());
+    writer.release();
+    assert!(reader.rc.checksum.value.get().is_some());
+    drop(writer);
+    assert_eq!(*reader.read(), 0);
+}
@@ -1,22 +1,146 @@
 #![doc = include_str!("../README.md")]
 #![deny(warnings, missing_docs)]
 
+#[cfg(feature = "derive")]
+extern crate self as rwrc;
+
+pub mod align;
+#[cfg(feature = "audit")]
+mod audit;
+mod borrowed;
+pub mod broadcast;
+mod builder;
+#[cfg(feature = "yoke")]
+mod cart;
+#[cfg(feature = "bytemuck")]
+mod cast;
+mod collect;
+pub mod collections;
+mod commit;
+mod conflict;
+#[cfg(feature = "deadlock-detect")]
+mod deadlock;
+pub mod debug;
+mod domain;
+mod double;
+mod error;
+mod ext;
+mod external;
+mod extras;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod finalize;
 mod flag;
+mod heap;
+mod hierarchy;
+#[cfg(feature = "hooks")]
+mod hooks;
+#[cfg(feature = "integrity-check")]
+mod integrity;
 mod local;
+mod mapped;
+#[cfg(feature = "memprof")]
+pub mod memprof;
+mod merge;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod mock;
+mod notify;
+mod order;
+mod part;
+#[cfg(feature = "serde")]
+mod persist;
+mod pointer;
+mod policy;
+#[cfg(feature = "lock_api")]
+mod raw;
+mod rcu;
+pub mod registry;
+pub mod sched;
+mod shared;
+pub mod signal;
+mod slim;
+mod static_rc;
+pub mod stm;
+mod store;
+mod teardown;
+mod tls;
+mod token;
+mod trace;
+#[cfg(feature = "transition-log")]
+mod transition;
+mod unwind;
+mod wait;
 mod weak;
+mod zip;
 
 use flag::RwFlag;
-use std::{cell::Cell, rc::Rc};
+use std::{
+    cell::{Cell, OnceCell},
+    rc::Rc,
+};
 
+pub use borrowed::{RwAnchor, RwRcRef};
+pub use builder::RwRcBuilder;
+#[cfg(feature = "yoke")]
+pub use cart::ReadCart;
+#[cfg(feature = "bytemuck")]
+pub use cast::CastError;
+pub use collect::collect_cycles;
+pub use conflict::ConflictReport;
+pub use domain::{DomainRc, LockDomain};
+pub use double::DoubleBuffer;
+pub use error::LockError;
+pub use ext::{MapEntry, RwRcBytesExt, RwRcMapExt, RwRcStringExt, RwRcVecExt};
+pub use external::External;
+#[cfg(feature = "ffi")]
+pub use ffi::FfiRwRc;
+pub use heap::HeapSize;
 pub use local::{LocalMut, LocalRef};
-pub use weak::RwWeak;
+pub use mapped::{MappedLocalMut, MappedLocalRef};
+#[cfg(feature = "mmap")]
+pub use memmap2::{Mmap, MmapMut};
+pub use mock::TestRwRc;
+pub use order::{LockOrder, Scope, read_all, scope, with_read2, with_write2, write_all};
+pub use part::RwPart;
+#[cfg(feature = "serde")]
+pub use persist::Persistent;
+pub use policy::{MisusePolicy, misuse_policy, set_misuse_policy};
+#[cfg(feature = "lock_api")]
+pub use raw::{RwFlagRaw, RwLock};
+#[cfg(feature = "derive")]
+pub use rwrc_derive::Trace;
+pub use shared::SharedRw;
+pub use slim::SlimRwRc;
+pub use static_rc::{StaticRwCell, StaticRwRc};
+pub use store::ComponentStore;
+pub use tls::RwRcLocalKey;
+pub use token::{RwRcCell, RwRcOwner};
+pub use trace::{Trace, Tracer, detect_cycles};
+#[cfg(feature = "transition-log")]
+pub use transition::Transition;
+pub use wait::{Acquire, Until};
+pub use weak::{RwWeak, RwWeakRead};
 
 /// 带有预期读写状态的引用计数。
+///
+/// 副本的读写状态保存在 `Cell` 中，获取和释放读写状态只需要共享引用，
+/// 因此副本可以放在其他 `Rc` 中通过共享引用操作。
+/// 可变借用共享对象仍然需要独占副本。
 pub struct RwRc<T> {
     /// 共享的对象和状态。
     rc: Rc<Internal<T>>,
     /// 此副本占用的读写状态。
-    state: RwState,
+    state: Cell<RwState>,
+    /// 此副本是否登记了等待中的写意图。
+    pending: Cell<bool>,
+    /// 此副本上存活的借用。
+    guards: local::GuardTracker,
+    /// 从持有状态获取的只读借用释放后是否保留读状态。
+    sticky: Cell<bool>,
+    /// 通过异步获取持有此副本读写状态的任务。
+    #[cfg(feature = "deadlock-detect")]
+    task: Cell<Option<usize>>,
 }
 
 /// 共享的对象和状态。
@@ -25,17 +149,193 @@ struct Internal<T> {
     val: Cell<T>,
     /// 共享读写状态。
     flag: RwFlag,
+    /// 读写偏好策略。
+    preference: Preference,
+    /// 是否有副本处于可升级的读状态。
+    upgradable: Cell<bool>,
+    /// 不常用的状态，第一次使用时分配。
+    extras: OnceCell<Box<extras::Extras<T>>>,
+    /// 最近一次写状态结束时共享对象的散列值。
+    #[cfg(feature = "integrity-check")]
+    checksum: integrity::Checksum<T>,
+    /// 克隆和丢弃副本时调用的钩子。
+    #[cfg(feature = "hooks")]
+    hooks: hooks::Hooks,
+    /// 按类型统计分配的计数标记。
+    #[cfg(feature = "memprof")]
+    _memprof: memprof::Token<T>,
+    /// 创建共享对象的线程。
+    #[cfg(feature = "thread-check")]
+    thread: std::thread::ThreadId,
+    /// 副本和借用记录的访问权限。
+    #[cfg(feature = "audit")]
+    ledger: audit::Ledger,
+    /// 最近的读写状态转换记录。
+    #[cfg(feature = "transition-log")]
+    transitions: transition::TransitionLog,
+}
+
+impl<T> Internal<T> {
+    /// 以指定的共享读写状态和读写偏好策略创建共享对象。
+    fn new(val: T, flag: RwFlag, preference: Preference) -> Self {
+        Self {
+            val: Cell::new(val),
+            flag,
+            preference,
+            upgradable: Cell::new(false),
+            extras: OnceCell::new(),
+            #[cfg(feature = "integrity-check")]
+            checksum: Default::default(),
+            #[cfg(feature = "hooks")]
+            hooks: Default::default(),
+            #[cfg(feature = "memprof")]
+            _memprof: memprof::Token::new(),
+            #[cfg(feature = "thread-check")]
+            thread: std::thread::current().id(),
+            #[cfg(feature = "audit")]
+            ledger: Default::default(),
+            #[cfg(feature = "transition-log")]
+            transitions: Default::default(),
+        }
+    }
+
+    /// 从持有状态获取一份读状态。
+    ///
+    /// 共享读写状态从无锁定变为锁定时需要同时占用父对象的一份读状态。
+    fn hold_to_read(&self) -> bool {
+        if self.is_reader_limited() || self.flag.is_writeable() && !self.enter_parent() {
+            return false;
+        }
+        let ok = self.flag.hold_to_read();
+        #[cfg(feature = "integrity-check")]
+        if ok {
+            self.verify_checksum()
+        }
+        ok
+    }
+
+    /// 从持有状态获取写状态。
+    fn hold_to_write(&self) -> bool {
+        if self.is_read_only() || !self.flag.is_writeable() || !self.enter_parent() {
+            return false;
+        }
+        self.flag.hold_to_write()
+    }
+
+    /// 从读状态升级到写状态。
+    fn read_to_write(&self) -> bool {
+        !self.is_read_only() && self.flag.read_to_write()
+    }
+
+    /// 判断共享对象是否拒绝写状态。
+    fn is_read_only(&self) -> bool {
+        #[cfg(feature = "mmap")]
+        {
+            self.extras().is_some_and(|e| e.read_only.get())
+        }
+        #[cfg(not(feature = "mmap"))]
+        {
+            false
+        }
+    }
+
+    /// 判断能否从持有状态获取读状态，不考虑读写偏好策略。
+    fn can_read(&self) -> bool {
+        self.flag.is_readable()
+            && !self.is_reader_limited()
+            && (!self.flag.is_writeable() || self.can_enter_parent())
+    }
+
+    /// 判断读状态的数量是否已经达到上限。
+    fn is_reader_limited(&self) -> bool {
+        self.extras()
+            .is_some_and(|e| self.flag.readers() >= e.max_readers.get())
+    }
+
+    /// 判断能否从持有状态获取写状态。
+    fn can_write(&self) -> bool {
+        !self.is_read_only() && self.flag.is_writeable() && self.can_enter_parent()
+    }
+
+    /// 释放一份读状态，共享读写状态变为无锁定时释放父对象的读状态。
+    fn read_to_hold(&self) {
+        self.flag.read_to_hold();
+        if self.flag.is_writeable() {
+            self.leave_parent()
+        }
+    }
+
+    /// 释放写状态，同时释放父对象的读状态。
+    fn write_to_hold(&self) {
+        self.commit();
+        self.flag.write_to_hold();
+        self.leave_parent()
+    }
+
+    /// 从写状态降级到读状态。
+    fn write_to_read(&self) {
+        self.commit();
+        self.flag.write_to_read()
+    }
+
+    /// 检查当前线程是否是创建共享对象的线程。
+    ///
+    /// 仅在启用 `thread-check` 特性时检查。
+    #[inline(always)]
+    fn check_thread(&self) {
+        #[cfg(feature = "thread-check")]
+        {
+            let current = std::thread::current().id();
+            if current != self.thread {
+                policy::misuse(format_args!(
+                    "RwRc created on {:?} is used on {current:?}",
+                    self.thread
+                ))
+            }
+        }
+    }
+}
+
+/// 访问权限类型。
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Access {
+    /// 读权限。
+    Read,
+    /// 写权限。
+    Write,
+}
+
+/// 读写偏好策略。
+///
+/// 决定存在等待中的写者时，其他副本能否继续获取新的读状态。
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Preference {
+    /// 读优先：只要没有副本持有写状态，就允许获取读状态。
+    #[default]
+    Read,
+    /// 写优先：有副本获取写状态失败后会登记写意图，
+    /// 在它获得写状态或放弃之前，其他副本无法获取新的读状态。
+    Write,
 }
 
 /// 副本读写状态。
 ///
 /// 表示 `RwRc` 实例当前的读写状态。
-#[derive(Clone, Copy, Debug)]
-enum RwState {
+/// 克隆时只有读状态会复制，其他状态的副本克隆为持有状态。
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RwState {
     /// 持有（不关心读写）。
     Hold,
     /// 预期读，禁止修改。
     Read,
+    /// 可升级的读，与其他读者共存，但排斥其他可升级的读者和写者。
+    ///
+    /// 由 [`RwRc::try_upgradable_global`] 获取，
+    /// 之后可以在其他读者全部释放后通过 [`RwRc::try_write_global`] 升级到写状态。
+    Upgradable,
     /// 预期写，限制读写。
     Write,
 }
@@ -49,55 +349,189 @@ impl<T> From<T> for RwRc<T> {
 impl<T> Clone for RwRc<T> {
     /// 克隆 `RwRc<T>` 实例。
     /// 只有当源对象在读状态时，克隆的对象才会设置读状态，否则设置为持有状态。
+    /// 读状态的数量达到上限，或者写优先策略下有副本登记了写意图时，克隆的对象也设置为持有状态。
     fn clone(&self) -> Self {
         // 复制读写锁时，先原样复制一个
-        let mut ans = Self {
-            rc: self.rc.clone(),
-            state: RwState::Hold,
-        };
+        let ans = Self::from_rc(self.rc.clone(), RwState::Hold);
         // 如果当前对象在读状态，复制的对象也设置读状态
-        if matches!(self.state, RwState::Read) {
-            ans.state = RwState::Read;
-            assert!(ans.rc.flag.hold_to_read())
+        if matches!(self.state.get(), RwState::Read) && ans.hold_to_read() {
+            ans.set_state(RwState::Read);
         }
+        #[cfg(feature = "hooks")]
+        Self::hook_cloned(&ans.rc);
         ans
     }
 }
 
+#[cfg(feature = "defmt")]
+impl<T> defmt::Format for RwRc<T> {
+    /// 只输出副本的读写状态和强引用计数，不访问共享对象。
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "RwRc {{ state: {}, strong: {=usize} }}",
+            self.state.get(),
+            Rc::strong_count(&self.rc)
+        )
+    }
+}
+
 impl<T> Drop for RwRc<T> {
     fn drop(&mut self) {
+        self.rc.check_thread();
+        // 丢弃时不可能存在借用，被遗忘的借用不应阻止释放
+        self.guards.reset();
         // 释放对象时也释放对象占用的锁
-        self.release()
+        self.release();
+        self.finalize();
+        #[cfg(feature = "hooks")]
+        self.rc.hooks.dropped(Rc::strong_count(&self.rc) - 1)
     }
 }
 
 impl<T> RwRc<T> {
     /// 从对象初始化读写锁时，直接设置到读状态。
     pub fn new(val: T) -> Self {
+        Self::with_preference(val, Preference::Read)
+    }
+
+    /// 以指定的读写偏好策略初始化读写锁，初始为读状态。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{Preference, RwRc};
+    ///
+    /// let mut writer = RwRc::with_preference(42, Preference::Write);
+    /// let mut reader = writer.clone();
+    /// writer.release();
+    ///
+    /// // reader 持有读状态，writer 获取写状态失败并登记写意图
+    /// assert!(!writer.try_write_global());
+    /// // 写意图存在时，其他副本无法获取新的读状态
+    /// reader.release();
+    /// assert!(!reader.try_read_global());
+    /// // 写者获得写状态后，写意图消除
+    /// assert!(writer.try_write_global());
+    /// writer.release();
+    /// assert!(reader.try_read_global());
+    /// ```
+    pub fn with_preference(val: T, preference: Preference) -> Self {
+        let rc = Rc::new(Internal::new(val, RwFlag::new_read(), preference));
+        #[cfg(feature = "audit")]
+        audit::register(&rc);
+        Self::from_rc(rc, RwState::Read)
+    }
+
+    /// 从共享对象构造副本，`state` 必须与共享读写状态一致。
+    fn from_rc(rc: Rc<Internal<T>>, state: RwState) -> Self {
+        rc.check_thread();
+        if let RwState::Upgradable = state {
+            rc.upgradable.set(true)
+        }
+        #[cfg(feature = "audit")]
+        rc.ledger.transition(None, Some(state));
         Self {
-            rc: Rc::new(Internal {
-                val: Cell::new(val),
-                flag: RwFlag::new_read(),
-            }),
-            state: RwState::Read,
+            rc,
+            state: Cell::new(state),
+            pending: Cell::new(false),
+            guards: Default::default(),
+            sticky: Cell::new(false),
+            #[cfg(feature = "deadlock-detect")]
+            task: Cell::new(None),
         }
     }
 
+    /// 共享对象的标识，即共享对象的地址。
+    ///
+    /// 同一个共享对象的所有副本和弱引用有相同的标识，共享对象存活期间不变；
+    /// 共享对象释放后地址可能被新的共享对象复用。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let rc = RwRc::new(0);
+    /// assert_eq!(rc.id(), rc.clone().id());
+    /// assert_eq!(rc.id(), rc.weak().id());
+    /// assert_ne!(rc.id(), RwRc::new(0).id());
+    /// ```
+    pub fn id(&self) -> usize {
+        Rc::as_ptr(&self.rc) as *const () as usize
+    }
+
+    /// 此副本当前的读写状态。
+    pub fn state(&self) -> RwState {
+        self.state.get()
+    }
+
     /// 判断是否可读。
     /// 会结合共享读写状态进行判断。
     pub fn is_readable(&self) -> bool {
-        match self.state {
-            RwState::Hold => self.rc.flag.is_readable(),
-            RwState::Read | RwState::Write => true,
+        self.rc.check_thread();
+        match self.state.get() {
+            RwState::Hold => self.rc.can_read(),
+            RwState::Read | RwState::Upgradable | RwState::Write => true,
+        }
+    }
+
+    /// 设置共享对象同时持有读状态的副本数量上限，`None` 表示不限制。
+    ///
+    /// 达到上限后，处于持有状态的副本无法获取读状态，
+    /// 已经持有读状态的副本不受影响。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{LockError, RwRc, RwRcVecExt};
+    ///
+    /// let rc = RwRc::new(vec![1]);
+    /// rc.set_max_readers(Some(1));
+    /// let other = rc.clone();
+    /// other.release();
+    /// assert!(!other.try_read_global());
+    /// assert_eq!(other.len_read(), Err(LockError::ReaderLimit));
+    /// ```
+    pub fn set_max_readers(&self, max: Option<usize>) {
+        self.rc.check_thread();
+        match max {
+            Some(max) => self.rc.ensure_extras().max_readers.set(max),
+            None => {
+                if let Some(e) = self.rc.extras() {
+                    e.max_readers.set(usize::MAX)
+                }
+            }
+        }
+    }
+
+    /// 共享对象同时持有读状态的副本数量上限，`None` 表示不限制。
+    pub fn max_readers(&self) -> Option<usize> {
+        let max = self.rc.extras().map_or(usize::MAX, |e| e.max_readers.get());
+        Some(max).filter(|&max| max != usize::MAX)
+    }
+
+    /// 此副本无法获取读状态时的错误。
+    fn read_error(&self) -> LockError {
+        if matches!(self.state.get(), RwState::Hold)
+            && self.rc.flag.is_readable()
+            && self.rc.is_reader_limited()
+        {
+            LockError::ReaderLimit
+        } else {
+            LockError::Blocked(Access::Read)
         }
     }
 
     /// 判断是否可写。
     /// 会结合全局状态进行判断。
     pub fn is_writeable(&self) -> bool {
-        match self.state {
-            RwState::Hold => self.rc.flag.is_writeable(),
-            RwState::Read => self.rc.flag.is_this_writeable(),
+        self.rc.check_thread();
+        match self.state.get() {
+            RwState::Hold => self.rc.can_write(),
+            RwState::Read | RwState::Upgradable => {
+                !self.rc.is_read_only() && self.rc.flag.is_this_writeable()
+            }
             RwState::Write => true,
         }
     }
@@ -107,16 +541,77 @@ impl<T> RwRc<T> {
     /// 尝试将当前实例设置为读状态，使其可以安全地读取数据。
     /// 如果当前全局状态允许新的读取操作，则会将实例设置为读状态，返回 `true`
     /// 否则当有其他对象持有写状态导致无法获取读状态时，返回 `false`。
-    pub fn try_read_global(&mut self) -> bool {
-        match self.state {
+    ///
+    /// # Panic
+    ///
+    /// 需要改变读写状态而此副本正被借用时 panic。
+    #[track_caller]
+    pub fn try_read_global(&self) -> bool {
+        self.rc.check_thread();
+        match self.state.get() {
             RwState::Hold => {
-                if !self.rc.flag.hold_to_read() {
+                self.guards.check_state_change();
+                if !self.hold_to_read() {
                     return false;
                 }
-                self.state = RwState::Read
+                self.set_state(RwState::Read);
             }
-            RwState::Read | RwState::Write => {}
+            RwState::Read | RwState::Upgradable | RwState::Write => {}
+        }
+        true
+    }
+
+    /// 尝试设置到可升级的读状态。
+    ///
+    /// 可升级的读者与其他读者共存，但同一时间只有一个副本可以处于可升级的读状态，
+    /// 其他副本也无法获取写状态，因此检查后再修改的过程中不会被其他写者插入。
+    /// 之后调用 [`try_write_global`](Self::try_write_global) 在其他读者全部释放后升级到写状态。
+    /// 已经在读状态的副本可以转换为可升级的读状态；已经在写状态时不改变状态并返回 `true`。
+    ///
+    /// 其他副本持有写状态或可升级的读状态时返回 `false`。
+    ///
+    /// # Panic
+    ///
+    /// 需要改变读写状态而此副本正被借用时 panic。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let checker = RwRc::new(vec![1]);
+    /// let reader = checker.clone();
+    /// let other = checker.clone();
+    /// other.release();
+    ///
+    /// assert!(checker.try_upgradable_global());
+    /// // 可升级的读与普通读者共存，但排斥其他可升级的读者和写者
+    /// assert!(other.try_read_global());
+    /// other.release();
+    /// assert!(!other.try_upgradable_global());
+    /// assert!(!other.try_write_global());
+    ///
+    /// // 其他读者释放后升级到写状态
+    /// assert!(!checker.try_write_global());
+    /// drop(reader);
+    /// assert!(checker.try_write_global());
+    /// ```
+    #[track_caller]
+    pub fn try_upgradable_global(&self) -> bool {
+        self.rc.check_thread();
+        match self.state.get() {
+            RwState::Upgradable | RwState::Write => return true,
+            RwState::Hold | RwState::Read => self.guards.check_state_change(),
         }
+        if self.rc.upgradable.get() {
+            return false;
+        }
+        if let RwState::Hold = self.state.get()
+            && !self.hold_to_read()
+        {
+            return false;
+        }
+        self.set_state(RwState::Upgradable);
         true
     }
 
@@ -125,12 +620,32 @@ impl<T> RwRc<T> {
     /// 尝试将当前实例设置为写状态，使其可以安全地修改数据。
     /// 如果没有其他对象持有读状态或写状态时，则会将实例设置为写状态，返回 `true`，
     /// 否则当有其他对象持有读状态或写状态时，返回 `false`。
-    pub fn try_write_global(&mut self) -> bool {
-        match self.state {
-            RwState::Hold if !self.rc.flag.hold_to_write() => false,
-            RwState::Read if !self.rc.flag.read_to_write() => false,
+    ///
+    /// 对于写优先策略的对象，获取失败会为此副本登记写意图，
+    /// 直到此副本获得写状态或调用 [`release`](Self::release)。
+    ///
+    /// # Panic
+    ///
+    /// 需要改变读写状态而此副本正被借用时 panic。
+    #[track_caller]
+    pub fn try_write_global(&self) -> bool {
+        self.rc.check_thread();
+        if self.state.get() != RwState::Write {
+            self.guards.check_state_change()
+        }
+        match self.state.get() {
+            RwState::Hold if !self.rc.hold_to_write() => {
+                self.register_intent();
+                false
+            }
+            RwState::Read | RwState::Upgradable if !self.rc.read_to_write() => {
+                self.register_intent();
+                false
+            }
             _ => {
-                self.state = RwState::Write;
+                self.clear_intent();
+                self.set_state(RwState::Write);
+                self.rc.apply_mutations();
                 true
             }
         }
@@ -140,12 +655,91 @@ impl<T> RwRc<T> {
     ///
     /// 将当前实例从读状态或写状态释放回持有状态，允许其他实例获取读或写权限。
     /// 当不再需要访问数据时，应该调用此方法释放状态。
+    /// 此方法同时会撤销此副本登记的写意图。
     /// `Drop` 会自动调用此方法。
-    pub fn release(&mut self) {
-        match std::mem::replace(&mut self.state, RwState::Hold) {
-            RwState::Hold => {}
-            RwState::Read => self.rc.flag.read_to_hold(),
-            RwState::Write => self.rc.flag.write_to_hold(),
+    ///
+    /// # Panic
+    ///
+    /// 需要改变读写状态而此副本正被借用时 panic。
+    #[track_caller]
+    pub fn release(&self) {
+        self.rc.check_thread();
+        if self.state.get() != RwState::Hold {
+            self.guards.check_state_change()
+        }
+        self.clear_intent();
+        match self.set_state(RwState::Hold) {
+            RwState::Hold => return,
+            RwState::Read | RwState::Upgradable => self.rc.read_to_hold(),
+            RwState::Write => self.rc.write_to_hold(),
+        }
+        self.notify_released()
+    }
+
+    /// 如果此副本是唯一的强引用，释放读写状态并取出共享对象，否则返回原副本。
+    fn try_into_value(self) -> Result<T, Self> {
+        if Rc::strong_count(&self.rc) != 1 {
+            return Err(self);
+        }
+        let mut this = std::mem::ManuallyDrop::new(self);
+        this.guards.reset();
+        this.release();
+        this.finalize();
+        #[cfg(feature = "hooks")]
+        this.rc.hooks.dropped(0);
+        // 副本不会再被使用，其余字段不需要释放
+        let rc = unsafe { std::ptr::read(&this.rc) };
+        match Rc::try_unwrap(rc) {
+            Ok(internal) => Ok(internal.val.into_inner()),
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// 从持有状态获取一份读状态，会考虑读写偏好策略。
+    fn hold_to_read(&self) -> bool {
+        !self.is_read_blocked() && self.rc.hold_to_read()
+    }
+
+    /// 判断新的读取是否被其他副本的写意图阻止。
+    fn is_read_blocked(&self) -> bool {
+        self.rc.pending_writers() > self.pending.get() as usize
+    }
+
+    /// 设置此副本的读写状态并返回原来的状态，同时维护共享对象上的可升级读标记。
+    #[track_caller]
+    fn set_state(&self, state: RwState) -> RwState {
+        let old = self.state.replace(state);
+        #[cfg(feature = "audit")]
+        self.rc.ledger.transition(Some(old), Some(state));
+        #[cfg(feature = "transition-log")]
+        if old != state {
+            self.rc.transitions.record(old, state)
+        }
+        #[cfg(feature = "deadlock-detect")]
+        if state == RwState::Hold {
+            self.unhold()
+        }
+        let upgradable = matches!(state, RwState::Upgradable);
+        if matches!(old, RwState::Upgradable) != upgradable {
+            self.rc.upgradable.set(upgradable)
+        }
+        old
+    }
+
+    /// 为写优先策略的对象登记写意图。
+    fn register_intent(&self) {
+        if matches!(self.rc.preference, Preference::Write) && !self.pending.get() {
+            self.pending.set(true);
+            let internal = &self.rc.ensure_extras().pending_writers;
+            internal.set(internal.get() + 1)
+        }
+    }
+
+    /// 撤销登记的写意图。
+    fn clear_intent(&self) {
+        if self.pending.replace(false) {
+            let internal = &self.rc.ensure_extras().pending_writers;
+            internal.set(internal.get() - 1)
         }
     }
 }
@@ -153,7 +747,7 @@ impl<T> RwRc<T> {
 #[test]
 fn test_new() {
     let rc = RwRc::new(42);
-    assert!(matches!(rc.state, RwState::Read));
+    assert!(matches!(rc.state.get(), RwState::Read));
     assert!(rc.is_readable());
     assert!(rc.is_writeable());
 }
@@ -164,34 +758,34 @@ fn test_clone() {
     let rc2 = rc1.clone();
 
     // 克隆时原对象在读状态，克隆对象也应处于读状态
-    assert!(matches!(rc1.state, RwState::Read));
-    assert!(matches!(rc2.state, RwState::Read));
+    assert!(matches!(rc1.state.get(), RwState::Read));
+    assert!(matches!(rc2.state.get(), RwState::Read));
 
     // 创建一个新对象并释放读状态
-    let mut rc3 = RwRc::new(100);
+    let rc3 = RwRc::new(100);
     rc3.release();
-    assert!(matches!(rc3.state, RwState::Hold));
+    assert!(matches!(rc3.state.get(), RwState::Hold));
 
     // 克隆时原对象在持有状态，克隆对象也应处于持有状态
     let rc4 = rc3.clone();
-    assert!(matches!(rc4.state, RwState::Hold));
+    assert!(matches!(rc4.state.get(), RwState::Hold));
 }
 
 #[test]
 fn test_try_read_global() {
-    let mut rc1 = RwRc::new(42);
+    let rc1 = RwRc::new(42);
     rc1.release(); // 先释放到持有状态
 
     // 尝试获取读状态
     assert!(rc1.try_read_global());
-    assert!(matches!(rc1.state, RwState::Read));
+    assert!(matches!(rc1.state.get(), RwState::Read));
     assert!(rc1.is_readable());
 
     // 已在读状态时再次获取读状态
     assert!(rc1.try_read_global());
 
     // 创建一个新的引用并获取写状态
-    let mut rc2 = rc1.clone();
+    let rc2 = rc1.clone();
     rc2.release(); // 释放到持有状态
 
     // rc1在读状态，rc2应该无法获取写状态
@@ -202,7 +796,7 @@ fn test_try_read_global() {
 
     // 现在rc2应该可以获取写状态
     assert!(rc2.try_write_global());
-    assert!(matches!(rc2.state, RwState::Write));
+    assert!(matches!(rc2.state.get(), RwState::Write));
 
     // 当rc2持有写状态时，rc1应该无法获取读状态
     assert!(!rc1.try_read_global());
@@ -210,17 +804,17 @@ fn test_try_read_global() {
 
 #[test]
 fn test_try_write_global() {
-    let mut rc1 = RwRc::new(42);
+    let rc1 = RwRc::new(42);
     rc1.release(); // 先释放到持有状态
 
     // 尝试获取写状态
     assert!(rc1.try_write_global());
-    assert!(matches!(rc1.state, RwState::Write));
+    assert!(matches!(rc1.state.get(), RwState::Write));
     assert!(rc1.is_readable());
     assert!(rc1.is_writeable());
 
     // 创建一个新的引用
-    let mut rc2 = rc1.clone();
+    let rc2 = rc1.clone();
 
     // rc1在写状态，rc2应该无法获取读状态或写状态
     assert!(!rc2.try_read_global());
@@ -231,10 +825,10 @@ fn test_try_write_global() {
 
     // 现在rc2应该可以获取读状态
     assert!(rc2.try_read_global());
-    assert!(matches!(rc2.state, RwState::Read));
+    assert!(matches!(rc2.state.get(), RwState::Read));
 
     // 再创建一个新的引用
-    let mut rc3 = rc1.clone();
+    let rc3 = rc1.clone();
 
     // rc2在读状态，rc3应该可以获取读状态但不能获取写状态
     assert!(rc3.try_read_global());
@@ -244,16 +838,16 @@ fn test_try_write_global() {
 
 #[test]
 fn test_drop() {
-    let mut rc1 = RwRc::new(42);
+    let rc1 = RwRc::new(42);
 
     // 创建一个作用域，在作用域中创建一个新的引用并获取写状态
     {
-        let mut rc2 = rc1.clone();
+        let rc2 = rc1.clone();
         rc1.release(); // 释放rc1的读状态
 
         // rc2获取写状态
         assert!(rc2.try_write_global());
-        assert!(matches!(rc2.state, RwState::Write));
+        assert!(matches!(rc2.state.get(), RwState::Write));
 
         // 此时rc1应该无法获取读状态
         assert!(!rc1.try_read_global());
@@ -263,14 +857,14 @@ fn test_drop() {
 
     // 作用域结束后，rc2应该已释放写状态，rc1应该可以获取读状态
     assert!(rc1.try_read_global());
-    assert!(matches!(rc1.state, RwState::Read));
+    assert!(matches!(rc1.state.get(), RwState::Read));
 }
 
 #[test]
 fn test_multiple_readers() {
-    let mut rc1 = RwRc::new(42);
-    let mut rc2 = rc1.clone();
-    let mut rc3 = rc1.clone();
+    let rc1 = RwRc::new(42);
+    let rc2 = rc1.clone();
+    let rc3 = rc1.clone();
 
     // 所有对象都释放到持有状态
     rc1.release();
@@ -298,23 +892,23 @@ fn test_multiple_readers() {
 fn test_from() {
     // 测试从基本类型转换
     let rc: RwRc<i32> = 42.into();
-    assert!(matches!(rc.state, RwState::Read));
+    assert!(matches!(rc.state.get(), RwState::Read));
     assert!(rc.is_readable());
 
     // 测试从字符串转换
     let rc: RwRc<String> = String::from("test").into();
-    assert!(matches!(rc.state, RwState::Read));
+    assert!(matches!(rc.state.get(), RwState::Read));
     assert!(rc.is_readable());
 
     // 测试显式使用 From trait
     let rc = RwRc::from(100);
-    assert!(matches!(rc.state, RwState::Read));
+    assert!(matches!(rc.state.get(), RwState::Read));
     assert!(rc.is_readable());
 }
 
 #[test]
 fn test_hold() {
-    let mut rc = RwRc::new(42);
+    let rc = RwRc::new(42);
     assert!(rc.is_readable()); // 新建对象默认在读状态，应该可读
 
     // 测试持有状态下的可读性
@@ -324,3 +918,189 @@ fn test_hold() {
     assert!(rc.is_writeable()); // Hold状态且全局可写时应该可写
     assert!(rc.try_read_global()); // 单个实例hold状态设置读状态，应该是可读的
 }
+
+#[test]
+fn test_write_preference() {
+    let writer = RwRc::with_preference(42, Preference::Write);
+    let reader = writer.clone();
+    writer.release();
+
+    // 读者持有读状态，写者获取失败并登记写意图
+    assert!(!writer.try_write_global());
+    assert!(writer.pending.get());
+
+    // 写意图存在时，其他副本无法获取新的读状态
+    reader.release();
+    assert!(!reader.try_read_global());
+    assert!(reader.try_read().is_none());
+
+    // 写者获得写状态后写意图消除
+    assert!(writer.try_write_global());
+    assert!(!writer.pending.get());
+    writer.release();
+    assert!(reader.try_read_global());
+}
+
+#[test]
+fn test_write_preference_clone() {
+    let writer = RwRc::with_preference(42, Preference::Write);
+    let reader = writer.clone();
+    writer.release();
+    assert!(!writer.try_write_global());
+
+    // 写意图存在时，读状态的副本克隆为持有状态，不会继续占用读状态
+    let clone = reader.clone();
+    assert_eq!(clone.state(), RwState::Hold);
+    drop(reader);
+    assert!(writer.try_write_global());
+    writer.release();
+    assert!(clone.try_read_global());
+    assert_eq!(clone.clone().state(), RwState::Read);
+}
+
+#[test]
+fn test_cancel_intent() {
+    let writer = RwRc::with_preference(42, Preference::Write);
+    let reader = writer.clone();
+    writer.release();
+    assert!(!writer.try_write_global());
+    reader.release();
+    assert!(!reader.try_read_global());
+
+    // 释放写者即撤销写意图
+    writer.release();
+    assert!(reader.try_read_global());
+
+    // 丢弃写者同样撤销写意图
+    assert!(!writer.try_write_global());
+    let another = reader.clone();
+    another.release();
+    assert!(!another.try_read_global());
+    drop(writer);
+    assert!(another.try_read_global());
+}
+
+#[test]
+fn test_read_preference() {
+    let writer = RwRc::new(42);
+    let reader = writer.clone();
+    writer.release();
+
+    // 读优先策略不登记写意图
+    assert!(!writer.try_write_global());
+    assert!(!writer.pending.get());
+    reader.release();
+    assert!(reader.try_read_global());
+}
+
+#[cfg(feature = "thread-check")]
+#[test]
+fn test_thread_check() {
+    struct SendPtr(*const RwRc<i32>);
+    unsafe impl Send for SendPtr {}
+
+    let rc = RwRc::new(42);
+    let ptr = SendPtr(&rc);
+    let result = std::thread::spawn(move || {
+        let ptr = ptr;
+        unsafe { &*ptr.0 }.is_readable()
+    })
+    .join();
+    let msg = result.unwrap_err();
+    assert!(msg.downcast_ref::<String>().unwrap().contains("is used on"));
+    assert!(rc.is_readable())
+}
+
+#[test]
+fn test_state_through_shared_ref() {
+    let shared = Rc::new(RwRc::new(1));
+    let other = Rc::new((*shared).clone());
+    shared.release();
+    other.release();
+
+    // 通过共享引用获取和释放读写状态
+    assert!(shared.try_write_global());
+    assert!(!other.try_read_global());
+    shared.release();
+    assert!(other.try_read_global());
+    assert_eq!(*shared.read(), 1);
+    other.release();
+}
+
+#[test]
+#[should_panic(expected = "while it is borrowed")]
+fn test_release_while_borrowed() {
+    let rc = RwRc::new(1);
+    let _guard = rc.read();
+    rc.release()
+}
+
+#[test]
+fn test_upgradable() {
+    let a = RwRc::new(0);
+    let b = a.clone();
+    let c = a.clone();
+    c.release();
+    assert!(a.try_upgradable_global());
+    assert!(a.rc.upgradable.get());
+    // 只有一个可升级的读者
+    assert!(!b.try_upgradable_global());
+    assert!(!c.try_upgradable_global());
+    assert!(matches!(c.state.get(), RwState::Hold));
+
+    // 其他读者存在时无法升级，也无法临时写入
+    assert!(!a.try_write_global());
+    assert_eq!(a.state(), RwState::Upgradable);
+    drop(b);
+    let mut a = a;
+    *a.write() = 1;
+    assert_eq!(a.state(), RwState::Upgradable);
+    assert!(c.try_read().is_some());
+
+    // 升级后释放可升级读标记
+    assert!(a.try_write_global());
+    assert!(!a.rc.upgradable.get());
+    a.release();
+    assert!(c.try_upgradable_global());
+    assert_eq!(a.clone().state(), RwState::Hold);
+    drop(c);
+    assert!(!a.rc.upgradable.get());
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+#[cfg(not(any(
+    feature = "integrity-check",
+    feature = "hooks",
+    feature = "memprof",
+    feature = "thread-check",
+    feature = "audit",
+    feature = "transition-log",
+    feature = "deadlock-detect",
+)))]
+fn test_size() {
+    use std::mem::size_of;
+    // 共享对象、读写状态、偏好、可升级标记和指向附加状态的指针
+    #[cfg(not(feature = "compact-flag"))]
+    assert_eq!(size_of::<Internal<u8>>(), 24);
+    #[cfg(feature = "compact-flag")]
+    assert_eq!(size_of::<Internal<u8>>(), 16);
+    // 发布模式下副本只有指针、借用深度和几个状态字节，调试模式下多记录一个借用位置
+    #[cfg(not(debug_assertions))]
+    assert_eq!(size_of::<RwRc<u8>>(), 16);
+    #[cfg(debug_assertions)]
+    assert_eq!(size_of::<RwRc<u8>>(), 32);
+}
+
+#[test]
+fn test_drop_with_leaked_guard() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    let rc = RwRc::new(1);
+    let other = rc.clone();
+    std::mem::forget(rc.read());
+    // 被遗忘的借用使副本无法改变状态，但丢弃时仍然释放读状态
+    assert!(catch_unwind(AssertUnwindSafe(|| rc.release())).is_err());
+    drop(rc);
+    assert!(other.try_write_global());
+}
@@ -1,10 +1,21 @@
-use crate::{RwRc, RwState};
+use crate::{RwRc, RwState, policy::misuse};
 use std::ops::{Deref, DerefMut};
 
+use std::{
+    cell::Cell,
+    cmp::Ordering,
+    fmt,
+    io::{self, BufRead, Read, Seek, SeekFrom, Write},
+};
+
+#[cfg(debug_assertions)]
+use std::panic::Location;
+
 /// 对 `RwRc<T>` 的只读借用。
 ///
 /// 该类型表示对 `RwRc<T>` 的只读借用，允许安全地访问内部数据。
 /// 当 `LocalRef` 被丢弃时，会自动还原 `RwRc` 的读写状态。
+/// 同一个副本上存在 [`LocalMut`] 时获取只读借用会 panic，发布模式下同样检查。
 ///
 /// # 示例
 ///
@@ -17,12 +28,18 @@ use std::ops::{Deref, DerefMut};
 ///     assert_eq!(*reader, 42); // 可以读取内部值
 /// } // reader被丢弃，如果RwRc处于Hold状态，读锁会被释放
 /// ```
-pub struct LocalRef<'w, T>(&'w RwRc<T>);
+pub struct LocalRef<'w, T> {
+    /// 借用的副本。
+    pub(super) rc: &'w RwRc<T>,
+    /// 是否是从 `LocalMut` 重借用得到的，重借用不占用读写状态。
+    pub(super) reborrowed: bool,
+}
 
 /// 对 `RwRc<T>` 的可变借用。
 ///
 /// 该类型表示对 `RwRc<T>` 的可变借用，允许安全地修改内部数据。
 /// 当 `LocalMut` 被丢弃时，会自动还原 `RwRc` 的读写状态。
+/// 同一个副本上存在其他借用时获取可变借用会 panic，发布模式下同样检查。
 ///
 /// # 示例
 ///
@@ -37,6 +54,128 @@ pub struct LocalRef<'w, T>(&'w RwRc<T>);
 /// ```
 pub struct LocalMut<'w, T>(&'w mut RwRc<T>);
 
+/// 记录一个副本上存活的借用，发现别名冲突时 panic。
+///
+/// 与 `RefCell` 的借用计数类似，一个副本上可以同时存在多个只读借用，
+/// 或者唯一的可变借用。
+/// 在读状态和写状态下获取借用时不会修改共享读写状态，依靠此计数防止别名冲突。
+/// 调试模式下还会记录借用的获取位置，以便在 panic 信息中指出冲突的双方。
+/// 只读借用和可变借用不会同时存在，两者共用一个位置记录。
+///
+/// 发布模式下检查同样生效：借用不修改共享读写状态，
+/// 去掉检查会使同一个副本上的只读借用和可变借用同时存在，破坏内存安全。
+/// 发布模式下只是不记录获取位置，panic 信息中没有冲突双方的位置。
+#[derive(Default)]
+pub(super) struct GuardTracker {
+    /// 借用深度。正数表示存活的只读借用数量，`-1` 表示存在可变借用。
+    depth: Cell<i32>,
+    /// 存活的可变借用或最近一次获取只读借用的位置。
+    #[cfg(debug_assertions)]
+    location: Cell<Option<&'static Location<'static>>>,
+}
+
+impl GuardTracker {
+    /// 检查能否获取只读借用，存在可变借用时 panic。
+    #[track_caller]
+    fn check(&self) {
+        if self.depth.get() < 0 {
+            #[cfg(debug_assertions)]
+            misuse(format_args!(
+                "cannot read at {}: the same RwRc is mutably borrowed at {}",
+                Location::caller(),
+                self.location.get().unwrap()
+            ));
+            #[cfg(not(debug_assertions))]
+            misuse(format_args!(
+                "cannot read: the same RwRc is mutably borrowed"
+            ))
+        }
+    }
+
+    /// 检查能否获取可变借用，存在其他借用时 panic。
+    #[track_caller]
+    pub(super) fn check_mut(&self) {
+        match self.depth.get() {
+            0 => {}
+            #[cfg(debug_assertions)]
+            n if n < 0 => misuse(format_args!(
+                "cannot write at {}: the same RwRc is mutably borrowed at {}",
+                Location::caller(),
+                self.location.get().unwrap()
+            )),
+            #[cfg(debug_assertions)]
+            n => misuse(format_args!(
+                "cannot write at {}: the same RwRc has {n} live LocalRef, last acquired at {}",
+                Location::caller(),
+                self.location.get().unwrap()
+            )),
+            #[cfg(not(debug_assertions))]
+            n if n < 0 => misuse(format_args!(
+                "cannot write: the same RwRc is mutably borrowed"
+            )),
+            #[cfg(not(debug_assertions))]
+            n => misuse(format_args!(
+                "cannot write: the same RwRc has {n} live LocalRef"
+            )),
+        }
+    }
+
+    /// 检查能否改变副本的读写状态，存在借用时 panic。
+    ///
+    /// 借用依赖获取时副本的读写状态，借用存在期间改变状态会破坏借用的前提。
+    #[track_caller]
+    pub(super) fn check_state_change(&self) {
+        if self.depth.get() != 0 {
+            misuse(format_args!(
+                "cannot change the state of a RwRc while it is borrowed"
+            ))
+        }
+    }
+
+    /// 清除借用记录。
+    ///
+    /// 能可变借用记录时不存在活跃的借用，残留的计数来自被遗忘的借用。
+    pub(super) fn reset(&mut self) {
+        *self = Self::default()
+    }
+
+    /// 登记一个只读借用。
+    #[track_caller]
+    fn borrow(&self) {
+        let Some(n) = self.depth.get().checked_add(1) else {
+            misuse(format_args!("too many LocalRef on the same RwRc"))
+        };
+        self.depth.set(n);
+        #[cfg(debug_assertions)]
+        self.location.set(Some(Location::caller()))
+    }
+
+    /// 登记一个可变借用。
+    #[track_caller]
+    fn borrow_mut(&self) {
+        self.depth.set(-1);
+        #[cfg(debug_assertions)]
+        self.location.set(Some(Location::caller()))
+    }
+
+    /// 注销一个只读借用。
+    fn release(&self) {
+        let n = self.depth.get() - 1;
+        self.depth.set(n);
+        #[cfg(debug_assertions)]
+        if n == 0 {
+            self.location.set(None)
+        }
+    }
+
+    /// 注销可变借用。
+    fn release_mut(&self) {
+        self.depth.set(0);
+        #[cfg(debug_assertions)]
+        self.location.set(None)
+    }
+}
+
 impl<T> RwRc<T> {
     /// 尝试获取只读引用`LocalRef<T>`，如果 RwRc 没有读取权限，则会尝试获取读取权限，如果获取失败，则返回 None。
     /// Drop 后不会改变 RwRc 的读写状态。
@@ -52,17 +191,21 @@ impl<T> RwRc<T> {
     /// assert_eq!(*reader, 42);
     /// assert_eq!(*reader2, 42);
     /// ```
-    pub fn try_read(&self) -> Option<LocalRef<T>> {
-        match self.state {
-            RwState::Hold => {
-                if self.rc.flag.hold_to_read() {
-                    Some(LocalRef(self))
-                } else {
-                    None
-                }
-            }
-            RwState::Read | RwState::Write => Some(LocalRef(self)),
+    #[track_caller]
+    pub fn try_read(&self) -> Option<LocalRef<'_, T>> {
+        self.rc.check_thread();
+        self.guards.check();
+        match self.state.get() {
+            RwState::Hold if !self.hold_to_read() => return None,
+            _ => {}
         }
+        self.guards.borrow();
+        #[cfg(feature = "audit")]
+        self.record_guard(false, true);
+        Some(LocalRef {
+            rc: self,
+            reborrowed: false,
+        })
     }
 
     /// 尝试获取可变引用`LocalMut<T>`，如果 RwRc 没有写入权限，则会尝试获取写入权限，如果获取失败，则返回 None。
@@ -80,24 +223,20 @@ impl<T> RwRc<T> {
     /// drop(writer);
     /// assert_eq!(*rwrc.read(), 43);
     /// ```
-    pub fn try_write(&mut self) -> Option<LocalMut<T>> {
-        match self.state {
-            RwState::Hold => {
-                if self.rc.flag.hold_to_write() {
-                    Some(LocalMut(self))
-                } else {
-                    None
-                }
-            }
-            RwState::Read => {
-                if self.rc.flag.read_to_write() {
-                    Some(LocalMut(self))
-                } else {
-                    None
-                }
-            }
-            RwState::Write => Some(LocalMut(self)),
+    #[track_caller]
+    pub fn try_write(&mut self) -> Option<LocalMut<'_, T>> {
+        self.rc.check_thread();
+        self.guards.check_mut();
+        match self.state.get() {
+            RwState::Hold if !self.rc.hold_to_write() => return None,
+            RwState::Read | RwState::Upgradable if !self.rc.read_to_write() => return None,
+            _ => {}
         }
+        self.rc.apply_mutations();
+        self.guards.borrow_mut();
+        #[cfg(feature = "audit")]
+        self.record_guard(true, true);
+        Some(LocalMut(self))
     }
 
     /// 读取，如果 RwRc 没有读取权限，则会尝试获取，如果获取失败，则会 panic。
@@ -106,27 +245,320 @@ impl<T> RwRc<T> {
     /// # Panic
     ///
     /// 当无法获取读取权限时会 panic。
-    pub fn read(&self) -> LocalRef<T> {
+    #[track_caller]
+    pub fn read(&self) -> LocalRef<'_, T> {
         self.try_read().unwrap()
     }
 
+    /// 设置此副本是否保留只读借用获取的读状态。
+    ///
+    /// 设置后，持有状态下获取的只读借用全部释放时副本停留在读状态，
+    /// 之后的读取不再需要修改共享读写状态，直到调用 [`release`](Self::release)。
+    /// 适合频繁读取的副本，代价是副本在两次读取之间也会阻止其他副本写入。
+    /// 此设置只属于这个副本，克隆的副本不继承。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{RwRc, RwState};
+    ///
+    /// let rc = RwRc::new(1);
+    /// rc.release();
+    /// rc.set_sticky_read(true);
+    /// assert_eq!(*rc.read(), 1);
+    /// assert_eq!(rc.state(), RwState::Read);
+    /// ```
+    pub fn set_sticky_read(&self, sticky: bool) {
+        self.sticky.set(sticky)
+    }
+
+    /// 此副本是否保留只读借用获取的读状态，参见 [`set_sticky_read`](Self::set_sticky_read)。
+    pub fn is_sticky_read(&self) -> bool {
+        self.sticky.get()
+    }
+
     /// 写入，如果 RwRc 没有写入权限，则会尝试获取，如果获取失败，则会 panic。
     /// Drop 后不会改变 RwRc 的读写状态。
     ///
     /// # Panic
     ///
     /// 当无法获取写入权限时会 panic。
-    pub fn write(&mut self) -> LocalMut<T> {
+    #[track_caller]
+    pub fn write(&mut self) -> LocalMut<'_, T> {
         self.try_write().unwrap()
     }
+
+    /// 读取共享对象并以映射结果创建新的独立共享对象，无法读取时返回 `None`。
+    ///
+    /// 新的共享对象使用相同的读写偏好策略，初始为读状态。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let config = RwRc::new(vec![("a", 1), ("b", 2)]);
+    /// let keys = config.map_value(|c| c.iter().map(|(k, _)| *k).collect::<Vec<_>>()).unwrap();
+    /// assert_eq!(*keys.read(), ["a", "b"]);
+    /// ```
+    #[track_caller]
+    pub fn map_value<U>(&self, f: impl FnOnce(&T) -> U) -> Option<RwRc<U>> {
+        let val = self.try_read()?;
+        Some(RwRc::with_preference(f(&val), self.rc.preference))
+    }
+
+    /// 临时读取并克隆共享对象，无法读取时返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let mut rc = RwRc::new(String::from("config"));
+    /// rc.release();
+    /// assert_eq!(rc.clone_inner().unwrap(), "config");
+    /// ```
+    #[track_caller]
+    pub fn clone_inner(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.try_read().map(|val| T::clone(&val))
+    }
+
+    /// 获取可变借用，无法获取写入权限时复制共享对象，使此副本成为新对象的唯一副本。
+    ///
+    /// 复制后的共享对象使用相同的读写偏好策略，保持此副本原来的读写状态，
+    /// 不继承父对象、回调和终结器；其他副本仍然共享原来的对象。
+    ///
+    /// # Panic
+    ///
+    /// 共享对象被其他副本写锁定而无法读取复制时 panic。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let mut rc = RwRc::new(vec![1]);
+    /// let reader = rc.clone();
+    ///
+    /// // 有其他读者时复制一份再修改
+    /// rc.try_write_or_fork().push(2);
+    /// assert_eq!(*rc.read(), [1, 2]);
+    /// assert_eq!(*reader.read(), [1]);
+    /// ```
+    #[track_caller]
+    pub fn try_write_or_fork(&mut self) -> LocalMut<'_, T>
+    where
+        T: Clone,
+    {
+        if !self.is_writeable() {
+            let val = self
+                .clone_inner()
+                .expect("cannot fork a RwRc write-locked by another handle");
+            let state = self.state.get();
+            let fork = RwRc::with_preference(val, self.rc.preference);
+            match state {
+                RwState::Hold => fork.release(),
+                // 新对象只有这一个副本，总是可以获取
+                RwState::Upgradable => assert!(fork.try_upgradable_global()),
+                RwState::Read | RwState::Write => {}
+            }
+            *self = fork
+        }
+        self.write()
+    }
+}
+
+impl<T> LocalMut<'_, T> {
+    /// 从可变借用重借用一个只读借用。
+    ///
+    /// 重借用不会改变读写状态，只读借用存在期间可变借用无法使用。
+    /// 用于在持有可变借用时调用接受 [`LocalRef`] 的函数。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{LocalRef, RwRc};
+    ///
+    /// fn sum(v: LocalRef<Vec<i32>>) -> i32 {
+    ///     v.iter().sum()
+    /// }
+    ///
+    /// let mut rc = RwRc::new(vec![1, 2]);
+    /// let mut writer = rc.write();
+    /// writer.push(3);
+    /// assert_eq!(sum(writer.as_local_ref()), 6);
+    /// writer.push(4);
+    /// ```
+    pub fn as_local_ref(&self) -> LocalRef<'_, T> {
+        LocalRef {
+            rc: self.0,
+            reborrowed: true,
+        }
+    }
+
+    /// 以 `val` 替换共享对象，返回原来的对象。
+    pub fn replace(&mut self, val: T) -> T {
+        std::mem::replace(&mut **self, val)
+    }
+
+    /// 消耗原来的对象并以 `f` 的返回值替换共享对象。
+    ///
+    /// # Abort
+    ///
+    /// `f` panic 时共享对象已经被移出而没有新的值可以放回，进程会直接中止。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// enum State {
+    ///     Idle(Vec<u8>),
+    ///     Busy(Vec<u8>),
+    /// }
+    ///
+    /// let mut rc = RwRc::new(State::Idle(vec![1]));
+    /// rc.write().replace_with(|s| match s {
+    ///     State::Idle(buf) | State::Busy(buf) => State::Busy(buf),
+    /// });
+    /// assert!(matches!(&*rc.read(), State::Busy(buf) if *buf == [1]));
+    /// ```
+    pub fn replace_with(&mut self, f: impl FnOnce(T) -> T) {
+        struct AbortOnUnwind;
+        impl Drop for AbortOnUnwind {
+            fn drop(&mut self) {
+                std::process::abort()
+            }
+        }
+
+        let ptr = self.0.rc.val.as_ptr();
+        let guard = AbortOnUnwind;
+        // 持有可变借用，原来的对象被移出后在 `f` 返回前不会被访问
+        unsafe { ptr.write(f(ptr.read())) };
+        std::mem::forget(guard)
+    }
+}
+
+impl<'w, T> LocalRef<'w, T> {
+    /// 结束只读借用，并把借用占用的读取权限转为副本的读状态，返回借用的副本。
+    ///
+    /// 从持有状态获取的借用直接把占用的读者转交给副本，期间没有释放后重新获取的间隙，
+    /// 其他副本无法在两者之间获取写状态。
+    /// 副本已经处于读状态或写状态，或者借用是从可变借用重借用得到的时，只结束借用。
+    ///
+    /// # Panic
+    ///
+    /// 需要改变读写状态而同一副本上还有其他借用时 panic。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{RwRc, RwState, LocalRef};
+    ///
+    /// let mut rc = RwRc::new(vec![1]);
+    /// rc.release();
+    /// let guard = rc.read();
+    /// if guard.contains(&1) {
+    ///     let rc = LocalRef::retain_read(guard);
+    ///     assert_eq!(rc.state(), RwState::Read);
+    /// }
+    /// ```
+    #[track_caller]
+    pub fn retain_read(this: Self) -> &'w RwRc<T> {
+        let rc = this.rc;
+        if !this.reborrowed && matches!(rc.state.get(), RwState::Hold) {
+            if rc.guards.depth.get() != 1 {
+                misuse(format_args!(
+                    "cannot retain a LocalRef while the same RwRc has other live borrows"
+                ))
+            }
+            std::mem::forget(this);
+            rc.guards.release();
+            #[cfg(feature = "audit")]
+            rc.record_guard(false, false);
+            rc.set_state(RwState::Read);
+        }
+        rc
+    }
+}
+
+impl<'w, T> LocalMut<'w, T> {
+    /// 结束可变借用，并把借用占用的写入权限转为副本的写状态，返回借用的副本。
+    ///
+    /// 借用占用的写状态直接转交给副本，期间没有释放后重新获取的间隙，
+    /// 其他副本无法在两者之间获取读状态或写状态。
+    /// 此副本登记的写意图同时被撤销。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{RwRc, RwState, LocalMut};
+    ///
+    /// let mut rc = RwRc::new(0);
+    /// let mut guard = rc.write();
+    /// *guard = 1;
+    /// let rc = LocalMut::retain_write(guard);
+    /// assert_eq!(rc.state(), RwState::Write);
+    /// ```
+    pub fn retain_write(this: Self) -> &'w mut RwRc<T> {
+        let rc = Self::leak(this);
+        rc.guards.release_mut();
+        #[cfg(feature = "audit")]
+        rc.record_guard(true, false);
+        rc.clear_intent();
+        rc.set_state(RwState::Write);
+        rc
+    }
+
+    /// 取出借用的副本而不还原读写状态，由调用者负责注销可变借用。
+    pub(super) fn leak(this: Self) -> &'w mut RwRc<T> {
+        let this = std::mem::ManuallyDrop::new(this);
+        // `this` 不会再被使用或释放
+        unsafe { std::ptr::read(&this.0) }
+    }
+}
+
+impl<T> RwRc<T> {
+    /// 注销一个只读借用，并还原读写状态。
+    pub(super) fn drop_ref(&self) {
+        self.guards.release();
+        #[cfg(feature = "audit")]
+        self.record_guard(false, false);
+        match self.state.get() {
+            // 最后一个借用占用的读状态直接转交给副本
+            RwState::Hold if self.sticky.get() && self.guards.depth.get() == 0 => {
+                self.set_state(RwState::Read);
+            }
+            RwState::Hold => {
+                self.rc.read_to_hold();
+                self.notify_released()
+            }
+            RwState::Read | RwState::Upgradable | RwState::Write => {}
+        }
+    }
+
+    /// 注销可变借用，并还原读写状态。
+    pub(super) fn drop_mut(&self) {
+        self.guards.release_mut();
+        #[cfg(feature = "audit")]
+        self.record_guard(true, false);
+        match self.state.get() {
+            RwState::Hold => self.rc.write_to_hold(),
+            RwState::Read | RwState::Upgradable => self.rc.write_to_read(),
+            RwState::Write => return,
+        }
+        self.notify_released()
+    }
 }
 
 impl<T> Drop for LocalRef<'_, T> {
     /// 释放 `LocalRef` 时，并还原 `RwRc` 的读写状态。
     fn drop(&mut self) {
-        match self.0.state {
-            RwState::Hold => self.0.rc.flag.read_to_hold(),
-            RwState::Read | RwState::Write => {}
+        if !self.reborrowed {
+            self.rc.drop_ref()
         }
     }
 }
@@ -134,11 +566,7 @@ impl<T> Drop for LocalRef<'_, T> {
 impl<T> Drop for LocalMut<'_, T> {
     /// 释放 `LocalMut` 时，并还原 `RwRc` 的读写状态。
     fn drop(&mut self) {
-        match self.0.state {
-            RwState::Hold => self.0.rc.flag.write_to_hold(),
-            RwState::Read => self.0.rc.flag.write_to_read(),
-            RwState::Write => {}
-        }
+        self.0.drop_mut()
     }
 }
 
@@ -146,7 +574,7 @@ impl<T> Deref for LocalRef<'_, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { &*self.0.rc.val.as_ptr() }
+        unsafe { &*self.rc.rc.val.as_ptr() }
     }
 }
 
@@ -164,6 +592,110 @@ impl<T> DerefMut for LocalMut<'_, T> {
     }
 }
 
+impl<T: fmt::Debug> fmt::Debug for LocalRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LocalMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/// 序列化借用的共享对象。
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for LocalRef<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for LocalMut<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+/// 借用与普通值比较时比较共享对象，使 `assert_eq!(rc.read(), 42)` 可以直接使用。
+impl<T: PartialEq> PartialEq<T> for LocalRef<'_, T> {
+    fn eq(&self, other: &T) -> bool {
+        **self == *other
+    }
+}
+
+impl<T: PartialEq> PartialEq<T> for LocalMut<'_, T> {
+    fn eq(&self, other: &T) -> bool {
+        **self == *other
+    }
+}
+
+impl<T: PartialOrd> PartialOrd<T> for LocalRef<'_, T> {
+    fn partial_cmp(&self, other: &T) -> Option<Ordering> {
+        (**self).partial_cmp(other)
+    }
+}
+
+impl<T: PartialOrd> PartialOrd<T> for LocalMut<'_, T> {
+    fn partial_cmp(&self, other: &T) -> Option<Ordering> {
+        (**self).partial_cmp(other)
+    }
+}
+
+impl<T: Read> Read for LocalMut<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read(buf)
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        (**self).read_to_end(buf)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        (**self).read_to_string(buf)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        (**self).read_exact(buf)
+    }
+}
+
+impl<T: Write> Write for LocalMut<'_, T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        (**self).write_all(buf)
+    }
+}
+
+impl<T: Seek> Seek for LocalMut<'_, T> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        (**self).seek(pos)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        (**self).stream_position()
+    }
+}
+
+impl<T: BufRead> BufRead for LocalMut<'_, T> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        (**self).fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        (**self).consume(amt)
+    }
+}
+
 #[test]
 fn test_recover_state() {
     let mut rwrc_hold = RwRc::new(42);
@@ -177,18 +709,18 @@ fn test_recover_state() {
         let _ = rwrc_read.read();
         let _ = rwrc_write.read();
     }
-    assert!(matches!(rwrc_hold.state, RwState::Hold));
-    assert!(matches!(rwrc_read.state, RwState::Read));
-    assert!(matches!(rwrc_write.state, RwState::Write));
+    assert!(matches!(rwrc_hold.state.get(), RwState::Hold));
+    assert!(matches!(rwrc_read.state.get(), RwState::Read));
+    assert!(matches!(rwrc_write.state.get(), RwState::Write));
 
     {
         let _ = rwrc_hold.write();
         let _ = rwrc_read.write();
         let _ = rwrc_write.write();
     }
-    assert!(matches!(rwrc_hold.state, RwState::Hold));
-    assert!(matches!(rwrc_read.state, RwState::Read));
-    assert!(matches!(rwrc_write.state, RwState::Write));
+    assert!(matches!(rwrc_hold.state.get(), RwState::Hold));
+    assert!(matches!(rwrc_read.state.get(), RwState::Read));
+    assert!(matches!(rwrc_write.state.get(), RwState::Write));
 }
 
 #[test]
@@ -215,19 +747,19 @@ fn test_read_write() {
     rwrc.release();
     // 测试hold状态,之后被其他对象获取全局写状态，进行读取，应该失败
     {
-        let mut rwrc2 = rwrc.clone();
+        let rwrc2 = rwrc.clone();
         assert!(rwrc2.try_write_global());
         assert!(rwrc.try_read().is_none()); // 修改这行，直接使用 assert!
     }
     //  测试hold状态,之后被其他对象获取全局写状态，进行写入，应该失败
     {
-        let mut rwrc2 = rwrc.clone();
+        let rwrc2 = rwrc.clone();
         assert!(rwrc2.try_write_global());
         assert!(rwrc.try_write().is_none());
     }
     //  测试数据有多个可读引用，有的可读引用想要转换成可写,应该失败
     {
-        let mut rwrc2 = rwrc.clone();
+        let rwrc2 = rwrc.clone();
         assert!(rwrc.try_read_global());
         assert!(rwrc2.try_read_global());
         assert!(rwrc.try_write().is_none());
@@ -271,3 +803,247 @@ fn test_deref() {
     assert_eq!(string_writer.len(), 4); // 可以访问字符串的方法
     assert_eq!(&*string_writer, "test"); // 可以解引用比较字符串内容
 }
+
+#[test]
+fn test_guard_tracker() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    let tracker = AssertUnwindSafe(GuardTracker::default());
+    for _ in 0..2 {
+        tracker.check();
+        tracker.borrow();
+    }
+    tracker.release();
+    assert_eq!(tracker.depth.get(), 1);
+
+    // 存在只读借用时获取可变借用会 panic
+    let msg = catch_unwind(|| tracker.check_mut()).unwrap_err();
+    let msg = msg.downcast_ref::<String>().unwrap();
+    assert!(msg.contains("1 live LocalRef"));
+    // 调试模式下指出双方的获取位置
+    #[cfg(debug_assertions)]
+    assert_eq!(msg.matches(file!()).count(), 2);
+
+    // 只读借用全部释放后可以获取可变借用
+    tracker.release();
+    tracker.check_mut();
+    tracker.borrow_mut();
+    assert_eq!(tracker.depth.get(), -1);
+
+    // 存在可变借用时获取任何借用都会 panic
+    assert!(catch_unwind(|| tracker.check()).is_err());
+    assert!(catch_unwind(|| tracker.check_mut()).is_err());
+    tracker.release_mut();
+    tracker.check();
+}
+
+#[test]
+fn test_nested_views_in_write_state() {
+    let mut rc = RwRc::new(42);
+    assert!(rc.try_write_global());
+    {
+        // 写状态下可以嵌套获取多个只读借用，借用深度正确计数
+        let r1 = rc.read();
+        let r2 = rc.read();
+        assert_eq!(rc.guards.depth.get(), 2);
+        assert_eq!(*r1 + *r2, 84);
+        drop(r1);
+        assert_eq!(rc.guards.depth.get(), 1);
+    }
+    assert_eq!(rc.guards.depth.get(), 0);
+    {
+        let w = rc.write();
+        assert_eq!(w.0.guards.depth.get(), -1);
+    }
+    assert_eq!(rc.guards.depth.get(), 0);
+    // 借用不影响副本和共享状态
+    assert!(matches!(rc.state.get(), RwState::Write));
+    assert!(!rc.rc.flag.is_readable());
+}
+
+#[test]
+fn test_io_passthrough() {
+    use std::io::Cursor;
+
+    fn copy_all(from: &mut impl BufRead, to: &mut impl Write) -> io::Result<u64> {
+        io::copy(from, to)
+    }
+
+    let mut src = RwRc::new(Cursor::new(b"line 1\nline 2\n".to_vec()));
+    let mut dst = RwRc::new(Cursor::new(Vec::new()));
+
+    // 通过守卫直接使用 io 泛型代码
+    assert_eq!(copy_all(&mut src.write(), &mut dst.write()).unwrap(), 14);
+    assert_eq!(dst.read().get_ref(), b"line 1\nline 2\n");
+
+    let mut reader = src.write();
+    reader.seek(SeekFrom::Start(0)).unwrap();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "line 1\n");
+    assert_eq!(reader.stream_position().unwrap(), 7);
+}
+
+#[test]
+fn test_as_local_ref() {
+    let mut rc = RwRc::new(42);
+    rc.release();
+    let mut other = rc.clone();
+    {
+        let mut writer = rc.write();
+        *writer = 1;
+        {
+            let r1 = writer.as_local_ref();
+            let r2 = writer.as_local_ref();
+            assert_eq!(*r1 + *r2, 2);
+        }
+        // 重借用释放后不影响读写状态和借用深度
+        assert_eq!(writer.0.guards.depth.get(), -1);
+        assert!(!writer.0.rc.flag.is_readable());
+        *writer = 2;
+    }
+    assert!(matches!(rc.state.get(), RwState::Hold));
+    assert_eq!(*other.write(), 2);
+}
+
+#[test]
+fn test_map_value() {
+    let rc = RwRc::with_preference(vec![1, 2, 3], crate::Preference::Write);
+    let mut sum = rc.map_value(|v| v.iter().sum::<i32>()).unwrap();
+    assert_eq!(*sum.read(), 6);
+    assert!(matches!(sum.rc.preference, crate::Preference::Write));
+
+    // 映射结果是独立的共享对象
+    *sum.write() = 0;
+    assert_eq!(*rc.read(), [1, 2, 3]);
+
+    // 无法读取时返回 None
+    rc.release();
+    let writer = rc.clone();
+    assert!(writer.try_write_global());
+    assert!(rc.map_value(|v| v.len()).is_none());
+}
+
+#[test]
+fn test_clone_inner() {
+    let rc = RwRc::new(vec![1]);
+    rc.release();
+    assert_eq!(rc.clone_inner(), Some(vec![1]));
+    // 临时读取不保留读状态
+    assert!(rc.is_writeable());
+    assert!(rc.try_write_global());
+    assert!(rc.clone().clone_inner().is_none());
+}
+
+#[test]
+fn test_replace() {
+    let mut rc = RwRc::new(String::from("a"));
+    let mut w = rc.write();
+    assert_eq!(w.replace(String::from("b")), "a");
+    w.replace_with(|s| s + "c");
+    drop(w);
+    assert_eq!(*rc.read(), "bc");
+}
+
+#[test]
+fn test_compare_with_value() {
+    let mut rc = RwRc::new(42);
+    assert_eq!(rc.read(), 42);
+    assert!(rc.read() < 43);
+    let mut w = rc.write();
+    *w += 1;
+    assert_eq!(w, 43);
+    assert!(w > 42);
+    assert_eq!(format!("{w:?}"), "43");
+}
+
+#[test]
+fn test_try_write_or_fork() {
+    let mut rc = RwRc::new(1);
+    let id = rc.id();
+    // 可以写入时不复制
+    *rc.try_write_or_fork() = 2;
+    assert_eq!(rc.id(), id);
+
+    let other = rc.clone();
+    rc.release();
+    other.release();
+    assert!(other.try_read_global());
+    *rc.try_write_or_fork() = 3;
+    assert_ne!(rc.id(), id);
+    assert!(matches!(rc.state.get(), RwState::Hold));
+    assert_eq!(*other.read(), 2);
+    assert_eq!(*rc.read(), 3);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize_guards() {
+    fn assert_serialize(_: impl serde::Serialize) {}
+
+    let mut rc = RwRc::new(vec![1, 2]);
+    assert_serialize(rc.read());
+    assert_serialize(rc.write());
+    assert_serialize(LocalRef::map(rc.read(), |v| v.as_slice()));
+}
+
+#[test]
+fn test_retain() {
+    let mut rc = RwRc::new(1);
+    let other = rc.clone();
+    rc.release();
+    other.release();
+
+    // 保留只读借用占用的读者
+    let guard = rc.read();
+    let retained = LocalRef::retain_read(guard);
+    assert_eq!(retained.state(), RwState::Read);
+    assert_eq!(retained.rc.flag.readers(), 1);
+    assert!(!other.try_write_global());
+
+    // 从读状态升级的可变借用保留为写状态
+    let mut guard = rc.write();
+    *guard = 2;
+    let retained = LocalMut::retain_write(guard);
+    assert_eq!(retained.state(), RwState::Write);
+    assert!(!other.try_read_global());
+    rc.release();
+    assert_eq!(*other.read(), 2);
+
+    // 同一副本上还有其他借用时无法保留
+    let a = rc.read();
+    let b = rc.read();
+    let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        LocalRef::retain_read(a);
+    }));
+    assert!(err.is_err());
+    drop(b);
+}
+
+#[test]
+fn test_sticky_read() {
+    let mut rc = RwRc::new(0);
+    let other = rc.clone();
+    rc.release();
+    other.release();
+    rc.set_sticky_read(true);
+    assert!(rc.is_sticky_read());
+
+    // 只有最后一个借用释放时保留读状态
+    let a = rc.read();
+    let b = rc.read();
+    drop(a);
+    assert_eq!(rc.state(), RwState::Hold);
+    drop(b);
+    assert_eq!(rc.state(), RwState::Read);
+    assert!(!other.try_write_global());
+    assert!(!rc.clone().is_sticky_read());
+
+    // 释放后恢复正常，关闭后借用释放时还原
+    rc.release();
+    rc.set_sticky_read(false);
+    assert_eq!(*rc.read(), 0);
+    assert_eq!(rc.state(), RwState::Hold);
+    *rc.write() = 1;
+    assert!(other.try_write_global());
+}
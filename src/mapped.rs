@@ -0,0 +1,208 @@
+use crate::{LocalMut, LocalRef, RwRc};
+use std::{
+    fmt,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+/// 映射到共享对象一部分的只读借用。
+///
+/// 由 [`LocalRef::map`] 创建，释放时与原来的 [`LocalRef`] 一样还原读写状态。
+/// 共享对象的类型被擦除，只保留映射后的类型。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{LocalRef, RwRc};
+///
+/// let rc = RwRc::new((String::from("name"), 42));
+/// let name = LocalRef::map(rc.read(), |(name, _)| name.as_str());
+/// assert_eq!(&*name, "name");
+/// ```
+pub struct MappedLocalRef<'w, U: ?Sized> {
+    /// 借用的副本。
+    owner: &'w dyn Owner,
+    /// 是否是重借用，重借用不占用读写状态。
+    reborrowed: bool,
+    /// 映射后的对象。
+    val: &'w U,
+}
+
+/// 映射到共享对象一部分的可变借用。
+///
+/// 由 [`LocalMut::map`] 创建，释放时与原来的 [`LocalMut`] 一样还原读写状态。
+pub struct MappedLocalMut<'w, U: ?Sized> {
+    /// 借用的副本。
+    owner: NonNull<dyn Owner + 'w>,
+    /// 映射后的对象。
+    val: NonNull<U>,
+    _phantom: PhantomData<&'w mut U>,
+}
+
+/// 类型擦除的被借用副本。
+trait Owner {
+    /// 注销一个只读借用。
+    fn drop_ref(&self);
+    /// 注销可变借用。
+    fn drop_mut(&self);
+}
+
+impl<T> Owner for RwRc<T> {
+    fn drop_ref(&self) {
+        RwRc::drop_ref(self)
+    }
+
+    fn drop_mut(&self) {
+        RwRc::drop_mut(self)
+    }
+}
+
+impl<'w, T> LocalRef<'w, T> {
+    /// 将只读借用映射到共享对象的一部分。
+    pub fn map<U: ?Sized>(this: Self, f: impl FnOnce(&T) -> &U) -> MappedLocalRef<'w, U> {
+        let this = ManuallyDrop::new(this);
+        let rc: &'w RwRc<T> = this.rc;
+        // 共享对象在借用存在期间可读
+        let val = f(unsafe { &*rc.rc.val.as_ptr() });
+        MappedLocalRef {
+            owner: rc,
+            reborrowed: this.reborrowed,
+            val,
+        }
+    }
+}
+
+impl<'w, T> LocalMut<'w, T> {
+    /// 将可变借用映射到共享对象的一部分。
+    pub fn map<U: ?Sized>(this: Self, f: impl FnOnce(&mut T) -> &mut U) -> MappedLocalMut<'w, U> {
+        let rc = LocalMut::leak(this);
+        // 共享对象在借用存在期间可写，且不存在其他引用
+        let val = f(unsafe { &mut *rc.rc.val.as_ptr() });
+        let owner: &'w mut (dyn Owner + 'w) = rc;
+        MappedLocalMut {
+            owner: NonNull::from(owner),
+            val: NonNull::from(val),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'w, U: ?Sized> MappedLocalRef<'w, U> {
+    /// 继续映射到对象的一部分。
+    pub fn map<V: ?Sized>(this: Self, f: impl FnOnce(&U) -> &V) -> MappedLocalRef<'w, V> {
+        let this = ManuallyDrop::new(this);
+        MappedLocalRef {
+            owner: this.owner,
+            reborrowed: this.reborrowed,
+            val: f(this.val),
+        }
+    }
+}
+
+impl<'w, U: ?Sized> MappedLocalMut<'w, U> {
+    /// 继续映射到对象的一部分。
+    pub fn map<V: ?Sized>(this: Self, f: impl FnOnce(&mut U) -> &mut V) -> MappedLocalMut<'w, V> {
+        let mut this = ManuallyDrop::new(this);
+        let val = f(unsafe { this.val.as_mut() });
+        MappedLocalMut {
+            owner: this.owner,
+            val: NonNull::from(val),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<U: ?Sized> Drop for MappedLocalRef<'_, U> {
+    fn drop(&mut self) {
+        if !self.reborrowed {
+            self.owner.drop_ref()
+        }
+    }
+}
+
+impl<U: ?Sized> Drop for MappedLocalMut<'_, U> {
+    fn drop(&mut self) {
+        // 映射后的引用不再使用，副本仍然被可变借用
+        unsafe { self.owner.as_ref() }.drop_mut()
+    }
+}
+
+impl<U: ?Sized> Deref for MappedLocalRef<'_, U> {
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        self.val
+    }
+}
+
+impl<U: ?Sized> Deref for MappedLocalMut<'_, U> {
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        unsafe { self.val.as_ref() }
+    }
+}
+
+impl<U: ?Sized> DerefMut for MappedLocalMut<'_, U> {
+    fn deref_mut(&mut self) -> &mut U {
+        unsafe { self.val.as_mut() }
+    }
+}
+
+impl<U: ?Sized + fmt::Debug> fmt::Debug for MappedLocalRef<'_, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<U: ?Sized + fmt::Debug> fmt::Debug for MappedLocalMut<'_, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<U: ?Sized + serde::Serialize> serde::Serialize for MappedLocalRef<'_, U> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<U: ?Sized + serde::Serialize> serde::Serialize for MappedLocalMut<'_, U> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+#[test]
+fn test_mapped() {
+    let mut rc = RwRc::new((vec![1, 2], String::from("a")));
+    let other = rc.clone();
+    rc.release();
+    other.release();
+    {
+        let first = MappedLocalRef::map(LocalRef::map(rc.read(), |(v, _)| v.as_slice()), |v| &v[0]);
+        assert_eq!(*first, 1);
+        // 映射后的借用仍然占用读状态
+        assert!(!other.is_writeable());
+    }
+    assert!(rc.is_writeable());
+    {
+        let mut s = LocalMut::map(rc.write(), |(_, s)| s);
+        s.push('b');
+        assert!(!other.is_readable());
+    }
+    assert!(other.is_readable());
+    assert_eq!(rc.read().1, "ab");
+
+    // 重借用映射后不占用读写状态
+    let w = rc.write();
+    let r = LocalRef::map(w.as_local_ref(), |(v, _)| v);
+    assert_eq!(*r, [1, 2]);
+    drop(r);
+    drop(w);
+    assert!(rc.is_writeable());
+}
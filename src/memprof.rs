@@ -0,0 +1,134 @@
+//! 按共享对象类型统计的内存分配计数。
+//!
+//! 启用 `memprof` 特性后，每次创建和释放共享对象都会更新当前线程上对应类型的计数。
+//! 计数只包括共享对象本身的分配，不包括共享对象拥有的堆内存，
+//! 后者可以通过 [`RwRc::heap_size`](crate::RwRc::heap_size) 查询。
+
+use crate::heap::allocation_size;
+use std::{any::type_name, cell::RefCell, collections::HashMap, marker::PhantomData};
+
+/// 一种类型的共享对象分配计数。
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct TypeStats {
+    /// 存活的共享对象数量。
+    pub count: usize,
+    /// 存活的共享对象占用的字节数。
+    pub bytes: usize,
+}
+
+thread_local! {
+    /// 当前线程上各类型的分配计数。
+    static STATS: RefCell<HashMap<&'static str, TypeStats>> = RefCell::new(HashMap::new());
+}
+
+/// 查询当前线程上类型为 `T` 的共享对象的分配计数。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, memprof};
+///
+/// struct Weights([f32; 16]);
+///
+/// let rc = RwRc::new(Weights([0.; 16]));
+/// assert_eq!(memprof::stats::<Weights>().count, 1);
+/// drop(rc);
+/// assert_eq!(memprof::stats::<Weights>().count, 0);
+/// ```
+pub fn stats<T>() -> TypeStats {
+    STATS.with_borrow(|stats| stats.get(type_name::<T>()).copied().unwrap_or_default())
+}
+
+/// 查询当前线程上所有类型的共享对象的分配计数。
+///
+/// 返回存活的共享对象的类型名和计数，按占用的字节数从多到少排列，
+/// 字节数相同时按类型名排列。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, memprof};
+///
+/// let small = RwRc::new(0u8);
+/// let large = RwRc::new([0u64; 32]);
+/// let report = memprof::report();
+/// assert_eq!(report[0].0, std::any::type_name::<[u64; 32]>());
+/// assert_eq!(report[1].0, std::any::type_name::<u8>());
+/// ```
+pub fn report() -> Vec<(&'static str, TypeStats)> {
+    let mut report = STATS.with_borrow(|stats| {
+        stats
+            .iter()
+            .filter(|(_, s)| s.count > 0)
+            .map(|(&name, &s)| (name, s))
+            .collect::<Vec<_>>()
+    });
+    report.sort_unstable_by(|(a, x), (b, y)| y.bytes.cmp(&x.bytes).then(a.cmp(b)));
+    report
+}
+
+/// 记录一个共享对象的创建。
+fn allocated<T>() {
+    STATS.with_borrow_mut(|stats| {
+        let entry = stats.entry(type_name::<T>()).or_default();
+        entry.count += 1;
+        entry.bytes += allocation_size::<T>()
+    })
+}
+
+/// 记录一个共享对象的释放。
+///
+/// 线程局部存储已经销毁时忽略。
+fn freed<T>() {
+    let _ = STATS.try_with(|stats| {
+        if let Some(entry) = stats.borrow_mut().get_mut(type_name::<T>()) {
+            entry.count -= 1;
+            entry.bytes -= allocation_size::<T>()
+        }
+    });
+}
+
+/// 共享对象携带的计数标记，随共享对象创建和释放更新计数。
+pub(crate) struct Token<T>(PhantomData<fn() -> T>);
+
+impl<T> Token<T> {
+    /// 记录一个共享对象的创建。
+    pub(crate) fn new() -> Self {
+        allocated::<T>();
+        Self(PhantomData)
+    }
+}
+
+impl<T> Drop for Token<T> {
+    fn drop(&mut self) {
+        freed::<T>()
+    }
+}
+
+#[test]
+fn test_memprof() {
+    use crate::RwRc;
+
+    struct Buffer(#[allow(dead_code)] [u8; 64]);
+
+    assert_eq!(stats::<Buffer>(), TypeStats::default());
+    let a = RwRc::new(Buffer([0; 64]));
+    let b = a.clone();
+    let c = RwRc::new(Buffer([1; 64]));
+    let expected = TypeStats {
+        count: 2,
+        bytes: 2 * allocation_size::<Buffer>(),
+    };
+    assert_eq!(stats::<Buffer>(), expected);
+
+    // 副本不会增加计数，最后一个副本释放时计数减少
+    drop(a);
+    assert_eq!(stats::<Buffer>(), expected);
+    let name = type_name::<Buffer>();
+    assert!(report().contains(&(name, expected)));
+    drop(b);
+    drop(c);
+    assert_eq!(stats::<Buffer>(), TypeStats::default());
+    // 没有存活对象的类型不出现在报告中
+    assert!(report().iter().all(|&(n, _)| n != name));
+}
@@ -0,0 +1,84 @@
+use crate::{LocalRef, LockError, RwRc};
+
+impl<T: Clone + PartialEq> RwRc<T> {
+    /// 以 `base` 为共同祖先，三方合并此副本和 `theirs` 的共享对象，返回合并后的值。
+    ///
+    /// 三个共享对象在同一组临时读取下比较，合并期间都不会被修改：
+    /// 只有一方相对 `base` 发生了变化时取这一方的值，双方相同时取相同的值，
+    /// 双方都发生了不同的变化时以 `(base, ours, theirs)` 调用 `resolver` 决定合并结果。
+    /// 合并不改变任何副本的读写状态，也不写回共享对象。
+    /// 任何一个共享对象无法读取时返回错误，`resolver` 不会被调用。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let base = RwRc::new(String::from("draft"));
+    /// let mut ours = base.clone();
+    /// let mut theirs = base.clone();
+    /// ours.try_write_or_fork().push_str(" v1");
+    /// theirs.try_write_or_fork().insert(0, '#');
+    ///
+    /// let merged = ours
+    ///     .merge(&base, &theirs, |_, ours, theirs| format!("{theirs}{}", &ours[5..]))
+    ///     .unwrap();
+    /// assert_eq!(merged, "#draft v1");
+    /// ```
+    pub fn merge(
+        &self,
+        base: &RwRc<T>,
+        theirs: &RwRc<T>,
+        resolver: impl FnOnce(&T, &T, &T) -> T,
+    ) -> Result<T, LockError> {
+        fn read<T>(rc: &RwRc<T>) -> Result<LocalRef<'_, T>, LockError> {
+            rc.try_read().ok_or_else(|| rc.read_error())
+        }
+        let ours = read(self)?;
+        let base_ = read(base)?;
+        let theirs = read(theirs)?;
+        Ok(if *ours == *theirs || *theirs == *base_ {
+            T::clone(&ours)
+        } else if *ours == *base_ {
+            T::clone(&theirs)
+        } else {
+            resolver(&base_, &ours, &theirs)
+        })
+    }
+}
+
+#[test]
+fn test_merge() {
+    use crate::Access;
+
+    let base = RwRc::new(vec![1, 2]);
+    let mut ours = base.clone();
+    let mut theirs = base.clone();
+
+    // 只有一方修改时无需解决冲突
+    theirs.try_write_or_fork().push(3);
+    let merged = ours.merge(&base, &theirs, |_, _, _| unreachable!());
+    assert_eq!(merged, Ok(vec![1, 2, 3]));
+
+    // 双方相同的修改
+    ours.try_write_or_fork().push(3);
+    let merged = ours.merge(&base, &theirs, |_, _, _| unreachable!());
+    assert_eq!(merged, Ok(vec![1, 2, 3]));
+
+    // 双方不同的修改交给解决函数
+    ours.write()[0] = 0;
+    let merged = ours.merge(&base, &theirs, |base, ours, theirs| {
+        assert_eq!((base.len(), ours[0], theirs[0]), (2, 0, 1));
+        [&ours[..], &[4]].concat()
+    });
+    assert_eq!(merged, Ok(vec![0, 2, 3, 4]));
+
+    // 任何一方无法读取时不合并
+    let writer = theirs.clone();
+    theirs.release();
+    assert!(writer.try_write_global());
+    assert_eq!(
+        ours.merge(&base, &theirs, |_, _, _| unreachable!()),
+        Err(LockError::Blocked(Access::Read))
+    );
+}
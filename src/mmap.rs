@@ -0,0 +1,83 @@
+use crate::RwRc;
+use memmap2::{Mmap, MmapMut};
+use std::{fs::File, io};
+
+impl RwRc<Mmap> {
+    /// 以只读方式映射 `file`，创建共享的字节切片，初始为读状态。
+    ///
+    /// 映射是只读的，任何副本都无法获取写状态，写入相关的方法总是失败。
+    /// 共享对象是 [`Mmap`] 而不是 `[u8]`：共享对象的分配需要大小确定的类型，
+    /// 映射本身也需要随最后一个副本一起解除，借用时以 `&[u8]` 访问映射的内容。
+    ///
+    /// # Safety
+    ///
+    /// 映射存在期间文件不能被截断或被其他进程修改，参见 [`Mmap::map`]。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{Mmap, RwRc};
+    /// use std::{fs::File, io::Write};
+    ///
+    /// let path = std::env::temp_dir().join("rwrc-doc-mmap");
+    /// File::create(&path).unwrap().write_all(b"weights").unwrap();
+    ///
+    /// let rc = unsafe { RwRc::<Mmap>::from_mmap(&File::open(&path).unwrap()) }.unwrap();
+    /// assert_eq!(&rc.read()[..], b"weights");
+    /// # drop(rc);
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    pub unsafe fn from_mmap(file: &File) -> io::Result<Self> {
+        let rc = Self::new(unsafe { Mmap::map(file) }?);
+        rc.rc.ensure_extras().read_only.set(true);
+        Ok(rc)
+    }
+}
+
+impl RwRc<MmapMut> {
+    /// 以读写方式映射 `file`，创建共享的可变字节切片，初始为读状态。
+    ///
+    /// 写状态下对映射的修改会写回文件，可以通过 [`MmapMut::flush`] 等待写回完成。
+    ///
+    /// # Safety
+    ///
+    /// 映射存在期间文件不能被截断或被其他进程修改，参见 [`MmapMut::map_mut`]。
+    pub unsafe fn from_mmap_mut(file: &File) -> io::Result<Self> {
+        unsafe { MmapMut::map_mut(file) }.map(Self::new)
+    }
+}
+
+#[test]
+fn test_mmap() {
+    use std::{fs::OpenOptions, io::Write};
+
+    let path = std::env::temp_dir().join(format!("rwrc-test-mmap-{}", std::process::id()));
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(&[0; 4]).unwrap();
+
+    let mut rc = unsafe { RwRc::<MmapMut>::from_mmap_mut(&file) }.unwrap();
+    let reader = rc.clone();
+    assert!(rc.try_write().is_none());
+    drop(reader);
+    rc.write()[..2].copy_from_slice(b"ok");
+    rc.read().flush().unwrap();
+    drop(rc);
+
+    let mut rc = unsafe { RwRc::<Mmap>::from_mmap(&file) }.unwrap();
+    assert_eq!(&rc.read()[..], b"ok\0\0");
+    // 只读映射无法获取写状态
+    assert!(!rc.is_writeable());
+    assert!(!rc.try_write_global());
+    assert!(rc.try_write().is_none());
+    rc.release();
+    assert!(!rc.try_write_global());
+    assert_eq!(rc.state(), crate::RwState::Hold);
+    drop(rc);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,143 @@
+use crate::{Access, LocalMut, LocalRef, RwRc, SharedRw};
+use std::{cell::RefCell, collections::VecDeque};
+
+/// 可以按脚本使获取失败的 [`RwRc<T>`]，用于测试 [`SharedRw`] 的使用者。
+///
+/// 每次通过 [`SharedRw`] 获取借用时依次取出对应访问权限的脚本结果：
+/// `false` 使这次获取直接失败而不触及共享对象，`true` 或脚本耗尽后按被包装的副本正常获取。
+/// 测试因此不需要构造复杂的副本拓扑就能覆盖“无法获取”的分支。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{Access, SharedRw, TestRwRc};
+///
+/// fn bump(p: &mut impl SharedRw<Target = i32>) -> Result<(), &'static str> {
+///     *p.try_write().ok_or("busy")? += 1;
+///     Ok(())
+/// }
+///
+/// let mut rc = TestRwRc::new(0);
+/// rc.script(Access::Write, [false, true]);
+/// assert_eq!(bump(&mut rc), Err("busy"));
+/// assert_eq!(bump(&mut rc), Ok(()));
+/// assert_eq!(*rc.inner().read(), 1);
+/// ```
+pub struct TestRwRc<T> {
+    /// 被包装的副本。
+    rc: RwRc<T>,
+    /// 只读借用的脚本。
+    reads: RefCell<VecDeque<bool>>,
+    /// 可变借用的脚本。
+    writes: RefCell<VecDeque<bool>>,
+}
+
+impl<T> From<RwRc<T>> for TestRwRc<T> {
+    fn from(rc: RwRc<T>) -> Self {
+        Self {
+            rc,
+            reads: Default::default(),
+            writes: Default::default(),
+        }
+    }
+}
+
+impl<T> TestRwRc<T> {
+    /// 以新的共享对象创建，被包装的副本初始为读状态。
+    pub fn new(val: T) -> Self {
+        RwRc::new(val).into()
+    }
+
+    /// 在 `access` 的脚本末尾追加结果，`false` 表示对应的获取失败。
+    pub fn script(&self, access: Access, outcomes: impl IntoIterator<Item = bool>) {
+        self.queue(access).borrow_mut().extend(outcomes)
+    }
+
+    /// 使接下来 `n` 次获取 `access` 失败。
+    pub fn fail_next(&self, access: Access, n: usize) {
+        self.script(access, std::iter::repeat_n(false, n))
+    }
+
+    /// 清空所有脚本。
+    pub fn reset(&self) {
+        self.reads.borrow_mut().clear();
+        self.writes.borrow_mut().clear()
+    }
+
+    /// 被包装的副本。
+    pub fn inner(&self) -> &RwRc<T> {
+        &self.rc
+    }
+
+    /// 被包装的副本。
+    pub fn inner_mut(&mut self) -> &mut RwRc<T> {
+        &mut self.rc
+    }
+
+    /// 取出被包装的副本。
+    pub fn into_inner(self) -> RwRc<T> {
+        self.rc
+    }
+
+    fn queue(&self, access: Access) -> &RefCell<VecDeque<bool>> {
+        match access {
+            Access::Read => &self.reads,
+            Access::Write => &self.writes,
+        }
+    }
+
+    /// 取出下一个脚本结果，脚本耗尽时允许获取。
+    fn allowed(&self, access: Access) -> bool {
+        self.queue(access).borrow_mut().pop_front().unwrap_or(true)
+    }
+}
+
+impl<T> SharedRw for TestRwRc<T> {
+    type Target = T;
+    type Ref<'a>
+        = LocalRef<'a, T>
+    where
+        T: 'a;
+    type Mut<'a>
+        = LocalMut<'a, T>
+    where
+        T: 'a;
+
+    fn try_read(&self) -> Option<Self::Ref<'_>> {
+        if !self.allowed(Access::Read) {
+            return None;
+        }
+        self.rc.try_read()
+    }
+
+    fn try_write(&mut self) -> Option<Self::Mut<'_>> {
+        if !self.allowed(Access::Write) {
+            return None;
+        }
+        self.rc.try_write()
+    }
+}
+
+#[test]
+fn test_scripted_failures() {
+    let mut rc = TestRwRc::new(vec![1]);
+    rc.script(Access::Read, [true, false]);
+    rc.fail_next(Access::Write, 2);
+    assert!(SharedRw::try_read(&rc).is_some());
+    assert!(SharedRw::try_read(&rc).is_none());
+    assert!(SharedRw::try_read(&rc).is_some());
+    assert!(SharedRw::try_write(&mut rc).is_none());
+    assert!(SharedRw::try_write(&mut rc).is_none());
+    SharedRw::try_write(&mut rc).unwrap().push(2);
+
+    // 脚本允许时仍然受真实的读写状态约束
+    let reader = rc.inner().clone();
+    rc.script(Access::Write, [true]);
+    assert!(SharedRw::try_write(&mut rc).is_none());
+    drop(reader);
+
+    rc.fail_next(Access::Read, 3);
+    rc.reset();
+    assert_eq!(*SharedRw::try_read(&rc).unwrap(), [1, 2]);
+    assert_eq!(*rc.into_inner().read(), [1, 2]);
+}
@@ -0,0 +1,270 @@
+use crate::{Access, Internal, RwRc, RwState};
+use std::rc::Rc;
+
+/// 以持有状态的副本调用的回调。
+type HandleFn<T> = Box<dyn FnOnce(&mut RwRc<T>)>;
+
+/// 访问权限可用时执行的回调函数。
+enum ReleaseFn<T> {
+    /// 以持有状态的新副本调用，由 [`RwRc::on_releasable`] 登记。
+    Handle(HandleFn<T>),
+    /// 在写状态下修改共享对象，由 [`RwRc::queue_mutation`] 登记。
+    Mutation(Box<dyn FnOnce(&mut T)>),
+}
+
+/// 等待访问权限可用的回调。
+pub(super) struct Releasable<T> {
+    /// 等待的访问权限。
+    access: Access,
+    /// 权限可用时执行的回调。
+    f: ReleaseFn<T>,
+}
+
+impl<T> RwRc<T> {
+    /// 登记一个在指定访问权限可用时执行的回调。
+    ///
+    /// 回调会在释放读写状态的调用栈上执行，参数是一个持有状态的新副本，
+    /// 执行时此副本可以获取所需的访问权限。
+    /// 如果登记时访问权限已经可用，回调会立即执行。
+    /// 多个回调按登记顺序执行。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{Access, RwRc};
+    /// use std::{cell::Cell, rc::Rc};
+    ///
+    /// let mut reader = RwRc::new(1);
+    /// let done = Rc::new(Cell::new(false));
+    ///
+    /// let flag = done.clone();
+    /// reader.on_releasable(Access::Write, move |rc| {
+    ///     *rc.write() += 1;
+    ///     flag.set(true)
+    /// });
+    /// // reader 持有读状态，写权限不可用
+    /// assert!(!done.get());
+    ///
+    /// // 释放读状态时执行回调
+    /// reader.release();
+    /// assert!(done.get());
+    /// assert_eq!(*reader.read(), 2);
+    /// ```
+    pub fn on_releasable(&self, access: Access, f: impl FnOnce(&mut RwRc<T>) + 'static) {
+        self.rc.check_thread();
+        self.push_releasable(access, ReleaseFn::Handle(Box::new(f)))
+    }
+
+    /// 登记等待 `access` 的回调，并执行已经可以执行的回调。
+    fn push_releasable(&self, access: Access, f: ReleaseFn<T>) {
+        let queue = &self.rc.ensure_extras().releasable;
+        queue.borrow_mut().push(Releasable { access, f });
+        self.notify_released()
+    }
+
+    /// 修改共享对象，无法立即获取写权限时推迟到写权限可用时执行。
+    ///
+    /// 可以获取写权限时立即以 [`try_write`](Self::try_write) 执行 `f`；
+    /// 否则把 `f` 登记为等待写权限的回调（参见 [`on_releasable`](Self::on_releasable)），
+    /// 阻止写入的读写状态释放后按登记顺序执行。此方法不会阻塞也不会失败。
+    ///
+    /// 推迟的修改之间保持登记顺序：任何副本通过 [`try_write`](Self::try_write)
+    /// 或 [`try_write_global`](Self::try_write_global) 获取写权限时，
+    /// 先执行所有推迟的修改，因此之后立即执行的修改不会越过它们。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let mut counter = RwRc::new(0);
+    /// let reader = counter.clone();
+    /// counter.release();
+    ///
+    /// counter.queue_mutation(|n| *n += 1);
+    /// counter.queue_mutation(|n| *n *= 10);
+    /// assert_eq!(*reader.read(), 0);
+    ///
+    /// reader.release();
+    /// assert_eq!(*counter.read(), 10);
+    /// ```
+    pub fn queue_mutation(&mut self, f: impl FnOnce(&mut T) + 'static) {
+        if let Some(mut val) = self.try_write() {
+            return f(&mut val);
+        }
+        self.push_releasable(Access::Write, ReleaseFn::Mutation(Box::new(f)))
+    }
+
+    /// 执行所有已经可以获取访问权限的回调。
+    pub(super) fn notify_released(&self) {
+        Internal::notify_released(&self.rc)
+    }
+}
+
+impl<T> Internal<T> {
+    /// 授予等待者访问权限，并执行 `rc` 及其子对象上所有已经可以获取访问权限的回调。
+    pub(super) fn notify_released(rc: &Rc<Self>) {
+        rc.grant_waiters();
+        while let Some(extras) = rc.extras() {
+            let next = {
+                let mut queue = extras.releasable.borrow_mut();
+                let pos = queue.iter().position(|r| match r.access {
+                    Access::Read => extras.pending_writers.get() == 0 && rc.can_read(),
+                    Access::Write => rc.can_write(),
+                });
+                match pos {
+                    // 推迟的修改留在队列中，获取写权限时按顺序执行
+                    Some(i) if matches!(queue[i].f, ReleaseFn::Mutation(_)) => None,
+                    Some(i) => Some(queue.remove(i).f),
+                    None => break,
+                }
+            };
+            let mut handle = RwRc::from_rc(rc.clone(), RwState::Hold);
+            match next {
+                Some(ReleaseFn::Handle(f)) => f(&mut handle),
+                _ => drop(handle.write()),
+            }
+        }
+        rc.notify_children()
+    }
+
+    /// 按登记顺序执行推迟的修改。调用时共享读写状态必须处于此调用者持有的写状态。
+    pub(super) fn apply_mutations(&self) {
+        let Some(extras) = self.extras() else { return };
+        loop {
+            let f = {
+                let mut queue = extras.releasable.borrow_mut();
+                let pos = queue
+                    .iter()
+                    .position(|r| matches!(r.f, ReleaseFn::Mutation(_)));
+                match pos.map(|i| queue.remove(i).f) {
+                    Some(ReleaseFn::Mutation(f)) => f,
+                    _ => break,
+                }
+            };
+            // 写状态下没有其他副本能访问共享对象
+            f(unsafe { &mut *self.val.as_ptr() })
+        }
+    }
+}
+
+#[test]
+fn test_on_releasable_write() {
+    use std::{cell::Cell, rc::Rc};
+
+    let rc1 = RwRc::new(0);
+    let rc2 = rc1.clone();
+    let count = Rc::new(Cell::new(0));
+
+    let counter = count.clone();
+    rc1.on_releasable(Access::Write, move |rc| {
+        *rc.write() += 1;
+        counter.set(counter.get() + 1)
+    });
+    assert_eq!(count.get(), 0);
+
+    // 还有 rc2 持有读状态，写权限仍不可用
+    rc1.release();
+    assert_eq!(count.get(), 0);
+
+    // 所有读状态释放后执行回调，且只执行一次
+    rc2.release();
+    assert_eq!(count.get(), 1);
+    assert!(rc1.try_read_global());
+    rc1.release();
+    assert_eq!(count.get(), 1);
+    assert_eq!(*rc1.read(), 1);
+}
+
+#[test]
+fn test_on_releasable_read() {
+    use std::{cell::Cell, rc::Rc};
+
+    let rc1 = RwRc::new(0);
+    let mut rc2 = rc1.clone();
+    rc1.release();
+    rc2.release();
+    assert!(rc2.try_write_global());
+
+    let read = Rc::new(Cell::new(None));
+    let value = read.clone();
+    rc1.on_releasable(Access::Read, move |rc| value.set(Some(*rc.read())));
+    assert_eq!(read.get(), None);
+
+    // 写守卫释放时触发回调
+    *rc2.write() = 5;
+    assert_eq!(read.get(), None);
+    rc2.release();
+    assert_eq!(read.get(), Some(5));
+}
+
+#[test]
+fn test_on_releasable_immediate_and_order() {
+    use std::{cell::RefCell, rc::Rc};
+
+    let rc = RwRc::new(0);
+    rc.release();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    // 访问权限可用时立即执行
+    let l = log.clone();
+    rc.on_releasable(Access::Write, move |_| l.borrow_mut().push(0));
+    assert_eq!(*log.borrow(), [0]);
+
+    // 权限不可用时按登记顺序排队执行
+    assert!(rc.try_read_global());
+    for i in 1..=3 {
+        let l = log.clone();
+        rc.on_releasable(Access::Write, move |_| l.borrow_mut().push(i));
+    }
+    assert_eq!(*log.borrow(), [0]);
+    {
+        // 守卫释放只影响临时状态，不会触发回调
+        let _ = rc.read();
+    }
+    assert_eq!(*log.borrow(), [0]);
+    rc.release();
+    assert_eq!(*log.borrow(), [0, 1, 2, 3]);
+}
+
+#[test]
+fn test_queue_mutation() {
+    let mut rc = RwRc::new(vec![0]);
+    // 可以获取写权限时立即执行
+    rc.queue_mutation(|v| v.push(1));
+    assert_eq!(*rc.read(), [0, 1]);
+
+    let writer = rc.clone();
+    rc.release();
+    writer.release();
+    assert!(writer.try_write_global());
+    rc.queue_mutation(|v| v.push(2));
+    rc.queue_mutation(|v| v.push(3));
+    assert_eq!(writer.read().len(), 2);
+
+    // 写者释放后按登记顺序执行
+    writer.release();
+    assert_eq!(*rc.read(), [0, 1, 2, 3]);
+    assert!(rc.rc.extras().unwrap().releasable.borrow().is_empty());
+}
+
+#[test]
+fn test_queue_mutation_order() {
+    let mut rc = RwRc::new(vec![0]);
+    let other = rc.clone();
+
+    // 推迟的修改先于之后立即执行的修改
+    rc.queue_mutation(|v| v.push(1));
+    other.release();
+    rc.queue_mutation(|v| v.push(2));
+    assert_eq!(*rc.read(), [0, 1, 2]);
+
+    // 此副本从读状态获取写状态时执行推迟的修改
+    assert!(other.try_read_global());
+    rc.queue_mutation(|v| v.push(3));
+    other.release();
+    assert!(rc.try_write_global());
+    assert_eq!(*rc.read(), [0, 1, 2, 3]);
+    rc.release();
+    assert!(rc.rc.extras().unwrap().releasable.borrow().is_empty());
+}
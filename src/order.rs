@@ -0,0 +1,514 @@
+use crate::{Access, LocalMut, LocalRef, LockError, RwRc, RwState};
+use std::{cell::RefCell, marker::PhantomData, ptr::NonNull};
+
+/// 按确定的顺序获取一组副本的访问权限。
+///
+/// 副本按共享对象的标识（[`RwRc::id`]）排序后依次获取，
+/// 因此无论以什么顺序登记，同一组共享对象总是以相同的顺序获取，
+/// 避免协作式任务之间因获取顺序不一致而互相阻挡。
+/// 任何一个副本获取失败时，已经获取的访问权限会被撤销，所有副本回到原来的读写状态。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{LockOrder, RwRc};
+///
+/// let mut a = RwRc::new(1);
+/// let mut b = RwRc::new("b");
+/// a.release();
+/// b.release();
+///
+/// LockOrder::new().write(&mut a).read(&mut b).acquire().unwrap();
+/// assert!(a.is_writeable());
+/// assert_eq!(*b.read(), "b");
+/// ```
+#[derive(Default)]
+pub struct LockOrder<'a> {
+    /// 登记的副本。
+    entries: Vec<Entry<'a>>,
+}
+
+/// 登记的一个副本。
+struct Entry<'a> {
+    /// 请求的访问权限。
+    access: Access,
+    /// 类型擦除的副本。
+    handle: &'a mut dyn Ordered,
+}
+
+/// 类型擦除的副本。
+///
+/// 出现在 [`sched`](crate::sched) 密封 trait 的签名中，因此声明为 `pub`，但模块外无法访问。
+pub trait Ordered {
+    /// 共享对象的标识。
+    fn id(&self) -> usize;
+    /// 副本当前的读写状态。
+    fn state(&self) -> RwState;
+    /// 不经过等待队列尝试获取 `access`。
+    fn acquire(&mut self, access: Access) -> bool;
+    /// 回到获取前的读写状态 `state`。
+    fn restore(&mut self, state: RwState);
+}
+
+impl<T> Ordered for RwRc<T> {
+    fn id(&self) -> usize {
+        RwRc::id(self)
+    }
+
+    fn state(&self) -> RwState {
+        self.state.get()
+    }
+
+    fn acquire(&mut self, access: Access) -> bool {
+        self.try_acquire(access)
+    }
+
+    fn restore(&mut self, state: RwState) {
+        match (self.state.get(), state) {
+            (RwState::Write, RwState::Read | RwState::Upgradable) => {
+                self.rc.write_to_read();
+                self.set_state(state);
+                self.notify_released()
+            }
+            (_, RwState::Hold) => self.release(),
+            _ => {}
+        }
+    }
+}
+
+impl<'a> LockOrder<'a> {
+    /// 创建空的获取顺序。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记一个需要读状态的副本。
+    pub fn read<T>(self, rc: &'a mut RwRc<T>) -> Self {
+        self.push(Access::Read, rc)
+    }
+
+    /// 登记一个需要写状态的副本。
+    pub fn write<T>(self, rc: &'a mut RwRc<T>) -> Self {
+        self.push(Access::Write, rc)
+    }
+
+    pub(super) fn push(mut self, access: Access, handle: &'a mut dyn Ordered) -> Self {
+        self.entries.push(Entry { access, handle });
+        self
+    }
+
+    /// 按共享对象的标识依次获取所有登记的访问权限。
+    ///
+    /// 获取失败时撤销已经获取的访问权限，返回第一个失败的副本请求的访问权限。
+    pub fn acquire(mut self) -> Result<(), LockError> {
+        self.entries.sort_by_key(|e| e.handle.id());
+        let states = self
+            .entries
+            .iter()
+            .map(|e| e.handle.state())
+            .collect::<Vec<_>>();
+        for i in 0..self.entries.len() {
+            let Entry { access, handle } = &mut self.entries[i];
+            if !handle.acquire(*access) {
+                let err = LockError::Blocked(*access);
+                for (entry, state) in self.entries[..i].iter_mut().zip(states).rev() {
+                    entry.handle.restore(state)
+                }
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 通过作用域获取访问权限的副本，由 [`scope`] 创建。
+///
+/// 作用域结束时，包括提前返回和 panic 展开时，
+/// 所有通过作用域获取过访问权限的副本按获取的逆序回到获取前的读写状态。
+pub struct Scope<'scope, 'env: 'scope> {
+    /// 通过作用域获取过访问权限的副本。
+    acquired: &'scope Acquired<'env>,
+    /// 与 [`std::thread::Scope`] 相同，使 `'scope` 不变，
+    /// 作用域内创建的副本因此无法借用整个作用域。
+    scope: PhantomData<&'scope mut &'scope ()>,
+}
+
+/// 通过作用域获取过访问权限的副本和获取前的读写状态，释放时还原。
+#[derive(Default)]
+struct Acquired<'env>(RefCell<Vec<(NonNull<dyn Ordered + 'env>, RwState)>>);
+
+impl Drop for Acquired<'_> {
+    fn drop(&mut self) {
+        for (mut handle, state) in self.0.get_mut().drain(..).rev() {
+            // 作用域已经结束，通过作用域得到的引用都已失效，副本只能通过这里访问
+            unsafe { handle.as_mut() }.restore(state)
+        }
+    }
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// 获取读状态，返回副本的引用。
+    ///
+    /// 副本在作用域结束时回到获取前的读写状态。
+    /// 无法获取时返回错误，副本不受作用域管理。
+    pub fn read<T: 'env>(
+        &'scope self,
+        rc: &'scope mut RwRc<T>,
+    ) -> Result<&'scope RwRc<T>, LockError> {
+        self.acquire(Access::Read, rc).map(|rc| &*rc)
+    }
+
+    /// 获取写状态，返回副本的引用。
+    ///
+    /// 副本在作用域结束时回到获取前的读写状态。
+    /// 无法获取时返回错误，副本不受作用域管理。
+    pub fn write<T: 'env>(
+        &'scope self,
+        rc: &'scope mut RwRc<T>,
+    ) -> Result<&'scope mut RwRc<T>, LockError> {
+        self.acquire(Access::Write, rc)
+    }
+
+    fn acquire<T: 'env>(
+        &'scope self,
+        access: Access,
+        rc: &'scope mut RwRc<T>,
+    ) -> Result<&'scope mut RwRc<T>, LockError> {
+        let state = rc.state.get();
+        if !rc.try_acquire(access) {
+            return Err(match access {
+                Access::Read => rc.read_error(),
+                Access::Write => LockError::Blocked(Access::Write),
+            });
+        }
+        let ptr = NonNull::from(rc);
+        self.acquired.0.borrow_mut().push((ptr, state));
+        // 返回的引用不会超出作用域，作用域结束前登记的指针不会被使用
+        Ok(unsafe { &mut *ptr.as_ptr() })
+    }
+}
+
+/// 创建一个作用域，作用域结束时撤销其中通过 [`Scope`] 获取的所有访问权限。
+///
+/// 与 [`LockOrder`] 一次获取一组访问权限不同，作用域中可以逐个获取并在任意位置以 `?` 提前返回，
+/// 不需要记录每条错误路径上已经获取了哪些副本。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{LockError, RwRc, RwState};
+///
+/// let mut a = RwRc::new(1);
+/// let mut b = RwRc::new(2);
+/// a.release();
+/// b.release();
+/// let blocker = b.clone();
+/// blocker.try_read_global();
+///
+/// let result = rwrc::scope(|s| -> Result<i32, LockError> {
+///     let a = s.read(&mut a)?;
+///     let b = s.write(&mut b)?; // 被读者阻挡，提前返回
+///     *b.write() += *a.read();
+///     Ok(*b.read())
+/// });
+/// assert!(result.is_err());
+/// // 已经获取的读状态在作用域结束时释放
+/// assert_eq!(a.state(), RwState::Hold);
+/// ```
+pub fn scope<'env, R>(f: impl for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> R) -> R {
+    let acquired = Acquired::default();
+    let scope = Scope {
+        acquired: &acquired,
+        scope: PhantomData,
+    };
+    f(&scope)
+}
+
+/// 获取所有副本的只读借用，任何一个无法获取时返回 `None`。
+///
+/// 返回 `None` 时已经获取的借用会被释放，所有副本回到原来的读写状态。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, read_all};
+///
+/// let a = RwRc::new(1);
+/// let b = RwRc::new(2);
+/// let sum = read_all(&[&a, &b]).unwrap().iter().map(|r| **r).sum::<i32>();
+/// assert_eq!(sum, 3);
+/// ```
+pub fn read_all<'a, T>(handles: &[&'a RwRc<T>]) -> Option<Vec<LocalRef<'a, T>>> {
+    handles.iter().map(|rc| rc.try_read()).collect()
+}
+
+/// 获取所有副本的可变借用，任何一个无法获取时返回 `None`。
+///
+/// 返回 `None` 时已经获取的借用会被释放，所有副本回到原来的读写状态。
+/// 同一个共享对象的多个副本无法同时获取可变借用。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, write_all};
+///
+/// let mut a = RwRc::new(1);
+/// let mut b = RwRc::new(2);
+/// for mut w in write_all(&mut [&mut a, &mut b]).unwrap() {
+///     *w *= 10
+/// }
+/// assert_eq!((*a.read(), *b.read()), (10, 20));
+/// ```
+pub fn write_all<'a, T>(handles: &'a mut [&mut RwRc<T>]) -> Option<Vec<LocalMut<'a, T>>> {
+    handles.iter_mut().map(|rc| rc.try_write()).collect()
+}
+
+/// 同时借用两个副本并以借用的值调用 `f`，任何一个无法读取时返回错误而不调用 `f`。
+///
+/// 返回错误时已经获取的借用会被释放，两个副本回到原来的读写状态。
+/// 两个以上的副本使用 [`with_read!`](crate::with_read)。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, with_read2};
+///
+/// let a = RwRc::new(vec![1, 2]);
+/// let b = RwRc::new(vec![3, 4]);
+/// let dot = with_read2(&a, &b, |x, y| x.iter().zip(y).map(|(x, y)| x * y).sum::<i32>());
+/// assert_eq!(dot, Ok(11));
+/// ```
+pub fn with_read2<A, B, R>(
+    a: &RwRc<A>,
+    b: &RwRc<B>,
+    f: impl FnOnce(&A, &B) -> R,
+) -> Result<R, LockError> {
+    let blocked = LockError::Blocked(Access::Read);
+    let x = a.try_read().ok_or(blocked)?;
+    let y = b.try_read().ok_or(blocked)?;
+    Ok(f(&x, &y))
+}
+
+/// 同时可变借用两个副本并以借用的值调用 `f`，任何一个无法写入时返回错误而不调用 `f`。
+///
+/// 返回错误时已经获取的借用会被释放，两个副本回到原来的读写状态。
+/// 同一个共享对象的两个副本无法同时写入。
+/// 两个以上的副本使用 [`with_write!`](crate::with_write)。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, with_write2};
+///
+/// let mut src = RwRc::new(vec![1, 2]);
+/// let mut dst = RwRc::new(Vec::new());
+/// with_write2(&mut src, &mut dst, |s, d| d.append(s)).unwrap();
+/// assert_eq!((src.read().len(), dst.read().len()), (0, 2));
+///
+/// // 同一个共享对象的另一个副本无法同时写入
+/// let mut alias = dst.clone();
+/// assert!(with_write2(&mut dst, &mut alias, |_, _| ()).is_err());
+/// ```
+pub fn with_write2<A, B, R>(
+    a: &mut RwRc<A>,
+    b: &mut RwRc<B>,
+    f: impl FnOnce(&mut A, &mut B) -> R,
+) -> Result<R, LockError> {
+    let blocked = LockError::Blocked(Access::Write);
+    let mut x = a.try_write().ok_or(blocked)?;
+    let mut y = b.try_write().ok_or(blocked)?;
+    Ok(f(&mut x, &mut y))
+}
+
+/// 同时借用任意数量的副本并求值表达式，任何一个无法读取时返回错误而不求值。
+///
+/// 与 [`with_read2`](crate::with_read2) 相同，但接受任意数量的副本：
+/// 每个 `名称 = 副本` 把副本的只读借用绑定到名称，`=>` 之后的表达式以这些名称求值，
+/// 结果为 `Result<R, LockError>`。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, with_read};
+///
+/// let a = RwRc::new(1);
+/// let b = RwRc::new(2.5);
+/// let c = RwRc::new("x");
+/// let s = with_read!(a = a, b = b, c = c => format!("{c}{}", *a as f64 + b));
+/// assert_eq!(s.unwrap(), "x3.5");
+/// ```
+#[macro_export]
+macro_rules! with_read {
+    ($($name:ident = $rc:expr),+ $(,)? => $body:expr) => {
+        (|| -> ::core::result::Result<_, $crate::LockError> {
+            $(let $name = $crate::RwRc::try_read(&$rc)
+                .ok_or($crate::LockError::Blocked($crate::Access::Read))?;)+
+            // 被遮蔽的借用存活到求值结束
+            $(let $name = &*$name;)+
+            ::core::result::Result::Ok($body)
+        })()
+    };
+}
+
+/// 同时可变借用任意数量的副本并求值表达式，任何一个无法写入时返回错误而不求值。
+///
+/// 与 [`with_write2`](crate::with_write2) 相同，但接受任意数量的副本：
+/// 每个 `名称 = 副本` 把副本的可变借用绑定到名称，`=>` 之后的表达式以这些名称求值，
+/// 结果为 `Result<R, LockError>`。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, with_write};
+///
+/// let mut a = RwRc::new(1);
+/// let mut b = RwRc::new(2);
+/// let mut c = RwRc::new(3);
+/// with_write!(x = a, y = b, z = c => {
+///     std::mem::swap(x, z);
+///     *y *= 10
+/// })
+/// .unwrap();
+/// assert_eq!((*a.read(), *b.read(), *c.read()), (3, 20, 1));
+/// ```
+#[macro_export]
+macro_rules! with_write {
+    ($($name:ident = $rc:expr),+ $(,)? => $body:expr) => {
+        (|| -> ::core::result::Result<_, $crate::LockError> {
+            $(let mut $name = $crate::RwRc::try_write(&mut $rc)
+                .ok_or($crate::LockError::Blocked($crate::Access::Write))?;)+
+            // 被遮蔽的借用存活到求值结束
+            $(let $name = &mut *$name;)+
+            ::core::result::Result::Ok($body)
+        })()
+    };
+}
+
+#[test]
+fn test_lock_order() {
+    let mut a = RwRc::new(1);
+    let mut b = RwRc::new(2);
+    let mut c = RwRc::new(3);
+    b.release();
+    c.release();
+    let blocker = c.clone();
+
+    // 登记顺序不影响结果
+    LockOrder::new()
+        .read(&mut c)
+        .write(&mut a)
+        .write(&mut b)
+        .acquire()
+        .unwrap();
+    assert!(matches!(a.state.get(), RwState::Write));
+    assert!(matches!(b.state.get(), RwState::Write));
+    assert!(matches!(c.state.get(), RwState::Read));
+    a.release();
+    b.release();
+    c.release();
+
+    // 任何一个失败时所有副本回到原来的状态
+    assert!(a.try_read_global());
+    assert!(blocker.try_write_global());
+    assert_eq!(
+        LockOrder::new()
+            .write(&mut a)
+            .write(&mut b)
+            .read(&mut c)
+            .acquire(),
+        Err(LockError::Blocked(Access::Read))
+    );
+    assert!(matches!(a.state.get(), RwState::Read));
+    assert!(matches!(b.state.get(), RwState::Hold));
+    assert!(matches!(c.state.get(), RwState::Hold));
+    assert!(b.is_writeable());
+    assert!(!a.is_read_by_others());
+}
+
+#[test]
+fn test_read_write_all() {
+    let mut a = RwRc::new(1);
+    let mut b = RwRc::new(2);
+    a.release();
+    b.release();
+    let mut blocker = b.clone();
+
+    assert_eq!(read_all(&[&a, &b, &a]).unwrap().len(), 3);
+    assert!(write_all(&mut [&mut a, &mut b]).is_some());
+    assert!(matches!(a.state.get(), RwState::Hold));
+
+    // 任何一个失败时已经获取的借用被释放
+    assert!(blocker.try_write_global());
+    assert!(read_all(&[&a, &b]).is_none());
+    assert!(a.is_writeable());
+    blocker.release();
+    assert!(write_all(&mut [&mut a, &mut b, &mut blocker]).is_none());
+    assert!(a.is_writeable() && b.is_writeable());
+}
+
+#[test]
+fn test_scope() {
+    let mut a = RwRc::new(1);
+    let mut b = RwRc::new(2);
+    b.release();
+    let observer = a.clone();
+    observer.release();
+
+    // 作用域结束时回到获取前的状态
+    let sum = scope(|s| {
+        let a = s.write(&mut a).unwrap();
+        let b = s.read(&mut b).unwrap();
+        *a.write() += 10;
+        assert!(!observer.is_readable());
+        *a.read() + *b.read()
+    });
+    assert_eq!(sum, 13);
+    assert_eq!(a.state(), RwState::Read);
+    assert_eq!(b.state(), RwState::Hold);
+    assert!(observer.is_readable());
+
+    // panic 展开时同样还原
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        scope(|s| {
+            s.write(&mut b).unwrap();
+            panic!()
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(b.state(), RwState::Hold);
+    assert!(b.is_writeable());
+
+    // 获取失败的副本不受作用域管理
+    assert!(observer.try_read_global());
+    scope(|s| {
+        assert_eq!(
+            s.write(&mut a).map(|_| ()),
+            Err(LockError::Blocked(Access::Write))
+        );
+    });
+    assert_eq!(a.state(), RwState::Read);
+}
+
+#[test]
+fn test_with2() {
+    let mut a = RwRc::new(1);
+    let mut b = RwRc::new(2);
+    b.release();
+    let blocker = b.clone();
+    assert!(blocker.try_write_global());
+
+    // 失败时不调用闭包，已经获取的状态还原
+    assert_eq!(
+        with_read2(&a, &b, |_, _| unreachable!()),
+        Err(LockError::Blocked(Access::Read))
+    );
+    assert!(with_write2(&mut a, &mut b, |_, _| unreachable!()).is_err());
+    assert_eq!(a.state(), RwState::Read);
+    assert!(crate::with_read!(x = a, y = b => x + y).is_err());
+
+    blocker.release();
+    assert_eq!(with_read2(&a, &b, |x, y| x + y), Ok(3));
+    assert_eq!(b.state(), RwState::Hold);
+    crate::with_write!(x = a, y = b => std::mem::swap(x, y)).unwrap();
+    assert_eq!(crate::with_read!(x = a, y = b => (*x, *y)), Ok((2, 1)));
+}
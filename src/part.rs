@@ -0,0 +1,207 @@
+use crate::{LocalMut, LocalRef, MappedLocalMut, MappedLocalRef, RwRc, RwState};
+
+/// 共享元组对象中一个分量的副本，由 [`RwRc::unzip`] 创建。
+///
+/// 分量副本与原来的共享对象共享同一个分配和共享读写状态：
+/// 锁定任何一个分量都会锁定整个元组，因此不同分量之间也按读写规则互相阻挡。
+/// 分量副本的读写状态规则与 [`RwRc`] 相同。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::RwRc;
+///
+/// let rc = RwRc::new((vec![1, 2], String::from("log")));
+/// rc.release();
+/// let (mut data, log) = rc.unzip();
+///
+/// data.write().push(3);
+/// assert_eq!(&*log.read(), "log");
+///
+/// // 一个分量被读取时另一个分量无法写入
+/// assert!(log.try_read_global());
+/// assert!(data.try_write().is_none());
+/// ```
+pub struct RwPart<U> {
+    /// 类型擦除的投影。
+    part: Box<dyn Part<U>>,
+}
+
+/// 类型擦除的投影。
+trait Part<U> {
+    /// 投影所在副本的读写状态。
+    fn state(&self) -> RwState;
+    /// 尝试设置到读状态。
+    fn try_read_global(&self) -> bool;
+    /// 尝试设置到写状态。
+    fn try_write_global(&self) -> bool;
+    /// 释放读写状态。
+    fn release(&self);
+    /// 尝试获取分量的只读借用。
+    fn try_read(&self) -> Option<MappedLocalRef<'_, U>>;
+    /// 尝试获取分量的可变借用。
+    fn try_write(&mut self) -> Option<MappedLocalMut<'_, U>>;
+    /// 克隆投影。
+    fn clone_part(&self) -> Box<dyn Part<U>>;
+}
+
+/// 从共享对象到它一部分的投影。
+struct Projection<T, U> {
+    /// 共享对象的副本。
+    rc: RwRc<T>,
+    /// 只读投影。
+    get: fn(&T) -> &U,
+    /// 可变投影。
+    get_mut: fn(&mut T) -> &mut U,
+}
+
+impl<T: 'static, U: 'static> Part<U> for Projection<T, U> {
+    fn state(&self) -> RwState {
+        self.rc.state()
+    }
+
+    fn try_read_global(&self) -> bool {
+        self.rc.try_read_global()
+    }
+
+    fn try_write_global(&self) -> bool {
+        self.rc.try_write_global()
+    }
+
+    fn release(&self) {
+        self.rc.release()
+    }
+
+    fn try_read(&self) -> Option<MappedLocalRef<'_, U>> {
+        self.rc.try_read().map(|r| LocalRef::map(r, self.get))
+    }
+
+    fn try_write(&mut self) -> Option<MappedLocalMut<'_, U>> {
+        let get_mut = self.get_mut;
+        self.rc.try_write().map(|w| LocalMut::map(w, get_mut))
+    }
+
+    fn clone_part(&self) -> Box<dyn Part<U>> {
+        Box::new(Self {
+            rc: self.rc.clone(),
+            get: self.get,
+            get_mut: self.get_mut,
+        })
+    }
+}
+
+impl<A: 'static, B: 'static> RwRc<(A, B)> {
+    /// 将元组共享对象拆分为两个分量副本。
+    ///
+    /// 第一个分量副本取得此副本的读写状态，第二个分量副本按 [`Clone`] 的规则创建。
+    pub fn unzip(self) -> (RwPart<A>, RwPart<B>) {
+        let second = Projection {
+            rc: self.clone(),
+            get: |(_, b): &(A, B)| b,
+            get_mut: |(_, b): &mut (A, B)| b,
+        };
+        let first = Projection {
+            rc: self,
+            get: |(a, _): &(A, B)| a,
+            get_mut: |(a, _): &mut (A, B)| a,
+        };
+        (
+            RwPart {
+                part: Box::new(first),
+            },
+            RwPart {
+                part: Box::new(second),
+            },
+        )
+    }
+}
+
+impl<U> RwPart<U> {
+    /// 此副本当前的读写状态。
+    pub fn state(&self) -> RwState {
+        self.part.state()
+    }
+
+    /// 尝试设置到读状态，参见 [`RwRc::try_read_global`]。
+    #[track_caller]
+    pub fn try_read_global(&self) -> bool {
+        self.part.try_read_global()
+    }
+
+    /// 尝试设置到写状态，参见 [`RwRc::try_write_global`]。
+    #[track_caller]
+    pub fn try_write_global(&self) -> bool {
+        self.part.try_write_global()
+    }
+
+    /// 释放读写状态，参见 [`RwRc::release`]。
+    #[track_caller]
+    pub fn release(&self) {
+        self.part.release()
+    }
+
+    /// 尝试获取分量的只读借用，无法获取读状态时返回 `None`。
+    pub fn try_read(&self) -> Option<MappedLocalRef<'_, U>> {
+        self.part.try_read()
+    }
+
+    /// 尝试获取分量的可变借用，无法获取写状态时返回 `None`。
+    pub fn try_write(&mut self) -> Option<MappedLocalMut<'_, U>> {
+        self.part.try_write()
+    }
+
+    /// 获取分量的只读借用。
+    ///
+    /// # Panic
+    ///
+    /// 无法获取读状态时 panic。
+    #[track_caller]
+    pub fn read(&self) -> MappedLocalRef<'_, U> {
+        self.try_read().unwrap()
+    }
+
+    /// 获取分量的可变借用。
+    ///
+    /// # Panic
+    ///
+    /// 无法获取写状态时 panic。
+    #[track_caller]
+    pub fn write(&mut self) -> MappedLocalMut<'_, U> {
+        self.try_write().unwrap()
+    }
+}
+
+impl<U> Clone for RwPart<U> {
+    /// 克隆分量副本，读写状态的规则与 [`RwRc`] 的克隆相同。
+    fn clone(&self) -> Self {
+        Self {
+            part: self.part.clone_part(),
+        }
+    }
+}
+
+#[test]
+fn test_unzip() {
+    let rc = RwRc::new((1, String::from("a")));
+    let whole = rc.clone();
+    let (mut num, mut text) = rc.unzip();
+    assert_eq!(num.state(), RwState::Read);
+    assert_eq!(text.state(), RwState::Read);
+    assert_eq!(*num.read(), 1);
+    assert!(num.try_write().is_none());
+
+    // 分量与原来的共享对象共享读写状态
+    whole.release();
+    num.release();
+    let mut guard = text.write();
+    guard.push('b');
+    assert!(!whole.is_readable());
+    assert!(num.try_read().is_none());
+    drop(guard);
+    text.release();
+
+    *num.write() += 1;
+    let copy = num.clone();
+    assert_eq!(copy.state(), RwState::Hold);
+    assert_eq!(*whole.read(), (2, String::from("ab")));
+}
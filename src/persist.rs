@@ -0,0 +1,217 @@
+use crate::RwRc;
+use serde::{Serialize, de::DeserializeOwned};
+use std::{
+    cell::Cell,
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    rc::{Rc, Weak},
+    time::{Duration, Instant},
+};
+
+/// 保存在文件中的共享对象，每次写状态结束时自动保存。
+///
+/// 创建时从文件加载共享对象，之后任何副本的写状态结束时都以编码函数序列化并写入文件，
+/// 写入先写临时文件再替换，中途失败不会破坏原来的文件。
+/// 设置了保存间隔时，距离上次保存不足间隔的提交只标记为未保存，
+/// 由下一次超过间隔的提交、[`flush`](Self::flush) 或丢弃 `Persistent` 时保存。
+/// 丢弃 `Persistent` 后不再自动保存。
+///
+/// 自动保存的错误无法返回给写入者，会被记录下来，由 [`take_error`](Self::take_error) 取出。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::Persistent;
+///
+/// let path = std::env::temp_dir().join(format!("rwrc-doc-{}.cfg", std::process::id()));
+/// let encode = |v: &u32| Ok::<_, std::io::Error>(v.to_le_bytes().to_vec());
+/// let decode = |b: &[u8]| b.try_into().map(u32::from_le_bytes);
+///
+/// let settings = Persistent::open(&path, || 1, encode, decode).unwrap();
+/// *settings.handle().write() = 2;
+/// drop(settings);
+///
+/// let settings = Persistent::open(&path, || 1, encode, decode).unwrap();
+/// assert_eq!(*settings.handle().read(), 2);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct Persistent<T> {
+    /// 持有状态的副本。
+    rc: RwRc<T>,
+    /// 保存状态，自动保存的观察者只持有弱引用。
+    state: Rc<State<T>>,
+}
+
+/// 序列化共享对象的函数。
+type Encode<T> = Box<dyn Fn(&T) -> io::Result<Vec<u8>>>;
+
+/// 保存的目标和进度。
+struct State<T> {
+    /// 文件路径。
+    path: PathBuf,
+    /// 序列化共享对象。
+    encode: Encode<T>,
+    /// 两次自动保存之间的最短间隔。
+    interval: Cell<Duration>,
+    /// 上一次保存的时间。
+    saved_at: Cell<Option<Instant>>,
+    /// 是否有没有保存的提交。
+    dirty: Cell<bool>,
+    /// 最近一次自动保存的错误。
+    error: Cell<Option<io::Error>>,
+}
+
+impl<T: Serialize + DeserializeOwned + 'static> Persistent<T> {
+    /// 从 `path` 加载共享对象，文件不存在时以 `init` 创建。
+    ///
+    /// `encode` 和 `decode` 以选定的格式序列化和反序列化，
+    /// 例如 `|v| serde_json::to_vec(v)` 与 `|b| serde_json::from_slice(b)`。
+    /// 文件存在但无法读取或解码时返回错误。
+    pub fn open<E, D>(
+        path: impl AsRef<Path>,
+        init: impl FnOnce() -> T,
+        encode: impl Fn(&T) -> Result<Vec<u8>, E> + 'static,
+        decode: impl FnOnce(&[u8]) -> Result<T, D>,
+    ) -> io::Result<Self>
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+        D: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let val = match fs::read(&path) {
+            Ok(bytes) => decode(&bytes).map_err(io::Error::other)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => init(),
+            Err(e) => return Err(e),
+        };
+        let state = Rc::new(State {
+            path,
+            encode: Box::new(move |val| encode(val).map_err(io::Error::other)),
+            interval: Cell::new(Duration::ZERO),
+            saved_at: Cell::new(None),
+            dirty: Cell::new(false),
+            error: Cell::new(None),
+        });
+        let rc = RwRc::new(val);
+        rc.release();
+        let weak = Rc::downgrade(&state);
+        rc.rc.ensure_extras().committed.push(move |val: &T| {
+            Weak::upgrade(&weak).is_some_and(|state| {
+                state.commit(val);
+                true
+            })
+        });
+        Ok(Self { rc, state })
+    }
+}
+
+impl<T> Persistent<T> {
+    /// 获取共享对象的副本，初始为持有状态。
+    pub fn handle(&self) -> RwRc<T> {
+        self.rc.clone()
+    }
+
+    /// 保存的文件路径。
+    pub fn path(&self) -> &Path {
+        &self.state.path
+    }
+
+    /// 设置两次自动保存之间的最短间隔，默认为零，每次提交都保存。
+    pub fn set_interval(&self, interval: Duration) {
+        self.state.interval.set(interval)
+    }
+
+    /// 立即保存没有保存的提交。
+    ///
+    /// 共享对象被其他副本写锁定时返回错误，写状态结束时的提交会照常处理。
+    pub fn flush(&self) -> io::Result<()> {
+        if !self.state.dirty.get() {
+            return Ok(());
+        }
+        let val = self
+            .rc
+            .try_read()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::WouldBlock, self.rc.read_error()))?;
+        self.state.save(&val);
+        self.take_error().map_or(Ok(()), Err)
+    }
+
+    /// 取出最近一次自动保存的错误。
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.state.error.take()
+    }
+}
+
+impl<T> Drop for Persistent<T> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+impl<T> State<T> {
+    /// 处理一次提交，距离上次保存不足间隔时只标记为未保存。
+    fn commit(&self, val: &T) {
+        match self.saved_at.get() {
+            Some(at) if at.elapsed() < self.interval.get() => self.dirty.set(true),
+            _ => self.save(val),
+        }
+    }
+
+    /// 保存共享对象，失败时记录错误并保持未保存标记。
+    fn save(&self, val: &T) {
+        let result = (self.encode)(val).and_then(|bytes| {
+            let tmp = self.path.with_extension("tmp");
+            fs::write(&tmp, bytes)?;
+            fs::rename(&tmp, &self.path)
+        });
+        match result {
+            Ok(()) => {
+                self.saved_at.set(Some(Instant::now()));
+                self.dirty.set(false)
+            }
+            Err(e) => {
+                self.dirty.set(true);
+                self.error.set(Some(e))
+            }
+        }
+    }
+}
+
+#[test]
+fn test_persistent() {
+    let path = std::env::temp_dir().join(format!("rwrc-test-{}.cfg", std::process::id()));
+    let encode = |v: &u32| Ok::<_, io::Error>(v.to_le_bytes().to_vec());
+    let decode = |b: &[u8]| b.try_into().map(u32::from_le_bytes);
+    let load = || u32::from_le_bytes(fs::read(&path).unwrap().try_into().unwrap());
+
+    let settings = Persistent::open(&path, || 1, encode, decode).unwrap();
+    assert!(!path.exists());
+    let mut rc = settings.handle();
+    *rc.write() = 2;
+    assert_eq!(load(), 2);
+
+    // 间隔内的提交推迟到刷新时保存
+    settings.set_interval(Duration::from_secs(3600));
+    *rc.write() = 3;
+    assert!(rc.try_write_global());
+    assert_eq!(load(), 2);
+    assert_eq!(
+        settings.flush().unwrap_err().kind(),
+        io::ErrorKind::WouldBlock
+    );
+    rc.release();
+    settings.flush().unwrap();
+    assert_eq!(load(), 3);
+
+    // 丢弃后不再自动保存
+    *rc.write() = 4;
+    drop(settings);
+    assert_eq!(load(), 4);
+    *rc.write() = 5;
+    assert_eq!(load(), 4);
+
+    // 无法解码时返回错误
+    fs::write(&path, [0]).unwrap();
+    assert!(Persistent::open(&path, || 0, encode, decode).is_err());
+    fs::remove_file(&path).unwrap();
+}
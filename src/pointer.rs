@@ -0,0 +1,121 @@
+use crate::{Internal, RwRc, RwState};
+use std::{mem::ManuallyDrop, rc::Rc};
+
+impl<T> RwRc<T> {
+    /// 释放读写状态后消耗副本，返回指向共享对象的指针。
+    ///
+    /// 指针代表一个持有状态的副本，占用一个强引用，
+    /// 需要以 [`from_raw`](Self::from_raw) 换回副本才能释放，否则共享对象会泄漏。
+    ///
+    /// # Panic
+    ///
+    /// 需要改变读写状态而此副本正被借用时 panic。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{RwRc, RwState};
+    ///
+    /// let rc = RwRc::new(String::from("ffi"));
+    /// let ptr = RwRc::into_raw(rc);
+    /// // 指针指向共享对象本身
+    /// assert_eq!(unsafe { &*ptr }, "ffi");
+    ///
+    /// let rc = unsafe { RwRc::from_raw(ptr) };
+    /// assert_eq!(rc.state(), RwState::Hold);
+    /// assert_eq!(*rc.read(), "ffi");
+    /// ```
+    #[track_caller]
+    pub fn into_raw(this: Self) -> *const T {
+        this.release();
+        let this = ManuallyDrop::new(this);
+        // 副本不会再被使用，其余字段不需要释放
+        let rc = unsafe { std::ptr::read(&this.rc) };
+        let internal = Rc::into_raw(rc);
+        unsafe { &raw const (*internal).val }.cast()
+    }
+
+    /// 以 [`into_raw`](Self::into_raw) 返回的指针重建一个持有状态的副本。
+    ///
+    /// # Safety
+    ///
+    /// `ptr` 必须来自 [`RwRc::<T>::into_raw`](Self::into_raw)，
+    /// 并且它代表的强引用还没有被 [`from_raw`](Self::from_raw) 或
+    /// [`decrement_strong_count`](Self::decrement_strong_count) 取回。
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        let internal = unsafe { ptr.byte_sub(std::mem::offset_of!(Internal<T>, val)) };
+        let rc = unsafe { Rc::from_raw(internal.cast::<Internal<T>>()) };
+        Self::from_rc(rc, RwState::Hold)
+    }
+
+    /// 为 [`into_raw`](Self::into_raw) 返回的指针增加一个强引用，
+    /// 相当于克隆一个持有状态的副本并转为同一个指针。
+    ///
+    /// 同一个指针交给多个回调时，每个回调各自以
+    /// [`decrement_strong_count`](Self::decrement_strong_count) 或
+    /// [`from_raw`](Self::from_raw) 取回自己的强引用。
+    ///
+    /// # Safety
+    ///
+    /// `ptr` 必须来自 [`RwRc::<T>::into_raw`](Self::into_raw)，
+    /// 并且调用期间共享对象至少还有一个强引用。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let ptr = RwRc::into_raw(RwRc::new(1));
+    /// unsafe { RwRc::increment_strong_count(ptr) };
+    ///
+    /// // 两个回调各自取回一个强引用
+    /// let mut first = unsafe { RwRc::from_raw(ptr) };
+    /// *first.write() += 1;
+    /// drop(first);
+    /// unsafe { RwRc::decrement_strong_count(ptr) };
+    /// ```
+    pub unsafe fn increment_strong_count(ptr: *const T) {
+        let rc = ManuallyDrop::new(unsafe { Self::from_raw(ptr) });
+        std::mem::forget(RwRc::clone(&rc))
+    }
+
+    /// 取回 [`into_raw`](Self::into_raw) 返回的指针代表的一个强引用，
+    /// 相当于以 [`from_raw`](Self::from_raw) 重建副本后丢弃。
+    ///
+    /// 最后一个强引用被取回时执行登记的终结回调并释放共享对象。
+    ///
+    /// # Safety
+    ///
+    /// 与 [`from_raw`](Self::from_raw) 相同。调用后不能再通过这一份强引用使用 `ptr`。
+    pub unsafe fn decrement_strong_count(ptr: *const T) {
+        drop(unsafe { Self::from_raw(ptr) })
+    }
+}
+
+#[test]
+fn test_raw_strong_count() {
+    use std::cell::Cell;
+
+    let mut rc = RwRc::new(vec![1]);
+    let dropped = Rc::new(Cell::new(false));
+    let flag = dropped.clone();
+    rc.on_last_drop(move |_| flag.set(true));
+    let observer = rc.clone();
+    observer.release();
+
+    // 转为指针前释放读写状态
+    rc.write().push(2);
+    let ptr = RwRc::into_raw(rc);
+    assert!(observer.is_writeable());
+    unsafe { RwRc::increment_strong_count(ptr) };
+    unsafe { RwRc::increment_strong_count(ptr) };
+    assert_eq!(Rc::strong_count(&observer.rc), 4);
+
+    let back = unsafe { RwRc::from_raw(ptr) };
+    assert_eq!(*back.read(), [1, 2]);
+    drop((back, observer));
+    unsafe { RwRc::decrement_strong_count(ptr) };
+    assert!(!dropped.get());
+    unsafe { RwRc::decrement_strong_count(ptr) };
+    assert!(dropped.get());
+}
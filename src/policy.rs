@@ -0,0 +1,76 @@
+use std::{
+    fmt,
+    panic::Location,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+/// 违反使用约定时的处理方式。
+///
+/// 使用约定包括：借用存在期间不改变副本的读写状态、不在其他线程上使用副本、
+/// 不通过其他锁域或所有者访问成员，以及 [`RwRc::read_global`](crate::RwRc::read_global)
+/// 等无法失败的方法获取成功。
+/// 文档中因违反这些约定而 panic 的情形都按当前的处理方式处理。
+///
+/// 只支持 panic 和终止进程两种处理方式，且对整个进程生效。
+/// 无法失败的方法的签名不返回错误值，因此没有返回错误值的处理方式，
+/// 需要以错误值处理失败时，应使用对应的 `try_` 方法；
+/// 共享对象也不保存各自的处理方式，以免每个分配多出一个字段。
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum MisusePolicy {
+    /// 以描述违反内容的信息 panic。
+    #[default]
+    Panic = 0,
+    /// 向标准错误输出描述违反内容的信息后终止进程，不展开调用栈。
+    Abort = 1,
+}
+
+/// 当前的处理方式。
+static POLICY: AtomicU8 = AtomicU8::new(MisusePolicy::Panic as u8);
+
+/// 设置整个进程违反使用约定时的处理方式，默认为 [`MisusePolicy::Panic`]。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{MisusePolicy, misuse_policy, set_misuse_policy};
+///
+/// assert_eq!(misuse_policy(), MisusePolicy::Panic);
+/// set_misuse_policy(MisusePolicy::Abort);
+/// assert_eq!(misuse_policy(), MisusePolicy::Abort);
+/// ```
+pub fn set_misuse_policy(policy: MisusePolicy) {
+    POLICY.store(policy as u8, Ordering::Relaxed)
+}
+
+/// 当前违反使用约定时的处理方式。
+pub fn misuse_policy() -> MisusePolicy {
+    match POLICY.load(Ordering::Relaxed) {
+        0 => MisusePolicy::Panic,
+        1 => MisusePolicy::Abort,
+        // 只有 set_misuse_policy 写入，存储的总是某个处理方式的值
+        n => unreachable!("invalid misuse policy {n}"),
+    }
+}
+
+/// 按当前的处理方式报告违反使用约定。
+#[cold]
+#[track_caller]
+pub(super) fn misuse(args: fmt::Arguments) -> ! {
+    match misuse_policy() {
+        MisusePolicy::Panic => panic!("{args}"),
+        MisusePolicy::Abort => {
+            eprintln!("{args} at {}", Location::caller());
+            std::process::abort()
+        }
+    }
+}
+
+#[test]
+fn test_misuse_policy() {
+    set_misuse_policy(MisusePolicy::Panic);
+    assert_eq!(misuse_policy(), MisusePolicy::Panic);
+    let err = std::panic::catch_unwind(|| misuse(format_args!("bad {}", 1))).unwrap_err();
+    assert_eq!(err.downcast_ref::<String>().unwrap(), "bad 1");
+}
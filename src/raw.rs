@@ -0,0 +1,118 @@
+use crate::flag::RwFlag;
+use lock_api::{GuardNoSend, RawRwLock, RawRwLockDowngrade};
+
+/// 基于共享读写状态的 [`RawRwLock`] 实现。
+///
+/// 与 [`RwRc<T>`](crate::RwRc) 使用相同的单线程读写状态语义。
+/// 由于只在单线程中使用，无法获取锁时等待永远不会成功，
+/// 因此阻塞的加锁方法在无法获取锁时会 panic。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::RwLock;
+///
+/// let lock = RwLock::new(42);
+/// {
+///     let r1 = lock.read();
+///     let r2 = lock.read();
+///     assert_eq!(*r1 + *r2, 84);
+///     assert!(lock.try_write().is_none());
+/// }
+/// *lock.write() = 99;
+/// assert_eq!(*lock.read(), 99);
+/// ```
+pub struct RwFlagRaw(RwFlag);
+
+/// 使用 [`RwFlagRaw`] 的 [`lock_api::RwLock`]。
+pub type RwLock<T> = lock_api::RwLock<RwFlagRaw, T>;
+
+unsafe impl RawRwLock for RwFlagRaw {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = Self(RwFlag::new_hold());
+
+    type GuardMarker = GuardNoSend;
+
+    fn lock_shared(&self) {
+        if !self.try_lock_shared() {
+            crate::policy::misuse(format_args!(
+                "lock_shared on a write-locked RwFlagRaw would never succeed"
+            ))
+        }
+    }
+
+    fn try_lock_shared(&self) -> bool {
+        self.0.hold_to_read()
+    }
+
+    unsafe fn unlock_shared(&self) {
+        self.0.read_to_hold()
+    }
+
+    fn lock_exclusive(&self) {
+        if !self.try_lock_exclusive() {
+            crate::policy::misuse(format_args!(
+                "lock_exclusive on a locked RwFlagRaw would never succeed"
+            ))
+        }
+    }
+
+    fn try_lock_exclusive(&self) -> bool {
+        self.0.hold_to_write()
+    }
+
+    unsafe fn unlock_exclusive(&self) {
+        self.0.write_to_hold()
+    }
+
+    fn is_locked(&self) -> bool {
+        !self.0.is_writeable()
+    }
+
+    fn is_locked_exclusive(&self) -> bool {
+        !self.0.is_readable()
+    }
+}
+
+unsafe impl RawRwLockDowngrade for RwFlagRaw {
+    unsafe fn downgrade(&self) {
+        self.0.write_to_read()
+    }
+}
+
+#[test]
+fn test_raw_rwlock() {
+    use lock_api::RwLockWriteGuard;
+
+    let lock = RwLock::new(vec![1]);
+    assert!(!lock.is_locked());
+    {
+        let r = lock.read();
+        assert!(lock.is_locked());
+        assert!(!lock.is_locked_exclusive());
+        assert!(lock.try_read().is_some());
+        assert!(lock.try_write().is_none());
+        assert_eq!(r.len(), 1);
+    }
+    {
+        let mut w = lock.write();
+        w.push(2);
+        assert!(lock.is_locked_exclusive());
+        assert!(lock.try_read().is_none());
+
+        // 写锁降级为读锁
+        let r = RwLockWriteGuard::downgrade(w);
+        assert!(lock.try_read().is_some());
+        assert!(lock.try_write().is_none());
+        assert_eq!(*r, [1, 2]);
+    }
+    assert!(!lock.is_locked());
+}
+
+#[test]
+#[should_panic(expected = "would never succeed")]
+fn test_raw_rwlock_deadlock() {
+    let lock = RwLock::new(0);
+    let _r = lock.read();
+    let _w = lock.write();
+}
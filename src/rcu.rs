@@ -0,0 +1,147 @@
+use crate::{Access, LockError, RwRc};
+use std::rc::Rc;
+
+impl<T: PartialEq> RwRc<T> {
+    /// 获取写状态，共享对象仍然等于 `expected` 时替换为 `new`，返回是否替换。
+    ///
+    /// 比较和替换在同一次写状态下完成，其他副本无法在两者之间修改共享对象。
+    /// 此副本原来的读写状态保持不变。
+    /// 有其他副本持有读写状态时返回错误，`new` 被丢弃。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let mut version = RwRc::new(1);
+    /// assert_eq!(version.compare_and_write(&1, 2), Ok(true));
+    /// assert_eq!(version.compare_and_write(&1, 3), Ok(false));
+    /// assert_eq!(*version.read(), 2);
+    /// ```
+    pub fn compare_and_write(&mut self, expected: &T, new: T) -> Result<bool, LockError> {
+        let mut current = self.try_write().ok_or(LockError::Blocked(Access::Write))?;
+        let matched = *current == *expected;
+        if matched {
+            *current = new
+        }
+        Ok(matched)
+    }
+}
+
+impl<T: Clone> RwRc<T> {
+    /// 在临时读取下克隆共享对象，得到不再参与读写状态的不可变快照。
+    ///
+    /// 克隆完成后立即结束读取，快照不会阻挡之后的写入，之后的写入也不会影响快照。
+    /// 共享对象本身是 [`Rc`] 时，[`snapshot`](RwRc::snapshot) 只克隆指针，开销更小。
+    /// 无法读取时返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let mut scene = RwRc::new(vec![1, 2]);
+    /// let frame = scene.cloned_snapshot().unwrap();
+    /// scene.write().push(3);
+    /// assert_eq!(*frame, [1, 2]);
+    /// ```
+    pub fn cloned_snapshot(&self) -> Option<Rc<T>> {
+        self.try_read().map(|val| Rc::new(T::clone(&val)))
+    }
+}
+
+impl<T> RwRc<Rc<T>> {
+    /// 获取当前发布的值的快照。
+    ///
+    /// 只在克隆 [`Rc`] 期间临时读取，不保留读状态，因此快照不会阻挡之后的发布。
+    /// 无法读取时返回 `None`。
+    pub fn snapshot(&self) -> Option<Rc<T>> {
+        self.try_read().map(|val| Rc::clone(&val))
+    }
+
+    /// 以当前发布的值构造新的值并发布。
+    ///
+    /// 已经取得的快照保持原来的值，此后的 [`snapshot`](Self::snapshot) 读到新的值。
+    ///
+    /// 发布仍然需要写状态：持有读状态的副本借用的是保存 [`Rc`] 的位置本身，
+    /// 替换它会使这些借用失效，因此任何副本持有读写状态时都无法发布。
+    /// 读者必须通过 [`snapshot`](Self::snapshot) 取得快照后释放读状态，不能长期持有读状态，
+    /// 这样发布只需要短暂的写状态，不会被读者阻挡。
+    /// 有其他副本持有读写状态时返回错误，`f` 不会被调用。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    /// use std::rc::Rc;
+    ///
+    /// let mut config = RwRc::new(Rc::new(vec!["a"]));
+    /// let reader = config.clone();
+    /// let old = reader.snapshot().unwrap();
+    /// drop(reader);
+    ///
+    /// config.update_publish(|v| [&v[..], &["b"]].concat()).unwrap();
+    /// assert_eq!(*old, ["a"]);
+    /// assert_eq!(*config.snapshot().unwrap(), ["a", "b"]);
+    /// ```
+    pub fn update_publish(&mut self, f: impl FnOnce(&T) -> T) -> Result<(), LockError> {
+        let mut current = self.try_write().ok_or(LockError::Blocked(Access::Write))?;
+        let next = Rc::new(f(&current));
+        *current = next;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_update_publish() {
+    let mut rc = RwRc::new(Rc::new(1));
+    let reader = rc.clone();
+    reader.release();
+    let snapshots = (0..3)
+        .map(|_| {
+            let old = reader.snapshot().unwrap();
+            rc.update_publish(|v| v + 1).unwrap();
+            old
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(snapshots.iter().map(|s| **s).collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(*reader.snapshot().unwrap(), 4);
+
+    // 持有读状态的副本阻挡发布
+    assert!(reader.try_read_global());
+    assert_eq!(
+        rc.update_publish(|_| unreachable!()),
+        Err(LockError::Blocked(Access::Write))
+    );
+}
+
+#[test]
+fn test_compare_and_write() {
+    let mut rc = RwRc::new(String::from("a"));
+    let reader = rc.clone();
+    assert_eq!(
+        rc.compare_and_write(&"a".into(), "b".into()),
+        Err(LockError::Blocked(Access::Write))
+    );
+    drop(reader);
+    assert_eq!(rc.compare_and_write(&"a".into(), "b".into()), Ok(true));
+    assert_eq!(rc.compare_and_write(&"a".into(), "c".into()), Ok(false));
+    assert_eq!(*rc.read(), "b");
+    assert!(matches!(rc.state.get(), crate::RwState::Read));
+}
+
+#[test]
+fn test_cloned_snapshot() {
+    let mut rc = RwRc::new(String::from("a"));
+    let reader = rc.clone();
+    let frame = reader.cloned_snapshot().unwrap();
+    // 快照不保留读状态
+    reader.release();
+    rc.write().push('b');
+    assert_eq!(*frame, "a");
+
+    assert!(rc.try_write_global());
+    assert!(reader.cloned_snapshot().is_none());
+    rc.release();
+    assert_eq!(*reader.cloned_snapshot().unwrap(), "ab");
+}
@@ -0,0 +1,117 @@
+//! 当前线程上按名字登记的共享对象。
+//!
+//! 登记后在同一线程的任何位置都可以通过名字查找共享对象，不需要层层传递。
+//! [`register`] 保存强引用，共享对象在注销前一直存活；
+//! [`register_weak`] 只保存弱引用，共享对象的其他副本全部释放后查找失败。
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rwrc::{RwRc, registry};
+//!
+//! let physics = RwRc::new(vec![0.0f32; 3]);
+//! registry::register("physics", &physics);
+//!
+//! let mut world = registry::lookup::<Vec<f32>>("physics").unwrap();
+//! drop(physics);
+//! world.write()[0] = 9.8;
+//! // 类型不符时查找失败
+//! assert!(registry::lookup::<String>("physics").is_none());
+//!
+//! let cache = RwRc::new(String::from("cache"));
+//! registry::register_weak("cache", &cache);
+//! assert!(registry::lookup::<String>("cache").is_some());
+//! drop(cache);
+//! assert!(registry::lookup::<String>("cache").is_none());
+//! ```
+
+use crate::{RwRc, RwWeak};
+use std::{any::Any, cell::RefCell, collections::HashMap};
+
+/// 登记的共享对象。
+enum Entry {
+    /// 持有状态的 [`RwRc<T>`]。
+    Strong(Box<dyn Any>),
+    /// [`RwWeak<T>`]。
+    Weak(Box<dyn Any>),
+}
+
+thread_local! {
+    /// 当前线程上登记的共享对象。
+    static REGISTRY: RefCell<HashMap<String, Entry>> = RefCell::new(HashMap::new());
+}
+
+/// 以 `name` 登记 `rc` 的强引用，替换同名的登记。
+pub fn register<T: 'static>(name: impl Into<String>, rc: &RwRc<T>) {
+    let rc = rc.clone();
+    rc.release();
+    insert(name.into(), Entry::Strong(Box::new(rc)))
+}
+
+/// 以 `name` 登记 `rc` 的弱引用，替换同名的登记。
+pub fn register_weak<T: 'static>(name: impl Into<String>, rc: &RwRc<T>) {
+    insert(name.into(), Entry::Weak(Box::new(rc.weak())))
+}
+
+/// 查找以 `name` 登记的共享对象，返回持有状态的副本。
+///
+/// 没有登记、类型不是 `T` 或者弱引用的共享对象已经释放时返回 `None`。
+/// 已经释放的弱引用登记会被移除。
+pub fn lookup<T: 'static>(name: &str) -> Option<RwRc<T>> {
+    REGISTRY.with_borrow_mut(|registry| {
+        let ans = match registry.get(name)? {
+            Entry::Strong(any) => return any.downcast_ref::<RwRc<T>>().cloned(),
+            Entry::Weak(any) => any.downcast_ref::<RwWeak<T>>()?.hold(),
+        };
+        if ans.is_none() {
+            registry.remove(name);
+        }
+        ans
+    })
+}
+
+/// 注销以 `name` 登记的共享对象，返回是否存在这个登记。
+pub fn unregister(name: &str) -> bool {
+    // 登记的副本在借用结束后释放，以免析构时访问登记表
+    let entry = REGISTRY.with_borrow_mut(|registry| registry.remove(name));
+    entry.is_some()
+}
+
+/// 注销当前线程上的所有登记。
+pub fn clear() {
+    let registry = REGISTRY.take();
+    drop(registry)
+}
+
+/// 替换 `name` 的登记，原来的登记在借用结束后释放。
+fn insert(name: String, entry: Entry) {
+    let old = REGISTRY.with_borrow_mut(|registry| registry.insert(name, entry));
+    drop(old)
+}
+
+#[test]
+fn test_registry() {
+    let mut rc = RwRc::new(1);
+    register("a", &rc);
+    register_weak("b", &rc);
+    *rc.write() = 2;
+    rc.release();
+
+    let a = lookup::<i32>("a").unwrap();
+    assert_eq!(*a.read(), 2);
+    assert_eq!(lookup::<i32>("b").unwrap().id(), a.id());
+    assert!(lookup::<u32>("b").is_none());
+
+    // 强引用登记让共享对象保持存活
+    drop((rc, a));
+    assert!(lookup::<i32>("b").is_some());
+    assert!(unregister("a"));
+    assert!(!unregister("a"));
+    assert!(lookup::<i32>("b").is_none());
+    // 已经释放的弱引用登记被移除
+    assert!(!unregister("b"));
+
+    register("c", &RwRc::new(()));
+    clear();
+    assert!(lookup::<()>("c").is_none());
+}
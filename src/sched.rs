@@ -0,0 +1,299 @@
+//! 按声明的访问权限调度任务。
+//!
+//! 每个任务声明需要读取和写入的副本，[`Scheduler`] 只在所有声明的访问权限都能获取时执行任务，
+//! 执行前一次性获取、执行后还原副本原来的读写状态。
+//! 访问同一个共享对象且至少一方写入的任务按提交顺序执行，
+//! 没有冲突的任务可以越过无法执行的任务先执行。
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rwrc::{RwRc, sched::Scheduler};
+//!
+//! let input = RwRc::new(vec![1, 2, 3]);
+//! let sum = RwRc::new(0);
+//! input.release();
+//! sum.release();
+//!
+//! let mut scheduler = Scheduler::new();
+//! scheduler.spawn(input.clone(), sum.clone(), |input, sum| {
+//!     *sum.write() = input.read().iter().sum()
+//! });
+//! scheduler.spawn(sum.clone(), (), |sum, ()| assert_eq!(*sum.read(), 6));
+//! assert_eq!(scheduler.run(), 2);
+//! assert!(scheduler.is_empty());
+//! ```
+
+use crate::{Access, LockOrder, RwRc, RwState, order::Ordered};
+use std::collections::VecDeque;
+
+mod private {
+    use crate::order::Ordered;
+
+    /// [`Deps`](super::Deps) 的实现，防止外部实现。
+    pub trait Sealed {
+        /// 按确定的顺序访问每个副本。
+        fn for_each<'s>(&'s mut self, f: &mut dyn FnMut(&'s mut dyn Ordered));
+    }
+}
+
+/// 任务声明依赖的一组副本。
+///
+/// 由 [`RwRc<T>`]、`()`、[`Vec`] 以及最多四个元素的元组实现，可以嵌套。
+pub trait Deps: private::Sealed {}
+
+impl<T> private::Sealed for RwRc<T> {
+    fn for_each<'s>(&'s mut self, f: &mut dyn FnMut(&'s mut dyn Ordered)) {
+        f(self)
+    }
+}
+
+impl<T> Deps for RwRc<T> {}
+
+impl<D: Deps> private::Sealed for Vec<D> {
+    fn for_each<'s>(&'s mut self, f: &mut dyn FnMut(&'s mut dyn Ordered)) {
+        for deps in self {
+            deps.for_each(f)
+        }
+    }
+}
+
+impl<D: Deps> Deps for Vec<D> {}
+
+macro_rules! impl_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: Deps),*> private::Sealed for ($($name,)*) {
+            #[allow(non_snake_case, unused_variables)]
+            fn for_each<'s>(&'s mut self, f: &mut dyn FnMut(&'s mut dyn Ordered)) {
+                let ($($name,)*) = self;
+                $($name.for_each(f);)*
+            }
+        }
+
+        impl<$($name: Deps),*> Deps for ($($name,)*) {}
+    };
+}
+
+impl_tuple!();
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+
+/// 类型擦除的任务。
+trait Runnable {
+    /// 任务需要的访问权限。
+    fn claims(&mut self) -> Vec<(usize, Access)>;
+    /// 获取所有访问权限并执行任务，无法获取时返回 `false`。
+    fn try_run(&mut self) -> bool;
+}
+
+/// 一个任务和它声明的副本。
+struct Task<R, W, F> {
+    /// 需要读取的副本。
+    reads: R,
+    /// 需要写入的副本。
+    writes: W,
+    /// 任务体，执行后为 `None`。
+    body: Option<F>,
+}
+
+impl<R: Deps, W: Deps, F: FnOnce(&R, &mut W)> Runnable for Task<R, W, F> {
+    fn claims(&mut self) -> Vec<(usize, Access)> {
+        let mut claims = Vec::new();
+        self.reads
+            .for_each(&mut |h| claims.push((h.id(), Access::Read)));
+        self.writes
+            .for_each(&mut |h| claims.push((h.id(), Access::Write)));
+        claims
+    }
+
+    fn try_run(&mut self) -> bool {
+        let mut states = Vec::new();
+        let mut order = LockOrder::new();
+        for (deps, access) in [
+            (&mut self.reads as &mut dyn private::Sealed, Access::Read),
+            (&mut self.writes, Access::Write),
+        ] {
+            deps.for_each(&mut |h| {
+                states.push(h.state());
+                order = std::mem::take(&mut order).push(access, h)
+            })
+        }
+        if order.acquire().is_err() {
+            return false;
+        }
+        let body = self.body.take().unwrap();
+        let guard = Restore {
+            reads: &mut self.reads,
+            writes: &mut self.writes,
+            states,
+        };
+        body(guard.reads, guard.writes);
+        true
+    }
+}
+
+/// 执行中的任务的副本和获取前的读写状态，释放时还原，任务体 panic 时也会还原。
+struct Restore<'a, R: Deps, W: Deps> {
+    reads: &'a mut R,
+    writes: &'a mut W,
+    states: Vec<RwState>,
+}
+
+impl<R: Deps, W: Deps> Drop for Restore<'_, R, W> {
+    fn drop(&mut self) {
+        let mut states = std::mem::take(&mut self.states).into_iter();
+        let mut restore = |h: &mut dyn Ordered| h.restore(states.next().unwrap());
+        self.reads.for_each(&mut restore);
+        self.writes.for_each(&mut restore);
+    }
+}
+
+/// 按声明的访问权限执行任务的协作式调度器。
+///
+/// 任务在 [`run`](Self::run) 中同步执行，执行时声明读取的副本处于读状态、
+/// 声明写入的副本处于写状态。同一个任务不能通过不同的副本同时读写同一个共享对象，
+/// 否则写状态永远无法获取。
+#[derive(Default)]
+pub struct Scheduler<'a> {
+    /// 尚未执行的任务，按提交顺序排列。
+    tasks: VecDeque<Box<dyn Runnable + 'a>>,
+}
+
+impl<'a> Scheduler<'a> {
+    /// 创建空的调度器。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 提交一个任务。
+    ///
+    /// 任务执行时以声明读取的副本 `reads` 和声明写入的副本 `writes` 调用 `body`，
+    /// 副本在任务执行后随任务一起释放。
+    pub fn spawn<R: Deps + 'a, W: Deps + 'a>(
+        &mut self,
+        reads: R,
+        writes: W,
+        body: impl FnOnce(&R, &mut W) + 'a,
+    ) {
+        self.tasks.push_back(Box::new(Task {
+            reads,
+            writes,
+            body: Some(body),
+        }))
+    }
+
+    /// 尚未执行的任务数量。
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// 判断是否所有任务都已经执行。
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// 按提交顺序执行所有可以执行的任务，直到没有任务可以执行，返回执行的任务数量。
+    ///
+    /// 任务无法获取声明的访问权限时跳过它，之后与它冲突的任务也被跳过；
+    /// 跳过的任务保留在调度器中，在其他副本释放读写状态后再次调用时执行。
+    /// 任务体 panic 时副本仍然还原到原来的读写状态，panic 的任务从调度器中移除。
+    pub fn run(&mut self) -> usize {
+        let mut total = 0;
+        loop {
+            let ran = self.run_once();
+            if ran == 0 {
+                break total;
+            }
+            total += ran
+        }
+    }
+
+    /// 依次尝试每个任务一次，返回执行的任务数量。
+    fn run_once(&mut self) -> usize {
+        let mut ran = 0;
+        let mut blocked = Vec::<(usize, Access)>::new();
+        let mut i = 0;
+        while i < self.tasks.len() {
+            let claims = self.tasks[i].claims();
+            let conflicted = claims.iter().any(|&(id, access)| {
+                blocked.iter().any(|&(other, prev)| {
+                    other == id && matches!((access, prev), (Access::Write, _) | (_, Access::Write))
+                })
+            });
+            if !conflicted {
+                // 执行期间任务不在调度器中，任务体 panic 时随之释放
+                let mut task = self.tasks.remove(i).unwrap();
+                if task.try_run() {
+                    ran += 1;
+                    continue;
+                }
+                self.tasks.insert(i, task)
+            }
+            blocked.extend(claims);
+            i += 1
+        }
+        ran
+    }
+}
+
+#[test]
+fn test_scheduler() {
+    use std::{cell::RefCell, rc::Rc};
+
+    let a = RwRc::new(1);
+    let b = RwRc::new(2);
+    a.release();
+    b.release();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let blocker = a.clone();
+    assert!(blocker.try_read_global());
+
+    let mut scheduler = Scheduler::new();
+    let l = log.clone();
+    scheduler.spawn((), a.clone(), move |(), a| {
+        *a.write() = 10;
+        l.borrow_mut().push("write a")
+    });
+    // 与无法执行的任务冲突，不能越过它
+    let l = log.clone();
+    scheduler.spawn(a.clone(), (), move |a, ()| {
+        assert_eq!(*a.read(), 10);
+        l.borrow_mut().push("read a")
+    });
+    // 不冲突的任务先执行，执行时处于声明的状态
+    let l = log.clone();
+    scheduler.spawn(vec![b.clone(), b.clone()], (), move |deps, ()| {
+        assert_eq!(deps[0].state(), crate::RwState::Read);
+        l.borrow_mut().push("read b")
+    });
+    assert_eq!(scheduler.run(), 1);
+    assert_eq!(*log.borrow(), ["read b"]);
+    assert_eq!(scheduler.len(), 2);
+
+    // 阻挡的读者释放后按提交顺序执行，副本回到原来的状态
+    blocker.release();
+    assert_eq!(scheduler.run(), 2);
+    assert_eq!(*log.borrow(), ["read b", "write a", "read a"]);
+    assert!(scheduler.is_empty());
+    assert_eq!(a.state(), crate::RwState::Hold);
+    assert!(b.try_write_global());
+}
+
+#[test]
+fn test_scheduler_panic() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    let a = RwRc::new(0);
+    a.release();
+    let mut scheduler = Scheduler::new();
+    scheduler.spawn((), a.clone(), |(), _| panic!("task failed"));
+    scheduler.spawn(a.clone(), (), |a, ()| assert_eq!(*a.read(), 0));
+    assert!(catch_unwind(AssertUnwindSafe(|| scheduler.run())).is_err());
+
+    // panic 的任务已经移除，它的副本回到持有状态
+    assert_eq!(scheduler.len(), 1);
+    assert_eq!(scheduler.run(), 1);
+    assert!(a.try_write_global());
+}
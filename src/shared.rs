@@ -0,0 +1,120 @@
+use crate::{LocalMut, LocalRef, RwRc};
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+/// 可共享读写的智能指针。
+///
+/// 抽象 [`RwRc<T>`] 和 `Rc<RefCell<T>>` 的借用接口，使库代码可以同时支持两种指针。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, SharedRw};
+/// use std::{cell::RefCell, rc::Rc};
+///
+/// fn increase(p: &mut impl SharedRw<Target = i32>) -> bool {
+///     match p.try_write() {
+///         Some(mut val) => {
+///             *val += 1;
+///             true
+///         }
+///         None => false,
+///     }
+/// }
+///
+/// let mut a = RwRc::new(1);
+/// let mut b = Rc::new(RefCell::new(1));
+/// assert!(increase(&mut a));
+/// assert!(increase(&mut b));
+/// assert_eq!(*a.read(), 2);
+/// assert_eq!(*b.borrow(), 2);
+/// ```
+pub trait SharedRw {
+    /// 共享的对象类型。
+    type Target;
+
+    /// 只读借用。
+    type Ref<'a>: Deref<Target = Self::Target>
+    where
+        Self: 'a;
+
+    /// 可变借用。
+    type Mut<'a>: DerefMut<Target = Self::Target>
+    where
+        Self: 'a;
+
+    /// 尝试获取只读借用，无法获取时返回 `None`。
+    fn try_read(&self) -> Option<Self::Ref<'_>>;
+
+    /// 尝试获取可变借用，无法获取时返回 `None`。
+    fn try_write(&mut self) -> Option<Self::Mut<'_>>;
+}
+
+impl<T> SharedRw for RwRc<T> {
+    type Target = T;
+    type Ref<'a>
+        = LocalRef<'a, T>
+    where
+        T: 'a;
+    type Mut<'a>
+        = LocalMut<'a, T>
+    where
+        T: 'a;
+
+    fn try_read(&self) -> Option<Self::Ref<'_>> {
+        RwRc::try_read(self)
+    }
+
+    fn try_write(&mut self) -> Option<Self::Mut<'_>> {
+        RwRc::try_write(self)
+    }
+}
+
+impl<T> SharedRw for Rc<RefCell<T>> {
+    type Target = T;
+    type Ref<'a>
+        = Ref<'a, T>
+    where
+        T: 'a;
+    type Mut<'a>
+        = RefMut<'a, T>
+    where
+        T: 'a;
+
+    fn try_read(&self) -> Option<Self::Ref<'_>> {
+        self.try_borrow().ok()
+    }
+
+    fn try_write(&mut self) -> Option<Self::Mut<'_>> {
+        self.try_borrow_mut().ok()
+    }
+}
+
+#[test]
+fn test_shared_rw() {
+    fn swap_first<P: SharedRw<Target = Vec<i32>>>(a: &mut P, b: &P) -> Option<()> {
+        let b = b.try_read()?;
+        let mut a = a.try_write()?;
+        a[0] = b[0];
+        Some(())
+    }
+
+    let mut a = RwRc::new(vec![1]);
+    let b = RwRc::new(vec![2]);
+    assert!(swap_first(&mut a, &b).is_some());
+    assert_eq!(*a.read(), [2]);
+
+    // 同一个共享对象无法同时读写
+    let mut c = a.clone();
+    assert!(swap_first(&mut c, &a).is_none());
+
+    let mut a = Rc::new(RefCell::new(vec![1]));
+    let b = Rc::new(RefCell::new(vec![2]));
+    assert!(swap_first(&mut a, &b).is_some());
+    assert_eq!(*a.borrow(), [2]);
+    let mut c = a.clone();
+    assert!(swap_first(&mut c, &a).is_none());
+}
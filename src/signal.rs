@@ -0,0 +1,680 @@
+//! 响应式信号。
+//!
+//! [`Signal<T>`] 包装 [`RwRc<T>`]，在 [`track`] 的作用域内读取信号会被记录为依赖，
+//! 通过信号写入的修改在释放可变借用时提交，并通知订阅者。
+//! [`Computed<T>`] 缓存由信号计算出的值，并在依赖的信号修改后重新计算。
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rwrc::signal::{Signal, track};
+//! use std::{cell::Cell, rc::Rc};
+//!
+//! let mut width = Signal::new(2);
+//! let height = Signal::new(3);
+//!
+//! let (area, deps) = track(|| *width.read() * *height.read());
+//! assert_eq!(area, 6);
+//!
+//! let dirty = Rc::new(Cell::new(false));
+//! let flag = dirty.clone();
+//! let _sub = deps.subscribe(move || flag.set(true));
+//!
+//! width.set(4);
+//! assert!(dirty.get());
+//! assert!(deps.is_changed());
+//! ```
+
+use crate::{Access, LocalMut, LocalRef, RwRc};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    rc::{Rc, Weak},
+};
+
+/// 可追踪读取、提交时通知订阅者的共享对象。
+///
+/// 克隆的信号共享同一个对象，读写状态语义与 [`RwRc<T>`] 相同，新的信号初始为持有状态。
+pub struct Signal<T> {
+    /// 共享对象。
+    rc: RwRc<T>,
+    /// 信号的版本和订阅者。
+    node: Rc<SignalNode>,
+    /// 接收修改前后的值的订阅者。
+    diffs: Rc<DiffSubscribers<T>>,
+}
+
+/// 接收修改前后的值的回调。
+type DiffFn<T> = dyn Fn(&T, &T);
+
+/// 复制修改前的值的函数。
+type Snapshot<T> = fn(&T) -> T;
+
+/// 接收修改前后的值的订阅者。
+struct DiffSubscribers<T> {
+    /// 复制修改前的值，第一次订阅时设置。
+    snapshot: Cell<Option<Snapshot<T>>>,
+    /// 订阅者，订阅被丢弃后自动失效。
+    subscribers: RefCell<Vec<Weak<DiffFn<T>>>>,
+}
+
+/// 信号共享的版本和订阅者。
+pub(crate) struct SignalNode {
+    /// 每次提交修改后递增。
+    version: Cell<u64>,
+    /// 订阅者，订阅被丢弃后自动失效。
+    subscribers: RefCell<Vec<Weak<dyn Fn()>>>,
+}
+
+/// 通过信号写入的可变借用，释放时提交修改。
+pub struct SignalMut<'w, T> {
+    /// 共享对象的可变借用。
+    guard: ManuallyDrop<LocalMut<'w, T>>,
+    /// 提交时通知的信号。
+    node: &'w Rc<SignalNode>,
+    /// 提交时接收修改前后的值的订阅者。
+    diffs: &'w DiffSubscribers<T>,
+    /// 有差异订阅者时，修改前的值。
+    old: Option<T>,
+}
+
+/// 一次追踪记录的依赖，由 [`track`] 返回。
+pub struct Tracked {
+    /// 依赖的信号及读取时的版本。
+    deps: Vec<(Rc<SignalNode>, u64)>,
+}
+
+/// 由信号计算出的缓存值。
+///
+/// 计算时读取的信号被记录为依赖，依赖的信号提交修改后，下一次 [`get`](Self::get) 重新计算。
+/// 在追踪作用域中读取缓存值相当于读取它依赖的所有信号，因此缓存值可以依赖其他缓存值。
+/// 只有通过 [`Signal`] 读取的值会被追踪，直接读取 [`RwRc`] 不会触发重新计算。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::signal::{Computed, Signal};
+///
+/// let mut items = Signal::new(vec![1, 2, 3]);
+/// let source = items.clone();
+/// let total = Computed::new(move || source.read().iter().sum::<i32>());
+/// assert_eq!(*total.get(), 6);
+///
+/// items.write().push(4);
+/// assert_eq!(*total.get(), 10);
+/// ```
+pub struct Computed<T> {
+    /// 计算函数。
+    f: Box<dyn Fn() -> T>,
+    /// 缓存的值和计算时记录的依赖。
+    cache: RefCell<Option<(Rc<T>, Tracked)>>,
+}
+
+/// 订阅，丢弃时取消订阅。
+#[must_use = "dropping a Subscription unsubscribes immediately"]
+pub struct Subscription(#[allow(dead_code)] Box<dyn Any>);
+
+/// 两个信号之间的双向绑定，由 [`bind`] 创建，丢弃时解除绑定。
+#[must_use = "dropping a Binding unbinds immediately"]
+pub struct Binding {
+    /// 两个方向的订阅。
+    _subscriptions: [Subscription; 2],
+    /// 绑定是否存活，推迟的同步在绑定解除后不再执行。
+    _alive: Rc<()>,
+}
+
+thread_local! {
+    /// 嵌套的追踪作用域，每层记录读取的信号。
+    static SCOPES: RefCell<Vec<Vec<Rc<SignalNode>>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 执行 `f`，记录其中读取的所有信号。
+///
+/// 追踪作用域可以嵌套，内层作用域中的读取只记录到内层。
+pub fn track<R>(f: impl FnOnce() -> R) -> (R, Tracked) {
+    SCOPES.with_borrow_mut(|scopes| scopes.push(Vec::new()));
+    struct Pop;
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            SCOPES.with_borrow_mut(|scopes| scopes.pop());
+        }
+    }
+    let pop = Pop;
+    let ans = f();
+    let nodes = SCOPES.with_borrow_mut(|scopes| scopes.last_mut().map(std::mem::take));
+    drop(pop);
+    let deps = nodes
+        .unwrap_or_default()
+        .into_iter()
+        .map(|node| {
+            let version = node.version.get();
+            (node, version)
+        })
+        .collect();
+    (ans, Tracked { deps })
+}
+
+/// 双向绑定两个信号。
+///
+/// 任何一个信号提交修改后，以对应的转换函数计算另一个信号的值并提交。
+/// 同步引起的提交不会再反向同步。
+/// 另一侧被其他副本锁定时，同步推迟到它可以获取写状态时执行。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::signal::{Signal, bind};
+///
+/// let mut celsius = Signal::new(0.0);
+/// let mut fahrenheit = Signal::new(32.0);
+/// let _binding = bind(&celsius, &fahrenheit, |c| c * 1.8 + 32.0, |f| (f - 32.0) / 1.8);
+///
+/// celsius.set(100.0);
+/// assert_eq!(*fahrenheit.read(), 212.0);
+/// fahrenheit.set(32.0);
+/// assert_eq!(*celsius.read(), 0.0);
+/// ```
+pub fn bind<T: 'static, U: 'static>(
+    a: &Signal<T>,
+    b: &Signal<U>,
+    a_to_b: impl Fn(&T) -> U + 'static,
+    b_to_a: impl Fn(&U) -> T + 'static,
+) -> Binding {
+    let syncing = Rc::new(Cell::new(false));
+    let alive = Rc::new(());
+    let forward = sync(a, b, a_to_b, &syncing, &alive);
+    let backward = sync(b, a, b_to_a, &syncing, &alive);
+    Binding {
+        _subscriptions: [a.subscribe(forward), b.subscribe(backward)],
+        _alive: alive,
+    }
+}
+
+/// 创建从 `src` 同步到 `dst` 的订阅者。
+fn sync<S: 'static, D: 'static>(
+    src: &Signal<S>,
+    dst: &Signal<D>,
+    f: impl Fn(&S) -> D + 'static,
+    syncing: &Rc<Cell<bool>>,
+    alive: &Rc<()>,
+) -> impl Fn() + 'static {
+    let mut src = src.clone();
+    let mut dst = dst.clone();
+    src.release();
+    dst.release();
+    let f = Rc::new(f);
+    let syncing = syncing.clone();
+    let alive = Rc::downgrade(alive);
+    move || {
+        if syncing.get() || try_sync(&src, &mut dst.clone(), &*f, &syncing) {
+            return;
+        }
+        // 另一侧被锁定，等到可以写入时以那时的值同步
+        let src = src.clone();
+        let mut target = dst.clone();
+        let f = f.clone();
+        let syncing = syncing.clone();
+        let alive = alive.clone();
+        dst.rc.on_releasable(Access::Write, move |_| {
+            if alive.strong_count() > 0 {
+                try_sync(&src, &mut target, &*f, &syncing);
+            }
+        })
+    }
+}
+
+/// 以 `src` 当前的值计算 `dst` 的值并通过信号提交，`dst` 无法写入时返回 `false`。
+///
+/// `src` 无法读取说明它正在被修改，那次修改提交时会再次同步，因此视为已经同步。
+fn try_sync<S, D>(
+    src: &Signal<S>,
+    dst: &mut Signal<D>,
+    f: &dyn Fn(&S) -> D,
+    syncing: &Cell<bool>,
+) -> bool {
+    let Some(val) = src.try_read().map(|v| f(&v)) else {
+        return true;
+    };
+    let Some(mut guard) = dst.try_write() else {
+        return false;
+    };
+    syncing.set(true);
+    *guard = val;
+    drop(guard);
+    syncing.set(false);
+    true
+}
+
+impl SignalNode {
+    fn new() -> Rc<Self> {
+        Rc::new(Self {
+            version: Cell::new(0),
+            subscribers: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// 记录到当前的追踪作用域。
+    fn observe(self: &Rc<Self>) {
+        SCOPES.with_borrow_mut(|scopes| {
+            if let Some(scope) = scopes.last_mut()
+                && !scope.iter().any(|n| Rc::ptr_eq(n, self))
+            {
+                scope.push(self.clone())
+            }
+        })
+    }
+
+    /// 提交一次修改并通知订阅者。
+    fn commit(&self) {
+        self.version.set(self.version.get() + 1);
+        let subscribers = {
+            let mut subscribers = self.subscribers.borrow_mut();
+            subscribers.retain(|s| s.strong_count() > 0);
+            subscribers
+                .iter()
+                .filter_map(Weak::upgrade)
+                .collect::<Vec<_>>()
+        };
+        for f in subscribers {
+            f()
+        }
+    }
+
+    fn subscribe(&self, f: &Rc<dyn Fn()>) {
+        self.subscribers.borrow_mut().push(Rc::downgrade(f))
+    }
+}
+
+impl<T> Clone for Signal<T> {
+    /// 只有当源信号在读状态时，克隆的信号才会设置读状态，否则设置为持有状态。
+    fn clone(&self) -> Self {
+        Self {
+            rc: self.rc.clone(),
+            node: self.node.clone(),
+            diffs: self.diffs.clone(),
+        }
+    }
+}
+
+impl<T> Signal<T> {
+    /// 创建信号，初始为持有状态。
+    pub fn new(val: T) -> Self {
+        let rc = RwRc::new(val);
+        rc.release();
+        Self {
+            rc,
+            node: SignalNode::new(),
+            diffs: Rc::new(DiffSubscribers {
+                snapshot: Cell::new(None),
+                subscribers: RefCell::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// 信号包装的副本，通过它的读取不会被追踪。
+    pub fn as_rwrc(&self) -> &RwRc<T> {
+        &self.rc
+    }
+
+    /// 已经提交的修改次数。
+    pub fn version(&self) -> u64 {
+        self.node.version.get()
+    }
+
+    /// 尝试读取信号，并记录到当前的追踪作用域。
+    #[track_caller]
+    pub fn try_read(&self) -> Option<LocalRef<'_, T>> {
+        self.node.observe();
+        self.rc.try_read()
+    }
+
+    /// 读取信号，并记录到当前的追踪作用域。
+    ///
+    /// # Panic
+    ///
+    /// 无法获取读取权限时 panic。
+    #[track_caller]
+    pub fn read(&self) -> LocalRef<'_, T> {
+        self.try_read().unwrap()
+    }
+
+    /// 尝试可变借用信号，借用释放时提交修改。
+    #[track_caller]
+    pub fn try_write(&mut self) -> Option<SignalMut<'_, T>> {
+        let guard = self.rc.try_write()?;
+        let old = self.diffs.snapshot(&guard);
+        Some(SignalMut {
+            guard: ManuallyDrop::new(guard),
+            node: &self.node,
+            diffs: &self.diffs,
+            old,
+        })
+    }
+
+    /// 可变借用信号，借用释放时提交修改。
+    ///
+    /// # Panic
+    ///
+    /// 无法获取写入权限时 panic。
+    #[track_caller]
+    pub fn write(&mut self) -> SignalMut<'_, T> {
+        self.try_write().unwrap()
+    }
+
+    /// 以 `val` 替换信号的值并提交。
+    #[track_caller]
+    pub fn set(&mut self, val: T) {
+        *self.write() = val
+    }
+
+    /// 订阅信号的每次提交。
+    pub fn subscribe(&self, f: impl Fn() + 'static) -> Subscription {
+        let f: Rc<dyn Fn()> = Rc::new(f);
+        self.node.subscribe(&f);
+        Subscription(Box::new(f))
+    }
+
+    /// 释放读写状态。
+    pub fn release(&mut self) {
+        self.rc.release()
+    }
+}
+
+impl<T: Clone + 'static> Signal<T> {
+    /// 订阅信号的每次提交，接收修改前后的值。
+    ///
+    /// 存在差异订阅者时，每次可变借用信号都会先复制一份修改前的值。
+    /// 差异订阅者在普通订阅者之后调用，调用期间信号处于读状态。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::signal::Signal;
+    /// use std::{cell::RefCell, rc::Rc};
+    ///
+    /// let mut items = Signal::new(vec![1]);
+    /// let log = Rc::new(RefCell::new(Vec::new()));
+    /// let sink = log.clone();
+    /// let _sub = items.subscribe_diff(move |old: &Vec<i32>, new: &Vec<i32>| {
+    ///     sink.borrow_mut().push((old.len(), new.len()))
+    /// });
+    ///
+    /// items.write().push(2);
+    /// assert_eq!(*log.borrow(), [(1, 2)]);
+    /// ```
+    pub fn subscribe_diff(&self, f: impl Fn(&T, &T) + 'static) -> Subscription {
+        let f: Rc<DiffFn<T>> = Rc::new(f);
+        self.diffs.snapshot.set(Some(T::clone));
+        self.diffs.subscribers.borrow_mut().push(Rc::downgrade(&f));
+        Subscription(Box::new(f))
+    }
+}
+
+impl<T> DiffSubscribers<T> {
+    /// 有存活的订阅者时复制修改前的值。
+    fn snapshot(&self, val: &T) -> Option<T> {
+        let clone = self.snapshot.get()?;
+        let mut subscribers = self.subscribers.borrow_mut();
+        subscribers.retain(|s| s.strong_count() > 0);
+        if subscribers.is_empty() {
+            None
+        } else {
+            Some(clone(val))
+        }
+    }
+
+    /// 以修改前后的值调用存活的订阅者。
+    fn notify(&self, old: &T, rc: &RwRc<T>) {
+        let Some(new) = rc.try_read() else { return };
+        let subscribers = self
+            .subscribers
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>();
+        for f in subscribers {
+            f(old, &new)
+        }
+    }
+}
+
+impl<T> Deref for SignalMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for SignalMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for SignalMut<'_, T> {
+    /// 先释放可变借用，使订阅者可以读取新的值，再通知订阅者。
+    fn drop(&mut self) {
+        let guard = unsafe { ManuallyDrop::take(&mut self.guard) };
+        let rc = LocalMut::leak(guard);
+        rc.drop_mut();
+        self.node.commit();
+        if let Some(old) = self.old.take() {
+            self.diffs.notify(&old, rc)
+        }
+    }
+}
+
+impl<T> Computed<T> {
+    /// 以计算函数创建缓存值，第一次读取时计算。
+    pub fn new(f: impl Fn() -> T + 'static) -> Self {
+        Self {
+            f: Box::new(f),
+            cache: RefCell::new(None),
+        }
+    }
+
+    /// 读取缓存值，依赖的信号有修改时重新计算。
+    pub fn get(&self) -> Rc<T> {
+        let fresh = matches!(&*self.cache.borrow(), Some((_, deps)) if !deps.is_changed());
+        if !fresh {
+            let (val, deps) = track(&self.f);
+            *self.cache.borrow_mut() = Some((Rc::new(val), deps))
+        }
+        let cache = self.cache.borrow();
+        let (val, deps) = cache.as_ref().unwrap();
+        for (node, _) in &deps.deps {
+            node.observe()
+        }
+        val.clone()
+    }
+
+    /// 判断缓存值是否需要重新计算。
+    pub fn is_dirty(&self) -> bool {
+        self.cache
+            .borrow()
+            .as_ref()
+            .is_none_or(|(_, deps)| deps.is_changed())
+    }
+}
+
+impl Tracked {
+    /// 判断追踪后是否有依赖的信号提交了修改。
+    pub fn is_changed(&self) -> bool {
+        self.deps.iter().any(|(n, v)| n.version.get() != *v)
+    }
+
+    /// 依赖的信号数量。
+    pub fn len(&self) -> usize {
+        self.deps.len()
+    }
+
+    /// 判断是否没有依赖任何信号。
+    pub fn is_empty(&self) -> bool {
+        self.deps.is_empty()
+    }
+
+    /// 订阅所有依赖的信号的提交。
+    pub fn subscribe(&self, f: impl Fn() + 'static) -> Subscription {
+        let f: Rc<dyn Fn()> = Rc::new(f);
+        for (node, _) in &self.deps {
+            node.subscribe(&f)
+        }
+        Subscription(Box::new(f))
+    }
+}
+
+#[test]
+fn test_signal() {
+    let mut a = Signal::new(1);
+    let b = Signal::new(String::from("b"));
+    let untracked = Signal::new(0);
+
+    let ((), deps) = track(|| {
+        // 重复读取只记录一次
+        let _ = *a.read();
+        let _ = *a.read();
+        let _ = b.read().len();
+        // 内层作用域的读取只记录到内层
+        let (_, inner) = track(|| *untracked.read());
+        assert_eq!(inner.len(), 1);
+        let _ = *untracked.as_rwrc().read();
+    });
+    assert_eq!(deps.len(), 2);
+    assert!(!deps.is_changed());
+
+    let count = Rc::new(Cell::new(0));
+    let c = count.clone();
+    let observer = a.clone();
+    let sub = deps.subscribe(move || {
+        // 订阅者被通知时可以读取新的值
+        c.set(c.get() + *observer.read())
+    });
+    a.set(10);
+    assert_eq!(a.version(), 1);
+    assert_eq!(count.get(), 10);
+    assert!(deps.is_changed());
+
+    drop(sub);
+    *a.write() += 1;
+    assert_eq!(count.get(), 10);
+}
+
+#[test]
+fn test_computed() {
+    let mut a = Signal::new(1);
+    let mut b = Signal::new(2);
+    let runs = Rc::new(Cell::new(0));
+
+    let (x, y, r) = (a.clone(), b.clone(), runs.clone());
+    let sum = Rc::new(Computed::new(move || {
+        r.set(r.get() + 1);
+        *x.read() + *y.read()
+    }));
+    let inner = sum.clone();
+    let doubled = Computed::new(move || *inner.get() * 2);
+
+    assert!(doubled.is_dirty());
+    assert_eq!(*doubled.get(), 6);
+    assert_eq!(*doubled.get(), 6);
+    assert_eq!(runs.get(), 1);
+
+    // 依赖的信号修改后重新计算，链上的缓存值都会更新
+    b.set(5);
+    assert!(doubled.is_dirty());
+    assert_eq!(*doubled.get(), 12);
+    assert_eq!(runs.get(), 2);
+    assert!(!sum.is_dirty());
+
+    a.set(0);
+    assert_eq!(*sum.get(), 5);
+    assert_eq!(*doubled.get(), 10);
+    assert_eq!(runs.get(), 3);
+}
+
+#[test]
+fn test_bind() {
+    let mut model = Signal::new(1);
+    let mut view = Signal::new(String::from("1"));
+    let commits = Rc::new(Cell::new(0));
+    let c = commits.clone();
+    let _count = model.subscribe(move || c.set(c.get() + 1));
+
+    let binding = bind(&model, &view, |n| n.to_string(), |s| s.parse().unwrap());
+    model.set(2);
+    assert_eq!(*view.read(), "2");
+    // 同步引起的提交不会反向同步
+    assert_eq!(commits.get(), 1);
+    view.set(String::from("3"));
+    assert_eq!(*model.read(), 3);
+    assert_eq!(commits.get(), 2);
+
+    // 另一侧被锁定时推迟同步
+    let mut reader = view.clone();
+    assert!(reader.as_rwrc().try_read().is_some());
+    let guard = reader.read();
+    model.set(4);
+    assert_eq!(*guard, "3");
+    drop(guard);
+    assert_eq!(*view.read(), "4");
+    reader.release();
+
+    drop(binding);
+    model.set(5);
+    assert_eq!(*view.read(), "4");
+}
+
+#[test]
+fn test_bind_deferred() {
+    let mut model = Signal::new(1);
+    let view = Signal::new(String::from("1"));
+    let diffs = Rc::new(RefCell::new(Vec::new()));
+    let sink = diffs.clone();
+    let _diff = view.subscribe_diff(move |old: &String, new: &String| {
+        sink.borrow_mut().push((old.clone(), new.clone()))
+    });
+    let _binding = bind(&model, &view, |n| n.to_string(), |s| s.parse().unwrap());
+
+    // 推迟期间另一侧被写入，推迟的同步以那时的值同步，不会覆盖新的值
+    let mut writer = view.clone();
+    assert!(writer.as_rwrc().try_read_global());
+    model.set(2);
+    assert_eq!(*writer.read(), "1");
+    writer.set(String::from("7"));
+    assert_eq!(*model.read(), 7);
+    writer.release();
+    assert_eq!(*view.read(), "7");
+    assert_eq!(*model.read(), 7);
+
+    // 推迟的同步通过信号提交，差异订阅者也会收到
+    let reader = view.clone();
+    assert!(reader.as_rwrc().try_read_global());
+    model.set(8);
+    drop(reader);
+    assert_eq!(*view.read(), "8");
+    assert_eq!(
+        diffs.borrow().last().unwrap(),
+        &(String::from("7"), String::from("8"))
+    );
+}
+
+#[test]
+fn test_subscribe_diff() {
+    let mut signal = Signal::new(1);
+    let diffs = Rc::new(RefCell::new(Vec::new()));
+    let sink = diffs.clone();
+    let sub = signal.subscribe_diff(move |old, new| sink.borrow_mut().push((*old, *new)));
+
+    signal.set(2);
+    *signal.write() += 3;
+    assert_eq!(*diffs.borrow(), [(1, 2), (2, 5)]);
+
+    // 取消订阅后不再复制修改前的值
+    drop(sub);
+    let w = signal.write();
+    assert!(w.old.is_none());
+    drop(w);
+    assert_eq!(diffs.borrow().len(), 2);
+}
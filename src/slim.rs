@@ -0,0 +1,211 @@
+use crate::{
+    RwRc,
+    flag::{LocalState, RwFlag},
+};
+use std::{cell::Cell, marker::PhantomData, ptr::NonNull};
+
+/// 不支持弱引用的精简版 [`RwRc<T>`]。
+///
+/// 共享对象只保存一个引用计数和共享读写状态，没有弱引用计数、读写偏好策略和等待回调，
+/// 分配更小，丢弃时也不需要检查弱引用。
+/// 副本的读写状态语义与 [`RwRc<T>`] 相同，但只能在读状态或写状态下借用共享对象。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::SlimRwRc;
+///
+/// let mut a = SlimRwRc::new(1);
+/// let mut b = a.clone();
+/// assert_eq!(*a.try_read().unwrap(), 1);
+///
+/// // 两个副本都持有读状态，无法写入
+/// assert!(a.try_write().is_none());
+/// b.release();
+/// *a.try_write().unwrap() = 2;
+/// ```
+pub struct SlimRwRc<T> {
+    /// 共享的对象和状态。
+    ptr: NonNull<SlimInternal<T>>,
+    /// 此副本占用的读写状态。
+    state: LocalState,
+    _phantom: PhantomData<SlimInternal<T>>,
+}
+
+/// 精简版副本共享的对象和状态。
+struct SlimInternal<T> {
+    /// 引用计数。
+    count: Cell<usize>,
+    /// 共享读写状态。
+    flag: RwFlag,
+    /// 共享对象。
+    val: Cell<T>,
+}
+
+impl<T> From<T> for SlimRwRc<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Clone for SlimRwRc<T> {
+    /// 只有当源对象在读状态时，克隆的对象才会设置读状态，否则设置为持有状态。
+    fn clone(&self) -> Self {
+        let internal = self.internal();
+        internal.count.set(internal.count.get() + 1);
+        Self {
+            ptr: self.ptr,
+            state: self.state.clone_with(&internal.flag),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for SlimRwRc<T> {
+    fn drop(&mut self) {
+        self.release();
+        let internal = self.internal();
+        let count = internal.count.get() - 1;
+        internal.count.set(count);
+        if count == 0 {
+            // 最后一个副本，没有其他引用
+            drop(unsafe { Box::from_raw(self.ptr.as_ptr()) })
+        }
+    }
+}
+
+impl<T> SlimRwRc<T> {
+    /// 从对象初始化，直接设置到读状态。
+    pub fn new(val: T) -> Self {
+        let internal = Box::new(SlimInternal {
+            count: Cell::new(1),
+            flag: RwFlag::new_read(),
+            val: Cell::new(val),
+        });
+        Self {
+            ptr: NonNull::from(Box::leak(internal)),
+            state: LocalState::Read,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// 共享对象的副本数量。
+    pub fn count(&self) -> usize {
+        self.internal().count.get()
+    }
+
+    /// 判断是否可读。
+    pub fn is_readable(&self) -> bool {
+        self.state.is_readable(&self.internal().flag)
+    }
+
+    /// 判断是否可写。
+    pub fn is_writeable(&self) -> bool {
+        self.state.is_writeable(&self.internal().flag)
+    }
+
+    /// 尝试设置到读状态。
+    pub fn try_read_global(&mut self) -> bool {
+        let (state, internal) = self.split();
+        state.try_read_global(&internal.flag)
+    }
+
+    /// 尝试设置到写状态。
+    pub fn try_write_global(&mut self) -> bool {
+        let (state, internal) = self.split();
+        state.try_write_global(&internal.flag)
+    }
+
+    /// 释放读写状态，回到持有状态。
+    pub fn release(&mut self) {
+        let (state, internal) = self.split();
+        state.release(&internal.flag)
+    }
+
+    /// 在读状态或写状态下借用共享对象，持有状态下返回 `None`。
+    ///
+    /// 借用期间无法改变此副本的状态，因此不需要额外的守卫。
+    pub fn try_read(&self) -> Option<&T> {
+        // 副本存在期间共享对象不会被释放
+        unsafe { self.state.borrow(self.internal().val.as_ptr()) }
+    }
+
+    /// 尝试设置到写状态并可变借用共享对象，无法获取写状态时返回 `None`。
+    pub fn try_write(&mut self) -> Option<&mut T> {
+        let (state, internal) = self.split();
+        // 副本存在期间共享对象不会被释放
+        unsafe { state.borrow_mut(&internal.flag, internal.val.as_ptr()) }
+    }
+
+    /// 尝试转换为完整的 [`RwRc<T>`]，保留此副本的读写状态。
+    ///
+    /// 精简版副本的分配不包含完整版需要的字段，转换只能把共享对象移动到新的分配中。
+    /// 其他副本仍然指向原来的分配，移走共享对象会使它们悬空，
+    /// 因此只有唯一的副本可以转换，存在其他副本时返回原副本。
+    ///
+    /// 不提供总是成功的转换：那要求精简版的分配与完整版的分配布局相同，
+    /// 也就失去了省去弱引用计数和其他字段的意义。
+    pub fn try_into_rwrc(self) -> Result<RwRc<T>, Self> {
+        if self.count() != 1 {
+            return Err(self);
+        }
+        let state = self.state;
+        let internal = unsafe { Box::from_raw(self.ptr.as_ptr()) };
+        std::mem::forget(self);
+
+        let rc = RwRc::new(internal.val.into_inner());
+        match state {
+            LocalState::Hold => rc.release(),
+            LocalState::Read => {}
+            LocalState::Write => assert!(rc.try_write_global()),
+        }
+        Ok(rc)
+    }
+
+    fn internal(&self) -> &SlimInternal<T> {
+        // 副本存在期间共享对象不会被释放
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// 同时借用此副本的读写状态和共享对象。
+    fn split(&mut self) -> (&mut LocalState, &SlimInternal<T>) {
+        // 副本存在期间共享对象不会被释放
+        (&mut self.state, unsafe { self.ptr.as_ref() })
+    }
+}
+
+#[test]
+fn test_slim() {
+    use std::{mem::size_of, rc::Rc};
+
+    assert_eq!(size_of::<SlimInternal<u64>>(), 3 * size_of::<usize>());
+    // 完整版的分配还包含强引用计数和弱引用计数
+    let full = size_of::<crate::Internal<u64>>() + 2 * size_of::<usize>();
+    assert!(size_of::<SlimInternal<u64>>() < full);
+
+    let mut a = SlimRwRc::new(vec![1]);
+    let mut b = a.clone();
+    assert_eq!(a.count(), 2);
+    assert!(a.try_write().is_none());
+    b.release();
+    assert!(a.is_writeable());
+    a.try_write().unwrap().push(2);
+
+    // 写状态的副本克隆为持有状态
+    let mut c = a.clone();
+    assert!(!c.try_read_global());
+    assert!(b.try_read().is_none());
+    a.release();
+    assert!(c.try_read().is_none());
+    assert!(c.try_read_global());
+    assert_eq!(*c.try_read().unwrap(), [1, 2]);
+
+    // 只有唯一的副本可以转换
+    drop(b);
+    let a = a.try_into_rwrc().err().unwrap();
+    drop(c);
+    let rc = a.try_into_rwrc().ok().unwrap();
+    assert!(matches!(rc.state.get(), crate::RwState::Hold));
+    assert_eq!(*rc.read(), [1, 2]);
+    assert_eq!(Rc::strong_count(&rc.rc), 1);
+}
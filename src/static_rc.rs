@@ -0,0 +1,160 @@
+use crate::flag::{LocalState, RwFlag};
+use std::{
+    cell::UnsafeCell,
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// 声明一个 [`StaticRwCell`] 静态变量。
+///
+/// # 示例
+///
+/// ```rust
+/// rwrc::static_rw_cell! {
+///     /// 全局配置。
+///     static CONFIG: u32 = 7;
+/// }
+///
+/// let mut config = CONFIG.take().unwrap();
+/// assert_eq!(*config.try_read().unwrap(), 7);
+/// // 存储只能被取出一次
+/// assert!(CONFIG.take().is_none());
+/// ```
+#[macro_export]
+macro_rules! static_rw_cell {
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $init:expr $(;)?) => {
+        $(#[$attr])*
+        $vis static $name: $crate::StaticRwCell<$ty> = $crate::StaticRwCell::new($init);
+    };
+}
+
+/// [`StaticRwRc<T>`] 的静态存储，通常由 [`static_rw_cell!`] 声明。
+///
+/// 存储只能通过 [`take`](Self::take) 取出一次，
+/// 取出的副本及其克隆都不能离开取出它的线程，因此存储本身可以放在静态变量中。
+pub struct StaticRwCell<T> {
+    /// 是否已经被取出。
+    taken: AtomicBool,
+    /// 共享读写状态。
+    flag: RwFlag,
+    /// 共享对象。
+    val: UnsafeCell<T>,
+}
+
+// 只有取出存储的线程能访问共享读写状态和共享对象，共享对象可能在其他线程上构造
+unsafe impl<T: Send> Sync for StaticRwCell<T> {}
+
+/// 由静态存储支持的 [`RwRc<T>`](crate::RwRc)。
+///
+/// 共享对象位于 [`StaticRwCell`] 中，既不分配也不计数，永远不会被释放。
+/// 副本的读写状态语义与 [`RwRc<T>`](crate::RwRc) 相同，但只能在读状态或写状态下借用共享对象。
+pub struct StaticRwRc<T: 'static> {
+    /// 共享的对象和状态。
+    cell: &'static StaticRwCell<T>,
+    /// 此副本占用的读写状态。
+    state: LocalState,
+    /// 副本不能发送到其他线程。
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<T> StaticRwCell<T> {
+    /// 创建静态存储。
+    pub const fn new(val: T) -> Self {
+        Self {
+            taken: AtomicBool::new(false),
+            flag: RwFlag::new_hold(),
+            val: UnsafeCell::new(val),
+        }
+    }
+
+    /// 取出第一个副本，初始为读状态。已经被取出时返回 `None`。
+    pub fn take(&'static self) -> Option<StaticRwRc<T>> {
+        if self.taken.swap(true, Ordering::Acquire) {
+            return None;
+        }
+        assert!(self.flag.hold_to_read());
+        Some(StaticRwRc {
+            cell: self,
+            state: LocalState::Read,
+            _not_send: PhantomData,
+        })
+    }
+}
+
+impl<T> Clone for StaticRwRc<T> {
+    /// 只有当源对象在读状态时，克隆的对象才会设置读状态，否则设置为持有状态。
+    fn clone(&self) -> Self {
+        Self {
+            cell: self.cell,
+            state: self.state.clone_with(&self.cell.flag),
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for StaticRwRc<T> {
+    fn drop(&mut self) {
+        self.release()
+    }
+}
+
+impl<T> StaticRwRc<T> {
+    /// 判断是否可读。
+    pub fn is_readable(&self) -> bool {
+        self.state.is_readable(&self.cell.flag)
+    }
+
+    /// 判断是否可写。
+    pub fn is_writeable(&self) -> bool {
+        self.state.is_writeable(&self.cell.flag)
+    }
+
+    /// 尝试设置到读状态。
+    pub fn try_read_global(&mut self) -> bool {
+        self.state.try_read_global(&self.cell.flag)
+    }
+
+    /// 尝试设置到写状态。
+    pub fn try_write_global(&mut self) -> bool {
+        self.state.try_write_global(&self.cell.flag)
+    }
+
+    /// 释放读写状态，回到持有状态。
+    pub fn release(&mut self) {
+        self.state.release(&self.cell.flag)
+    }
+
+    /// 在读状态或写状态下借用共享对象，持有状态下返回 `None`。
+    pub fn try_read(&self) -> Option<&T> {
+        // 静态存储永远不会被释放，借用期间无法改变此副本的状态
+        unsafe { self.state.borrow(self.cell.val.get()) }
+    }
+
+    /// 尝试设置到写状态并可变借用共享对象，无法获取写状态时返回 `None`。
+    pub fn try_write(&mut self) -> Option<&mut T> {
+        // 静态存储永远不会被释放，借用期间无法改变此副本的状态
+        unsafe { self.state.borrow_mut(&self.cell.flag, self.cell.val.get()) }
+    }
+}
+
+#[test]
+fn test_static_rwrc() {
+    static_rw_cell! {
+        static CELL: Vec<i32> = Vec::new();
+    }
+
+    let mut a = CELL.take().unwrap();
+    assert!(CELL.take().is_none());
+    let mut b = a.clone();
+    assert!(a.try_write().is_none());
+    b.release();
+    a.try_write().unwrap().push(1);
+
+    // 写状态的副本克隆为持有状态
+    let mut c = a.clone();
+    assert!(!c.try_read_global());
+    drop(a);
+    assert!(c.try_read_global());
+    assert_eq!(*c.try_read().unwrap(), [1]);
+    assert!(!b.is_writeable());
+}
@@ -0,0 +1,306 @@
+//! 跨多个共享对象的事务。
+//!
+//! [`atomically`] 以 [`Transaction`] 调用闭包：闭包中的读取记录共享对象的版本，
+//! 写入只修改事务内的副本。闭包返回后事务检查读取过的共享对象是否在此期间被写入，
+//! 没有冲突时一次性获取所有写入对象的写状态并替换共享对象，否则放弃写入并重新执行闭包。
+//! 因此闭包可能执行多次，不应当有事务之外的副作用。
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rwrc::{RwRc, stm};
+//!
+//! let from = RwRc::new(100);
+//! let to = RwRc::new(0);
+//! stm::atomically(|tx| {
+//!     let amount = tx.read(&from)?.min(30);
+//!     tx.write(&from, |x| *x -= amount)?;
+//!     tx.write(&to, |x| *x += amount)?;
+//!     Ok(())
+//! })
+//! .unwrap();
+//! assert_eq!((*from.read(), *to.read()), (70, 30));
+//! ```
+
+use crate::{Access, LockError, RwRc, RwState};
+use std::{error::Error, fmt, ptr::NonNull};
+
+/// 事务因冲突重新执行的次数上限。
+const MAX_ATTEMPTS: usize = 8;
+
+/// 事务失败的原因。
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum TxError {
+    /// 无法获取读取或写入需要的访问权限。
+    Lock(LockError),
+    /// 重新执行达到次数上限后仍然冲突。
+    Conflict,
+}
+
+impl From<LockError> for TxError {
+    fn from(e: LockError) -> Self {
+        Self::Lock(e)
+    }
+}
+
+impl fmt::Display for TxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lock(e) => write!(f, "transaction failed: {e}"),
+            Self::Conflict => write!(
+                f,
+                "transaction kept conflicting after {MAX_ATTEMPTS} attempts"
+            ),
+        }
+    }
+}
+
+impl Error for TxError {}
+
+/// 正在执行的事务，记录读取过的版本和尚未提交的写入。
+pub struct Transaction<'a> {
+    /// 读取过的共享对象和第一次读取时的版本。
+    reads: Vec<(&'a dyn Versioned, u64)>,
+    /// 尚未提交的写入，每个共享对象最多一项。
+    writes: Vec<Box<dyn Pending + 'a>>,
+}
+
+/// 类型擦除的读取记录。
+trait Versioned {
+    /// 共享对象的标识。
+    fn id(&self) -> usize;
+    /// 共享对象当前的版本。
+    fn version(&self) -> u64;
+}
+
+impl<T> Versioned for RwRc<T> {
+    fn id(&self) -> usize {
+        RwRc::id(self)
+    }
+
+    fn version(&self) -> u64 {
+        self.rc.extras().map_or(0, |e| e.version.get())
+    }
+}
+
+/// 类型擦除的写入。
+trait Pending {
+    /// 共享对象的标识。
+    fn id(&self) -> usize;
+    /// 事务内的副本。
+    fn value(&mut self) -> NonNull<()>;
+    /// 不改变副本的读写状态获取写状态。
+    fn lock(&self) -> bool;
+    /// 撤销 [`lock`](Self::lock) 获取的写状态，不视为一次写入。
+    fn unlock(&self);
+    /// 以事务内的副本替换共享对象并释放 [`lock`](Self::lock) 获取的写状态。
+    fn commit(self: Box<Self>);
+}
+
+/// 一个共享对象上尚未提交的写入。
+struct Buffered<'a, T> {
+    /// 写入的副本。
+    rc: &'a RwRc<T>,
+    /// 事务内的副本。
+    val: T,
+}
+
+impl<T> Pending for Buffered<'_, T> {
+    fn id(&self) -> usize {
+        self.rc.id()
+    }
+
+    fn value(&mut self) -> NonNull<()> {
+        NonNull::from(&mut self.val).cast()
+    }
+
+    fn lock(&self) -> bool {
+        let rc = self.rc;
+        // 与可变借用相同，副本上存在借用时无法写入
+        rc.guards.check_mut();
+        match rc.state.get() {
+            RwState::Hold => rc.rc.hold_to_write(),
+            RwState::Read | RwState::Upgradable => rc.rc.read_to_write(),
+            RwState::Write => true,
+        }
+    }
+
+    fn unlock(&self) {
+        let rc = &self.rc.rc;
+        match self.rc.state.get() {
+            RwState::Hold => {
+                rc.flag.write_to_hold();
+                rc.leave_parent()
+            }
+            RwState::Read | RwState::Upgradable => rc.flag.write_to_read(),
+            RwState::Write => {}
+        }
+    }
+
+    fn commit(self: Box<Self>) {
+        let Self { rc, val } = *self;
+        // 写状态下没有其他副本能访问共享对象
+        unsafe { *rc.rc.val.as_ptr() = val };
+        match rc.state.get() {
+            RwState::Hold => rc.rc.write_to_hold(),
+            RwState::Read | RwState::Upgradable => rc.rc.write_to_read(),
+            // 副本仍然处于写状态，写状态结束时才通知观察者
+            RwState::Write => {
+                let version = &rc.rc.ensure_extras().version;
+                version.set(version.get() + 1);
+                return;
+            }
+        }
+        rc.notify_released()
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// 读取共享对象，返回它在事务中的克隆。
+    ///
+    /// 事务已经写入过这个共享对象时返回写入后的值。
+    /// 只在克隆期间占用读取权限，无法获取时返回错误。
+    ///
+    /// # Panic
+    ///
+    /// 副本正被可变借用时 panic。
+    pub fn read<T: Clone>(&mut self, rc: &'a RwRc<T>) -> Result<T, LockError> {
+        if let Some(val) = self.buffered(rc) {
+            return Ok(val.clone());
+        }
+        let val = rc.try_read().ok_or_else(|| rc.read_error())?.clone();
+        let id = rc.id();
+        if !self.reads.iter().any(|(r, _)| r.id() == id) {
+            // 分配版本计数之后的写入才会被计数
+            self.reads.push((rc, rc.rc.ensure_extras().version.get()))
+        }
+        Ok(val)
+    }
+
+    /// 以 `f` 修改共享对象在事务中的克隆，事务提交时替换共享对象。
+    ///
+    /// 第一次写入一个共享对象时先像 [`read`](Self::read) 一样读取它。
+    ///
+    /// # Panic
+    ///
+    /// 副本正被可变借用时 panic。
+    pub fn write<T: Clone>(
+        &mut self,
+        rc: &'a RwRc<T>,
+        f: impl FnOnce(&mut T),
+    ) -> Result<(), LockError> {
+        if let Some(val) = self.buffered(rc) {
+            f(val);
+            return Ok(());
+        }
+        let mut val = self.read(rc)?;
+        f(&mut val);
+        self.writes.push(Box::new(Buffered { rc, val }));
+        Ok(())
+    }
+
+    /// 事务内对共享对象尚未提交的写入。
+    fn buffered<T>(&mut self, rc: &RwRc<T>) -> Option<&mut T> {
+        let id = rc.id();
+        let pending = self.writes.iter_mut().find(|w| w.id() == id)?;
+        // 标识相同的副本属于同一个共享对象，事务借用副本期间共享对象不会被释放，类型一定相同
+        Some(unsafe { pending.value().cast::<T>().as_mut() })
+    }
+
+    /// 提交事务，读取过的共享对象已经被写入时返回 `Ok(false)`。
+    fn commit(mut self) -> Result<bool, LockError> {
+        if self.reads.iter().any(|(r, v)| r.version() != *v) {
+            return Ok(false);
+        }
+        self.writes.sort_by_key(|w| w.id());
+        // 先获取所有写状态，失败时撤销已经获取的部分
+        for i in 0..self.writes.len() {
+            if !self.writes[i].lock() {
+                for w in self.writes[..i].iter().rev() {
+                    w.unlock()
+                }
+                return Err(LockError::Blocked(Access::Write));
+            }
+        }
+        for w in self.writes {
+            w.commit()
+        }
+        Ok(true)
+    }
+}
+
+/// 以事务执行 `f`，返回 `f` 的结果。
+///
+/// `f` 返回错误或者提交时无法获取写状态时放弃所有写入并返回错误；
+/// `f` 执行期间读取过的共享对象被写入时放弃写入并重新执行 `f`，
+/// 重新执行达到次数上限后返回 [`TxError::Conflict`]。
+pub fn atomically<'a, R>(
+    mut f: impl FnMut(&mut Transaction<'a>) -> Result<R, TxError>,
+) -> Result<R, TxError> {
+    for _ in 0..MAX_ATTEMPTS {
+        let mut tx = Transaction {
+            reads: Vec::new(),
+            writes: Vec::new(),
+        };
+        let ans = f(&mut tx)?;
+        if tx.commit()? {
+            return Ok(ans);
+        }
+    }
+    Err(TxError::Conflict)
+}
+
+#[test]
+fn test_atomically() {
+    let a = RwRc::new(vec![1]);
+    let b = RwRc::new(0);
+    let alias = b.clone();
+
+    // 事务内的写入在提交前不可见，读取返回写入后的值
+    let mut bumper = a.clone();
+    bumper.release();
+    let mut attempts = 0;
+    let len = atomically(|tx| {
+        attempts += 1;
+        tx.write(&a, |v| v.push(2))?;
+        assert!(!a.read().contains(&2));
+        let len = tx.read(&a)?.len();
+        tx.write(&b, |x| *x = len)?;
+        tx.write(&alias, |x| *x += 1)?;
+        // 第一次执行时读取过的对象被写入，事务重新执行
+        if attempts == 1 {
+            alias.release();
+            a.release();
+            bumper.write().push(0);
+            assert!(a.try_read_global());
+        }
+        Ok(len)
+    });
+    assert_eq!((len, attempts), (Ok(3), 2));
+    assert_eq!((a.read().clone(), *b.read()), (vec![1, 0, 2], 4));
+
+    // 无法获取写状态时不写入任何对象
+    drop(alias);
+    assert!(bumper.try_read_global());
+    assert_eq!(
+        atomically(|tx| {
+            tx.write(&b, |x| *x = 0)?;
+            Ok(tx.write(&a, Vec::clear)?)
+        }),
+        Err(TxError::Lock(LockError::Blocked(Access::Write)))
+    );
+    assert_eq!((a.read().len(), *b.read()), (3, 4));
+
+    // 始终冲突时返回错误
+    a.release();
+    assert_eq!(
+        atomically(|tx| {
+            tx.read(&a)?;
+            *bumper.write() = vec![];
+            Ok(())
+        }),
+        Err(TxError::Conflict)
+    );
+}
@@ -0,0 +1,232 @@
+use crate::{Access, LockError, RwRc};
+use std::{
+    any::{Any, TypeId},
+    borrow::Borrow,
+    collections::HashMap,
+    hash::Hash,
+};
+
+/// 以类型和实体键索引的异构共享对象集合。
+///
+/// 每个组件保存为持有状态的 [`RwRc`]，[`get`](Self::get) 返回同一个共享对象的新副本，
+/// [`read`](Self::read) 和 [`write`](Self::write) 在集合保存的副本上临时获取访问权限。
+/// 不需要区分实体时使用默认的单元键 `()`。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{Access, ComponentStore, LockError};
+///
+/// let mut store = ComponentStore::new();
+/// store.insert(String::from("hero"));
+/// store.insert(100u32);
+///
+/// let mut health = store.get::<u32>().unwrap();
+/// *health.write() -= 10;
+/// assert_eq!(store.read(|hp: &u32| *hp), Some(Ok(90)));
+///
+/// // 外部副本持有读状态时无法通过集合修改
+/// assert!(health.try_read_global());
+/// assert_eq!(
+///     store.write(|hp: &mut u32| *hp = 0),
+///     Some(Err(LockError::Blocked(Access::Write)))
+/// );
+/// // 不存在的组件返回 None
+/// assert!(store.get::<f32>().is_none());
+/// ```
+pub struct ComponentStore<K = ()> {
+    /// 按类型分组的组件。
+    map: HashMap<TypeId, HashMap<K, Box<dyn Any>>>,
+}
+
+impl<K> Default for ComponentStore<K> {
+    fn default() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq> ComponentStore<K> {
+    /// 创建空的组件集合。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为实体 `key` 插入类型为 `T` 的组件，返回原来的组件。
+    pub fn insert_for<T: 'static>(&mut self, key: K, val: T) -> Option<RwRc<T>> {
+        let rc = RwRc::new(val);
+        rc.release();
+        self.map
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .insert(key, Box::new(rc))
+            .map(downcast)
+    }
+
+    /// 获取实体 `key` 类型为 `T` 的组件的副本，初始为持有状态。
+    pub fn get_for<T: 'static, Q>(&self, key: &Q) -> Option<RwRc<T>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entry(key).cloned()
+    }
+
+    /// 移出实体 `key` 类型为 `T` 的组件。
+    pub fn remove_for<T: 'static, Q>(&mut self, key: &Q) -> Option<RwRc<T>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let group = self.map.get_mut(&TypeId::of::<T>())?;
+        let ans = group.remove(key).map(downcast);
+        if group.is_empty() {
+            self.map.remove(&TypeId::of::<T>());
+        }
+        ans
+    }
+
+    /// 判断实体 `key` 是否有类型为 `T` 的组件。
+    pub fn contains_for<T: 'static, Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entry::<T, Q>(key).is_some()
+    }
+
+    /// 读取实体 `key` 类型为 `T` 的组件。
+    ///
+    /// 组件不存在时返回 `None`，无法获取读状态时返回 [`LockError`]。
+    pub fn read_for<T: 'static, Q, R>(
+        &self,
+        key: &Q,
+        f: impl FnOnce(&T) -> R,
+    ) -> Option<Result<R, LockError>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let rc = self.entry(key)?;
+        Some(rc.try_read().map(|r| f(&r)).ok_or_else(|| rc.read_error()))
+    }
+
+    /// 修改实体 `key` 类型为 `T` 的组件。
+    ///
+    /// 组件不存在时返回 `None`，无法获取写状态时返回 [`LockError`]。
+    pub fn write_for<T: 'static, Q, R>(
+        &mut self,
+        key: &Q,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Option<Result<R, LockError>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let rc = self
+            .map
+            .get_mut(&TypeId::of::<T>())?
+            .get_mut(key)?
+            .downcast_mut::<RwRc<T>>()
+            .unwrap();
+        Some(
+            rc.try_write()
+                .map(|mut w| f(&mut w))
+                .ok_or(LockError::Blocked(Access::Write)),
+        )
+    }
+
+    /// 组件的总数。
+    pub fn len(&self) -> usize {
+        self.map.values().map(HashMap::len).sum()
+    }
+
+    /// 判断集合是否为空。
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// 查找实体 `key` 类型为 `T` 的组件。
+    fn entry<T: 'static, Q>(&self, key: &Q) -> Option<&RwRc<T>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let any = self.map.get(&TypeId::of::<T>())?.get(key)?;
+        // 组件按类型分组，组内的类型总是 `T`
+        Some(any.downcast_ref().unwrap())
+    }
+}
+
+impl ComponentStore {
+    /// 插入类型为 `T` 的组件，返回原来的组件。
+    pub fn insert<T: 'static>(&mut self, val: T) -> Option<RwRc<T>> {
+        self.insert_for((), val)
+    }
+
+    /// 获取类型为 `T` 的组件的副本，初始为持有状态。
+    pub fn get<T: 'static>(&self) -> Option<RwRc<T>> {
+        self.get_for(&())
+    }
+
+    /// 移出类型为 `T` 的组件。
+    pub fn remove<T: 'static>(&mut self) -> Option<RwRc<T>> {
+        self.remove_for(&())
+    }
+
+    /// 判断是否有类型为 `T` 的组件。
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.contains_for::<T, ()>(&())
+    }
+
+    /// 读取类型为 `T` 的组件，参见 [`read_for`](Self::read_for)。
+    pub fn read<T: 'static, R>(&self, f: impl FnOnce(&T) -> R) -> Option<Result<R, LockError>> {
+        self.read_for(&(), f)
+    }
+
+    /// 修改类型为 `T` 的组件，参见 [`write_for`](Self::write_for)。
+    pub fn write<T: 'static, R>(
+        &mut self,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Option<Result<R, LockError>> {
+        self.write_for(&(), f)
+    }
+}
+
+/// 取出组内保存的副本。
+fn downcast<T: 'static>(any: Box<dyn Any>) -> RwRc<T> {
+    *any.downcast().unwrap()
+}
+
+#[test]
+fn test_component_store() {
+    let mut store = ComponentStore::<&str>::new();
+    assert!(store.insert_for("a", 1i32).is_none());
+    store.insert_for("b", 2i32);
+    store.insert_for("a", 'x');
+    assert_eq!(store.len(), 3);
+    assert!(store.contains_for::<char, _>("a"));
+    assert!(!store.contains_for::<char, _>("b"));
+
+    // 同一实体的不同类型互不影响
+    let mut a = store.get_for::<i32, _>("a").unwrap();
+    assert!(a.try_write_global());
+    assert_eq!(store.read_for("a", |c: &char| *c), Some(Ok('x')));
+    assert_eq!(
+        store.read_for("a", |n: &i32| *n),
+        Some(Err(LockError::Blocked(Access::Read)))
+    );
+    *a.write() = 10;
+    a.release();
+    assert_eq!(store.write_for("a", |n: &mut i32| *n += 1), Some(Ok(())));
+    assert_eq!(*a.read(), 11);
+
+    // 替换后原来的副本仍然指向旧组件
+    let old = store.insert_for("a", 0i32).unwrap();
+    assert_eq!(old.id(), a.id());
+    assert_eq!(store.read_for("a", |n: &i32| *n), Some(Ok(0)));
+    assert!(store.remove_for::<char, _>("a").is_some());
+    assert!(store.remove_for::<char, _>("a").is_none());
+    assert_eq!(store.len(), 2);
+}
@@ -0,0 +1,82 @@
+use crate::RwRc;
+
+impl<T> RwRc<T> {
+    /// 迭代地释放以此副本开头的链式结构，避免递归释放耗尽栈空间。
+    ///
+    /// 如果此副本是共享对象唯一的强引用，取出共享对象，
+    /// 由 `next` 从中摘下下一个节点的副本，再释放共享对象，然后对下一个节点重复此过程。
+    /// 遇到仍有其他强引用的节点时，只丢弃这个副本并停止。
+    ///
+    /// `next` 必须把下一个节点从共享对象中移出（例如通过 [`Option::take`]），
+    /// 否则释放共享对象时仍会递归。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// struct Node {
+    ///     next: Option<RwRc<Node>>,
+    /// }
+    ///
+    /// let mut head = RwRc::new(Node { next: None });
+    /// for _ in 0..100_000 {
+    ///     head = RwRc::new(Node { next: Some(head) });
+    /// }
+    /// head.drop_deep(|node| node.next.take());
+    /// ```
+    pub fn drop_deep(self, mut next: impl FnMut(&mut T) -> Option<Self>) {
+        let mut current = self;
+        loop {
+            match current.try_into_value() {
+                Ok(mut val) => {
+                    let link = next(&mut val);
+                    drop(val);
+                    match link {
+                        Some(rc) => current = rc,
+                        None => break,
+                    }
+                }
+                Err(rc) => {
+                    drop(rc);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_drop_deep() {
+    use std::{cell::Cell, rc::Rc};
+
+    struct Node {
+        next: Option<RwRc<Node>>,
+        dropped: Rc<Cell<usize>>,
+    }
+
+    impl Drop for Node {
+        fn drop(&mut self) {
+            self.dropped.set(self.dropped.get() + 1)
+        }
+    }
+
+    let dropped = Rc::new(Cell::new(0));
+    let node = |next| {
+        RwRc::new(Node {
+            next,
+            dropped: dropped.clone(),
+        })
+    };
+    let tail = node(None);
+    let mut head = node(Some(tail.clone()));
+    for _ in 0..200_000 {
+        head = node(Some(head))
+    }
+    head.drop_deep(|node| node.next.take());
+    // 仍被引用的尾节点被保留
+    assert_eq!(dropped.get(), 200_001);
+    assert!(tail.is_readable());
+    drop(tail);
+    assert_eq!(dropped.get(), 200_002);
+}
@@ -0,0 +1,96 @@
+use crate::RwRc;
+use std::thread::LocalKey;
+
+/// 声明一个线程局部的共享对象，通过 [`RwRcLocalKey`] 访问。
+///
+/// 每个线程第一次访问时以初始化表达式创建自己的共享对象，之后的访问得到同一个共享对象的副本。
+///
+/// # 示例
+///
+/// ```rust
+/// #[derive(Default)]
+/// struct Config {
+///     verbose: bool,
+/// }
+///
+/// rwrc::rwrc_static! {
+///     /// 全局配置。
+///     static CONFIG: Config = Config::default();
+/// }
+///
+/// let mut config = CONFIG.get();
+/// config.write().verbose = true;
+/// assert!(CONFIG.get().read().verbose);
+/// ```
+#[macro_export]
+macro_rules! rwrc_static {
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $init:expr $(;)?) => {
+        $(#[$attr])*
+        $vis static $name: $crate::RwRcLocalKey<$ty> = {
+            ::std::thread_local! {
+                static KEY: $crate::RwRc<$ty> = {
+                    let rc = $crate::RwRc::new($init);
+                    rc.release();
+                    rc
+                };
+            }
+            $crate::RwRcLocalKey::new(&KEY)
+        };
+    };
+}
+
+/// 线程局部共享对象的访问器，由 [`rwrc_static!`] 声明。
+///
+/// 访问器保存一个持有状态的副本，[`get`](Self::get) 返回它的克隆，
+/// 因此共享对象在线程结束前一直存活。
+pub struct RwRcLocalKey<T: 'static> {
+    /// 保存副本的线程局部变量。
+    key: &'static LocalKey<RwRc<T>>,
+}
+
+impl<T: 'static> RwRcLocalKey<T> {
+    /// 以保存副本的线程局部变量创建访问器。通常由 [`rwrc_static!`] 调用。
+    #[doc(hidden)]
+    pub const fn new(key: &'static LocalKey<RwRc<T>>) -> Self {
+        Self { key }
+    }
+
+    /// 获取当前线程上共享对象的副本，初始为持有状态。
+    ///
+    /// # Panic
+    ///
+    /// 在当前线程的线程局部变量被销毁后调用时 panic。
+    pub fn get(&'static self) -> RwRc<T> {
+        self.key.with(RwRc::clone)
+    }
+
+    /// 以当前线程上访问器保存的副本调用 `f`，不创建新的副本。
+    ///
+    /// # Panic
+    ///
+    /// 在当前线程的线程局部变量被销毁后调用时 panic。
+    pub fn with<R>(&'static self, f: impl FnOnce(&RwRc<T>) -> R) -> R {
+        self.key.with(f)
+    }
+}
+
+#[cfg(test)]
+rwrc_static! {
+    static COUNTER: u32 = 1;
+}
+
+#[test]
+fn test_rwrc_static() {
+    let mut a = COUNTER.get();
+    assert_eq!(a.state(), crate::RwState::Hold);
+    *a.write() += 1;
+    assert_eq!(COUNTER.with(|rc| *rc.read()), 2);
+    // 返回的副本共享同一个对象
+    let b = COUNTER.get();
+    assert!(b.try_read_global());
+    assert!(a.try_write().is_none());
+
+    // 其他线程上有自己的共享对象
+    let other = std::thread::spawn(|| *COUNTER.get().read()).join().unwrap();
+    assert_eq!(other, 1);
+}
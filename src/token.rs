@@ -0,0 +1,146 @@
+use crate::{RwRc, RwWeak};
+use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+/// [`RwRcCell<T>`] 的所有者令牌。
+///
+/// 通过所有者的借用来静态地控制对单元的访问：
+/// 持有 `&RwRcOwner` 可以读取单元，持有 `&mut RwRcOwner` 可以修改单元。
+/// 每个所有者有唯一的标识，单元只接受创建它的所有者。
+pub struct RwRcOwner(usize);
+
+/// 由所有者令牌控制访问的共享对象。
+///
+/// 单元持有共享对象的写状态，与 [`RwRc<T>`] 使用相同的分配和弱引用机制，
+/// 但访问时只检查所有者标识而不检查读写状态。
+/// 从单元的弱引用升级得到的 [`RwRc<T>`] 在单元存在期间无法获得读写权限。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRcCell, RwRcOwner};
+///
+/// let mut owner = RwRcOwner::new();
+/// let a = RwRcCell::new(&owner, 1);
+/// let b = RwRcCell::new(&owner, 2);
+///
+/// *a.rw(&mut owner) += *b.ro(&owner);
+/// assert_eq!(*a.ro(&owner), 3);
+/// ```
+pub struct RwRcCell<T> {
+    /// 持有写状态的副本。
+    rc: RwRc<T>,
+    /// 所有者标识。
+    owner: usize,
+}
+
+impl Default for RwRcOwner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RwRcOwner {
+    /// 创建一个新的所有者。
+    pub fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        Self(NEXT.fetch_add(1, Relaxed))
+    }
+}
+
+impl<T> RwRc<T> {
+    /// 将副本转换为由 `owner` 控制访问的单元。
+    ///
+    /// 副本需要获取写状态，如果无法获取则返回原副本。
+    pub fn into_cell(self, owner: &RwRcOwner) -> Result<RwRcCell<T>, Self> {
+        if self.try_write_global() {
+            Ok(RwRcCell {
+                rc: self,
+                owner: owner.0,
+            })
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<T> RwRcCell<T> {
+    /// 创建由 `owner` 控制访问的单元。
+    pub fn new(owner: &RwRcOwner, val: T) -> Self {
+        match RwRc::new(val).into_cell(owner) {
+            Ok(cell) => cell,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// 通过所有者的共享借用读取单元。
+    ///
+    /// # Panic
+    ///
+    /// `owner` 不是单元的所有者时 panic。
+    pub fn ro<'a>(&'a self, owner: &'a RwRcOwner) -> &'a T {
+        self.check(owner);
+        // 单元持有写状态，其他副本无法访问；所有者被共享借用期间，只存在共享引用
+        unsafe { &*self.rc.rc.val.as_ptr() }
+    }
+
+    /// 通过所有者的可变借用修改单元。
+    ///
+    /// # Panic
+    ///
+    /// `owner` 不是单元的所有者时 panic。
+    pub fn rw<'a>(&'a self, owner: &'a mut RwRcOwner) -> &'a mut T {
+        self.check(owner);
+        // 单元持有写状态，其他副本无法访问；所有者被可变借用期间，不存在其他引用
+        unsafe { &mut *self.rc.rc.val.as_ptr() }
+    }
+
+    /// 创建单元的弱引用。
+    pub fn weak(&self) -> RwWeak<T> {
+        self.rc.weak()
+    }
+
+    /// 将单元转换回持有写状态的副本。
+    pub fn into_rwrc(self) -> RwRc<T> {
+        self.rc
+    }
+
+    fn check(&self, owner: &RwRcOwner) {
+        if self.owner != owner.0 {
+            crate::policy::misuse(format_args!("RwRcCell accessed with a foreign owner"))
+        }
+    }
+}
+
+#[test]
+fn test_token_cell() {
+    let mut owner = RwRcOwner::new();
+    let rc = RwRc::new(vec![1]);
+    let other = rc.clone();
+
+    // 其他副本持有读状态时无法转换
+    let rc = rc.into_cell(&owner).err().unwrap();
+    other.release();
+    let cell = rc.into_cell(&owner).ok().unwrap();
+
+    cell.rw(&mut owner).push(2);
+    assert_eq!(*cell.ro(&owner), [1, 2]);
+
+    // 单元存在期间其他副本无法访问
+    assert!(other.try_read().is_none());
+    let weak = cell.weak();
+    assert!(weak.hold().unwrap().try_read().is_none());
+
+    // 转换回副本后恢复读写状态控制
+    let rc = cell.into_rwrc();
+    assert!(other.try_read().is_none());
+    rc.release();
+    assert_eq!(*other.read(), [1, 2]);
+}
+
+#[test]
+#[should_panic(expected = "foreign owner")]
+fn test_token_cell_foreign_owner() {
+    let owner = RwRcOwner::new();
+    let cell = RwRcCell::new(&owner, 0);
+    let _ = cell.ro(&RwRcOwner::new());
+}
@@ -0,0 +1,430 @@
+use crate::{Internal, RwFlag, RwRc, RwWeak};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    rc::{Rc, Weak},
+    vec,
+};
+
+/// 枚举对象中包含的 [`RwRc`] 和 [`RwWeak`]。
+///
+/// 环检测、环回收等功能通过此 trait 遍历共享对象之间的引用关系。
+/// 实现时应当对每个直接或间接包含的副本和弱引用恰好调用一次 [`Tracer`] 的方法。
+/// 不应当穿过 `Rc`、`Arc` 等其他共享指针报告副本，否则同一个副本可能被多次报告。
+/// 启用 `derive` 特性后可以通过 `#[derive(Trace)]` 生成实现。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, RwWeak, Trace, Tracer};
+///
+/// struct Node {
+///     children: Vec<RwRc<Node>>,
+///     parent: Option<RwWeak<Node>>,
+/// }
+///
+/// impl Trace for Node {
+///     fn trace(&self, tracer: &mut Tracer) {
+///         self.children.trace(tracer);
+///         self.parent.trace(tracer);
+///     }
+/// }
+/// ```
+pub trait Trace {
+    /// 向 `tracer` 报告包含的副本和弱引用。
+    fn trace(&self, tracer: &mut Tracer);
+}
+
+/// 收集 [`Trace::trace`] 报告的引用。
+#[derive(Default)]
+pub struct Tracer {
+    /// 报告的引用。
+    pub(crate) edges: Vec<Edge>,
+}
+
+/// 共享对象之间的一条引用。
+pub(crate) struct Edge {
+    /// 被引用的共享对象。
+    pub node: Weak<dyn Node>,
+    /// 是否是强引用。
+    pub strong: bool,
+}
+
+/// 类型擦除的可遍历共享对象。
+pub(crate) trait Node {
+    /// 遍历共享对象包含的引用。
+    ///
+    /// 共享对象被写锁定时无法读取，返回 `false`。
+    fn trace(&self, tracer: &mut Tracer) -> bool;
+
+    /// 共享对象的类型名。
+    fn type_name(&self) -> &'static str;
+
+    /// 共享读写状态。
+    fn flag(&self) -> &RwFlag;
+}
+
+impl Tracer {
+    /// 报告一个强引用。
+    pub fn strong<U: Trace + 'static>(&mut self, rc: &RwRc<U>) {
+        let node: Weak<Internal<U>> = Rc::downgrade(&rc.rc);
+        self.edges.push(Edge { node, strong: true })
+    }
+
+    /// 报告一个弱引用。
+    pub fn weak<U: Trace + 'static>(&mut self, weak: &RwWeak<U>) {
+        let node: Weak<Internal<U>> = weak.0.clone();
+        self.edges.push(Edge {
+            node,
+            strong: false,
+        })
+    }
+}
+
+impl<T: Trace> Node for Internal<T> {
+    fn trace(&self, tracer: &mut Tracer) -> bool {
+        /// 遍历期间占用的读状态，释放时归还，用户代码 panic 时也会归还。
+        struct Reading<'a>(&'a RwFlag);
+
+        impl Drop for Reading<'_> {
+            fn drop(&mut self) {
+                self.0.read_to_hold()
+            }
+        }
+
+        if !self.flag.hold_to_read() {
+            return false;
+        }
+        let _reading = Reading(&self.flag);
+        // 遍历期间占用一份读状态，用户代码无法通过其他副本获取写状态
+        unsafe { &*self.val.as_ptr() }.trace(tracer);
+        true
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn flag(&self) -> &RwFlag {
+        &self.flag
+    }
+}
+
+/// 共享对象的标识，即共享对象的地址。
+pub(crate) fn node_id<N: ?Sized>(node: &Weak<N>) -> usize {
+    node.as_ptr() as *const () as usize
+}
+
+/// 遍历共享对象，返回它的强引用。
+///
+/// 共享对象已释放或被写锁定时返回空。
+fn strong_children(node: &Weak<dyn Node>) -> vec::IntoIter<Weak<dyn Node>> {
+    let mut tracer = Tracer::default();
+    if let Some(rc) = node.upgrade() {
+        rc.trace(&mut tracer);
+    }
+    tracer
+        .edges
+        .into_iter()
+        .filter(|e| e.strong)
+        .map(|e| e.node)
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+/// 从 `roots` 报告的副本出发检测强引用环，不会释放任何对象。
+///
+/// 每个环表示为环上共享对象标识（即 [`RwRc::id`]）的路径，
+/// 路径上每个对象强引用下一个对象，最后一个对象强引用第一个对象。
+/// 深度优先遍历中每条回边报告一个环，因此共享部分路径的环可能只报告其中一部分。
+/// 被写锁定的对象无法读取，不会继续遍历。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::{RwRc, Trace, Tracer, detect_cycles};
+///
+/// struct Node(Vec<RwRc<Node>>);
+///
+/// impl Trace for Node {
+///     fn trace(&self, tracer: &mut Tracer) {
+///         self.0.trace(tracer)
+///     }
+/// }
+///
+/// let leaf = RwRc::new(Node(vec![]));
+/// let root = RwRc::new(Node(vec![leaf.clone(), leaf]));
+/// assert!(detect_cycles(&root).is_empty());
+/// ```
+pub fn detect_cycles(roots: &(impl Trace + ?Sized)) -> Vec<Vec<usize>> {
+    let mut tracer = Tracer::default();
+    roots.trace(&mut tracer);
+
+    let mut cycles = Vec::new();
+    let mut finished = HashSet::new();
+    let mut on_path = HashMap::new();
+    let mut path = Vec::new();
+    for root in tracer.edges.into_iter().filter(|e| e.strong) {
+        let id = node_id(&root.node);
+        if finished.contains(&id) {
+            continue;
+        }
+        on_path.insert(id, 0);
+        path.push((id, strong_children(&root.node)));
+
+        while let Some((_, children)) = path.last_mut() {
+            match children.next() {
+                Some(child) => {
+                    let id = node_id(&child);
+                    if let Some(&i) = on_path.get(&id) {
+                        cycles.push(path[i..].iter().map(|(id, _)| *id).collect())
+                    } else if !finished.contains(&id) {
+                        on_path.insert(id, path.len());
+                        path.push((id, strong_children(&child)))
+                    }
+                }
+                None => {
+                    let (id, _) = path.pop().unwrap();
+                    on_path.remove(&id);
+                    finished.insert(id);
+                }
+            }
+        }
+    }
+    cycles
+}
+
+impl<U: Trace + 'static> Trace for RwRc<U> {
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.strong(self)
+    }
+}
+
+impl<U: Trace + 'static> Trace for RwWeak<U> {
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.weak(self)
+    }
+}
+
+macro_rules! trace_nothing {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Trace for $ty {
+                #[inline]
+                fn trace(&self, _: &mut Tracer) {}
+            }
+        )*
+    };
+}
+
+trace_nothing!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    str,
+    String,
+);
+
+impl<T: Trace + ?Sized> Trace for Box<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        (**self).trace(tracer)
+    }
+}
+
+impl<T: Trace> Trace for Option<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Some(val) = self {
+            val.trace(tracer)
+        }
+    }
+}
+
+impl<T: Trace> Trace for [T] {
+    fn trace(&self, tracer: &mut Tracer) {
+        for val in self {
+            val.trace(tracer)
+        }
+    }
+}
+
+impl<T: Trace, const N: usize> Trace for [T; N] {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.as_slice().trace(tracer)
+    }
+}
+
+impl<T: Trace> Trace for Vec<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.as_slice().trace(tracer)
+    }
+}
+
+impl<T: Trace> Trace for VecDeque<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        for val in self {
+            val.trace(tracer)
+        }
+    }
+}
+
+impl<K: Trace, V: Trace, S> Trace for HashMap<K, V, S> {
+    fn trace(&self, tracer: &mut Tracer) {
+        for (k, v) in self {
+            k.trace(tracer);
+            v.trace(tracer)
+        }
+    }
+}
+
+impl<K: Trace, V: Trace> Trace for BTreeMap<K, V> {
+    fn trace(&self, tracer: &mut Tracer) {
+        for (k, v) in self {
+            k.trace(tracer);
+            v.trace(tracer)
+        }
+    }
+}
+
+macro_rules! trace_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Trace),+> Trace for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn trace(&self, tracer: &mut Tracer) {
+                let ($($name,)+) = self;
+                $($name.trace(tracer);)+
+            }
+        }
+    };
+}
+
+trace_tuple!(A);
+trace_tuple!(A, B);
+trace_tuple!(A, B, C);
+trace_tuple!(A, B, C, D);
+
+#[test]
+fn test_tracer() {
+    let a = RwRc::new(1);
+    let b = RwRc::new(2);
+    let value = (vec![a.clone(), b.clone()], Some(a.weak()), String::new());
+
+    let mut tracer = Tracer::default();
+    value.trace(&mut tracer);
+    let edges = tracer
+        .edges
+        .iter()
+        .map(|e| (node_id(&e.node), e.strong))
+        .collect::<Vec<_>>();
+    let id = RwRc::id;
+    assert_eq!(edges, [(id(&a), true), (id(&b), true), (id(&a), false)]);
+}
+
+#[test]
+fn test_detect_cycles() {
+    struct Node(Vec<RwRc<Node>>);
+
+    impl Trace for Node {
+        fn trace(&self, tracer: &mut Tracer) {
+            self.0.trace(tracer)
+        }
+    }
+
+    let id = RwRc::id;
+    let mut a = RwRc::new(Node(vec![]));
+    let mut b = RwRc::new(Node(vec![]));
+    a.release();
+    b.release();
+    // root -> a -> b -> a，root -> b
+    let root = RwRc::new(Node(vec![a.clone(), b.clone()]));
+    a.write().0.push(b.clone());
+    assert!(detect_cycles(&root).is_empty());
+    b.write().0.push(a.clone());
+    assert_eq!(detect_cycles(&root), [vec![id(&a), id(&b)]]);
+    assert_eq!(detect_cycles(&[b.clone()]), [vec![id(&b), id(&a)]]);
+
+    // 被写锁定的对象不会继续遍历
+    assert!(a.try_write_global());
+    assert!(detect_cycles(&root).is_empty());
+    a.release();
+
+    // 打断环
+    b.write().0.clear();
+    assert!(detect_cycles(&root).is_empty());
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_derive_trace() {
+    #[derive(rwrc::Trace)]
+    struct Named<T> {
+        children: Vec<RwRc<T>>,
+        #[trace(skip)]
+        #[allow(dead_code)]
+        name: std::cell::Cell<u8>,
+    }
+
+    #[derive(rwrc::Trace)]
+    #[allow(dead_code)]
+    enum Tree {
+        Leaf,
+        Branch(RwRc<Tree>, #[trace(skip)] std::cell::Cell<u8>, RwWeak<Tree>),
+    }
+
+    let leaf = RwRc::new(Tree::Leaf);
+    let branch = Tree::Branch(leaf.clone(), Default::default(), leaf.weak());
+    let named = Named {
+        children: vec![RwRc::new(branch)],
+        name: Default::default(),
+    };
+
+    let mut tracer = Tracer::default();
+    named.trace(&mut tracer);
+    assert_eq!(tracer.edges.len(), 1);
+    let mut tracer = Tracer::default();
+    named.children[0].read().trace(&mut tracer);
+    let edges = tracer
+        .edges
+        .iter()
+        .map(|e| (node_id(&e.node), e.strong))
+        .collect::<Vec<_>>();
+    let id = leaf.id();
+    assert_eq!(edges, [(id, true), (id, false)]);
+}
+
+#[test]
+fn test_trace_holds_read() {
+    use std::cell::RefCell;
+
+    /// 持有自身副本的节点，遍历时尝试写入自身。
+    struct Node(RefCell<Option<RwRc<Node>>>);
+
+    impl Trace for Node {
+        fn trace(&self, tracer: &mut Tracer) {
+            if let Some(me) = &*self.0.borrow() {
+                assert!(me.clone().try_write().is_none());
+                tracer.strong(me)
+            }
+        }
+    }
+
+    let rc = RwRc::new(Node(RefCell::new(None)));
+    rc.release();
+    *rc.read().0.borrow_mut() = Some(rc.clone());
+    assert_eq!(detect_cycles(&rc), [vec![rc.id()]]);
+    // 遍历结束后归还读状态
+    assert!(rc.rc.flag.is_writeable());
+    rc.read().0.borrow_mut().take();
+}
@@ -0,0 +1,80 @@
+use crate::{RwRc, RwState};
+use std::{cell::RefCell, collections::VecDeque, panic::Location, time::Instant};
+
+/// 每个共享对象最多保留的状态转换记录数量。
+const CAPACITY: usize = 64;
+
+/// 一次副本读写状态的转换，由 [`RwRc::transition_log`] 返回。
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct Transition {
+    /// 转换发生的时间。
+    pub at: Instant,
+    /// 转换前的读写状态。
+    pub from: RwState,
+    /// 转换后的读写状态。
+    pub to: RwState,
+    /// 引起转换的调用位置。
+    pub location: &'static Location<'static>,
+}
+
+/// 共享对象上最近的状态转换记录。
+#[derive(Default)]
+pub(super) struct TransitionLog(RefCell<VecDeque<Transition>>);
+
+impl TransitionLog {
+    /// 记录一次转换，超过容量时丢弃最早的记录。
+    #[track_caller]
+    pub fn record(&self, from: RwState, to: RwState) {
+        let mut log = self.0.borrow_mut();
+        if log.len() == CAPACITY {
+            log.pop_front();
+        }
+        log.push_back(Transition {
+            at: Instant::now(),
+            from,
+            to,
+            location: Location::caller(),
+        })
+    }
+}
+
+impl<T> RwRc<T> {
+    /// 共享对象的所有副本最近的读写状态转换，从早到晚排列。
+    ///
+    /// 每个共享对象最多保留最近 64 次转换。
+    /// 记录的位置是改变读写状态的公开方法的调用者；丢弃副本时释放状态的位置在本 crate 内部。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::{RwRc, RwState};
+    ///
+    /// let rc = RwRc::new(0);
+    /// rc.release();
+    /// assert!(rc.try_write_global());
+    /// let log = rc.transition_log();
+    /// assert_eq!(log.len(), 2);
+    /// assert_eq!((log[1].from, log[1].to), (RwState::Hold, RwState::Write));
+    /// assert_eq!(log[1].location.file(), file!());
+    /// ```
+    pub fn transition_log(&self) -> Vec<Transition> {
+        self.rc.check_thread();
+        self.rc.transitions.0.borrow().iter().copied().collect()
+    }
+}
+
+#[test]
+fn test_transition_log() {
+    let rc = RwRc::new(0);
+    let other = rc.clone();
+    for _ in 0..CAPACITY {
+        other.release();
+        assert!(other.try_read_global());
+    }
+    let log = rc.transition_log();
+    assert_eq!(log.len(), CAPACITY);
+    assert_eq!(log.last().unwrap().to, RwState::Read);
+    assert_eq!(log.last().unwrap().location.file(), file!());
+    assert!(log.windows(2).all(|w| w[0].at <= w[1].at));
+}
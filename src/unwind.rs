@@ -0,0 +1,47 @@
+//! 展开安全标记。
+//!
+//! 与 `Rc<RefCell<T>>` 一样，副本允许通过共享的句柄修改共享对象，也没有 `Mutex` 那样的中毒机制，
+//! panic 时修改到一半的共享对象可以在 `catch_unwind` 之外通过其他副本观察到，
+//! 因此 [`RwRc<T>`](crate::RwRc) 和 [`RwWeak<T>`](crate::RwWeak) 不实现展开安全标记，
+//! 需要跨越 `catch_unwind` 时由调用者以 `AssertUnwindSafe` 断言。
+//!
+//! [`LocalRef`] 只能读取共享对象，借用的 `Drop` 总会还原读写状态，
+//! 因此只要共享对象满足 [`RefUnwindSafe`]，只读借用就可以安全地跨越 `catch_unwind`。
+//! 与 `&mut T` 一样，[`LocalMut`](crate::LocalMut) 在 panic 时可能留下修改到一半的共享对象，不实现这些标记。
+
+use crate::LocalRef;
+use std::panic::{RefUnwindSafe, UnwindSafe};
+
+impl<T: RefUnwindSafe> UnwindSafe for LocalRef<'_, T> {}
+impl<T: RefUnwindSafe> RefUnwindSafe for LocalRef<'_, T> {}
+
+#[test]
+fn test_unwind_safe() {
+    use crate::RwRc;
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
+    assert_unwind_safe::<LocalRef<'_, i32>>();
+
+    let rc = RwRc::new(1);
+    let weak = rc.weak();
+    rc.release();
+    // panic 时借用被释放，读写状态被还原
+    let reader = rc.read();
+    let result = catch_unwind(|| {
+        let _guard = &reader;
+        panic!("boom")
+    });
+    assert!(result.is_err());
+    drop(reader);
+    assert!(rc.is_writeable());
+
+    // 副本需要断言展开安全，panic 前的修改保留下来
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut rc = weak.hold().unwrap();
+        *rc.write() += 1;
+        panic!("boom")
+    }));
+    assert!(result.is_err());
+    assert_eq!(*rc.read(), 2);
+}
@@ -0,0 +1,429 @@
+use crate::{Access, Internal, RwRc, RwState};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+/// 异步获取访问权限的等待者。
+pub(super) struct Waiter {
+    /// 等待者标识。
+    id: usize,
+    /// 等待的访问权限。
+    access: Access,
+    /// 是否是从读状态升级到写状态。
+    upgrade: bool,
+    /// 是否已经获得访问权限。
+    granted: bool,
+    /// 获得访问权限时唤醒的任务。
+    waker: Option<Waker>,
+    /// 登记等待的任务。
+    #[cfg(feature = "deadlock-detect")]
+    task: Option<usize>,
+}
+
+/// 异步获取读写状态的 future，由 [`RwRc::read_async`] 和 [`RwRc::write_async`] 创建。
+///
+/// 完成时副本处于请求的读写状态。
+/// 丢弃未完成的 future 会撤销等待，已经获得的访问权限会被释放，等待队列中不留下等待者，
+/// 因此可以在 `select!` 等会丢弃未完成分支的组合中使用。
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Acquire<'a, T> {
+    /// 获取读写状态的副本。
+    rc: &'a mut RwRc<T>,
+    /// 请求的访问权限。
+    access: Access,
+    /// 在等待队列中的标识。
+    id: Option<usize>,
+}
+
+/// 在另一个 future 完成前异步获取读写状态的 future，由 [`Acquire::until`] 创建。
+///
+/// 获得访问权限时完成并返回 `Ok(())`；`until` 先完成时撤销等待并返回它的输出。
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Until<'a, T, F> {
+    /// 获取读写状态的 future。
+    acquire: Acquire<'a, T>,
+    /// 截止的 future。
+    until: F,
+}
+
+#[cfg(feature = "audit")]
+impl Waiter {
+    /// 等待的访问权限。
+    pub(super) fn access(&self) -> Access {
+        self.access
+    }
+
+    /// 是否是从读状态升级到写状态。
+    pub(super) fn upgrade(&self) -> bool {
+        self.upgrade
+    }
+
+    /// 是否已经获得访问权限。
+    pub(super) fn granted(&self) -> bool {
+        self.granted
+    }
+}
+
+impl<T> RwRc<T> {
+    /// 异步地将此副本设置到读状态。
+    ///
+    /// 无法立即获取时进入共享对象的等待队列。
+    /// 等待队列按登记顺序授予访问权限，连续的读者会一起获得读状态，
+    /// 因此先登记的写者不会被后登记的读者饿死。
+    /// 同步的获取方法不经过等待队列。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    /// use std::{pin::pin, task::{Context, Waker}};
+    ///
+    /// let mut cx = Context::from_waker(Waker::noop());
+    /// let mut writer = RwRc::new(0);
+    /// let mut reader = writer.clone();
+    /// reader.release();
+    /// assert!(writer.try_write_global());
+    ///
+    /// let mut future = pin!(reader.read_async());
+    /// assert!(future.as_mut().poll(&mut cx).is_pending());
+    /// writer.release();
+    /// assert!(future.as_mut().poll(&mut cx).is_ready());
+    /// ```
+    pub fn read_async(&mut self) -> Acquire<'_, T> {
+        self.acquire_async(Access::Read)
+    }
+
+    /// 异步地将此副本设置到写状态。
+    ///
+    /// 与 [`read_async`](Self::read_async) 共享同一个等待队列。
+    pub fn write_async(&mut self) -> Acquire<'_, T> {
+        self.acquire_async(Access::Write)
+    }
+
+    fn acquire_async(&mut self, access: Access) -> Acquire<'_, T> {
+        self.rc.check_thread();
+        Acquire {
+            rc: self,
+            access,
+            id: None,
+        }
+    }
+
+    /// 不经过等待队列尝试获取 `access`，不登记写意图。
+    pub(super) fn try_acquire(&mut self, access: Access) -> bool {
+        let ok = match (access, self.state.get()) {
+            (Access::Read, RwState::Hold) => self.hold_to_read(),
+            (Access::Write, RwState::Hold) => self.rc.hold_to_write(),
+            (Access::Write, RwState::Read | RwState::Upgradable) => self.rc.read_to_write(),
+            (Access::Read, _) | (Access::Write, RwState::Write) => return true,
+        };
+        if ok {
+            self.set_state(target(access));
+        }
+        ok
+    }
+}
+
+/// 获得 `access` 后副本的读写状态。
+fn target(access: Access) -> RwState {
+    match access {
+        Access::Read => RwState::Read,
+        Access::Write => RwState::Write,
+    }
+}
+
+impl<T> Internal<T> {
+    /// 按登记顺序授予等待者访问权限，遇到第一个无法授予的等待者时停止。
+    pub(super) fn grant_waiters(&self) {
+        let Some(extras) = self.extras() else { return };
+        let mut wakers = Vec::new();
+        {
+            let mut waiters = extras.waiters.borrow_mut();
+            for waiter in waiters.iter_mut().filter(|w| !w.granted) {
+                let ok = match (waiter.access, waiter.upgrade) {
+                    (Access::Read, _) => extras.pending_writers.get() == 0 && self.hold_to_read(),
+                    (Access::Write, false) => self.hold_to_write(),
+                    (Access::Write, true) => self.read_to_write(),
+                };
+                if !ok {
+                    break;
+                }
+                waiter.granted = true;
+                #[cfg(feature = "deadlock-detect")]
+                if let Some(task) = waiter.task {
+                    crate::deadlock::stop_waiting(task, self as *const Self as *const () as usize)
+                }
+                wakers.extend(waiter.waker.take())
+            }
+        }
+        wakers.into_iter().for_each(Waker::wake)
+    }
+}
+
+impl<T> Future for Acquire<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let internal = this.rc.rc.clone();
+        match this.id {
+            None => {
+                let queued = internal
+                    .extras()
+                    .is_some_and(|e| !e.waiters.borrow().is_empty());
+                if !queued {
+                    if this.rc.try_acquire(this.access) {
+                        #[cfg(feature = "deadlock-detect")]
+                        this.rc.hold_by(cx.waker());
+                        return Poll::Ready(());
+                    }
+                } else if matches!(
+                    (this.access, this.rc.state.get()),
+                    (
+                        Access::Read,
+                        RwState::Read | RwState::Upgradable | RwState::Write
+                    ) | (Access::Write, RwState::Write)
+                ) {
+                    return Poll::Ready(());
+                }
+                let extras = internal.ensure_extras();
+                let mut waiters = extras.waiters.borrow_mut();
+                let id = extras.next_waiter.get();
+                extras.next_waiter.set(id.wrapping_add(1));
+                waiters.push_back(Waiter {
+                    id,
+                    access: this.access,
+                    upgrade: matches!(this.rc.state.get(), RwState::Read | RwState::Upgradable),
+                    granted: false,
+                    waker: Some(cx.waker().clone()),
+                    #[cfg(feature = "deadlock-detect")]
+                    task: crate::deadlock::task_of(cx.waker()),
+                });
+                this.id = Some(id);
+                #[cfg(feature = "deadlock-detect")]
+                if let Some(task) = crate::deadlock::task_of(cx.waker()) {
+                    drop(waiters);
+                    this.rc.wait_by(task)
+                }
+                Poll::Pending
+            }
+            Some(id) => {
+                let mut waiters = internal.ensure_extras().waiters.borrow_mut();
+                let i = waiters.iter().position(|w| w.id == id).unwrap();
+                if waiters[i].granted {
+                    waiters.remove(i);
+                    this.id = None;
+                    this.rc.set_state(target(this.access));
+                    #[cfg(feature = "deadlock-detect")]
+                    this.rc.hold_by(cx.waker());
+                    Poll::Ready(())
+                } else {
+                    waiters[i].waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T> Acquire<'a, T> {
+    /// 以 `until` 作为截止条件，`until` 先完成时撤销等待。
+    ///
+    /// 每次轮询先检查访问权限，两者同时可以完成时获得访问权限。
+    /// 撤销后副本回到原来的读写状态，等待队列中不留下等待者。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    /// use std::{future::ready, pin::pin, task::{Context, Poll, Waker}};
+    ///
+    /// let mut cx = Context::from_waker(Waker::noop());
+    /// let reader = RwRc::new(0);
+    /// let mut writer = reader.clone();
+    /// writer.release();
+    ///
+    /// // 读者不释放，截止条件先完成
+    /// let mut future = pin!(writer.write_async().until(ready("timeout")));
+    /// assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(Err("timeout")));
+    /// ```
+    pub fn until<F: Future>(self, until: F) -> Until<'a, T, F> {
+        Until {
+            acquire: self,
+            until,
+        }
+    }
+
+    /// 撤销等待，已经授予的访问权限会被释放。
+    fn cancel(&mut self) {
+        let Some(id) = self.id.take() else { return };
+        let internal = &self.rc.rc;
+        let waiter = {
+            let mut waiters = internal.ensure_extras().waiters.borrow_mut();
+            let i = waiters.iter().position(|w| w.id == id).unwrap();
+            waiters.remove(i).unwrap()
+        };
+        if waiter.granted {
+            // 撤销已经授予但还没有被取走的访问权限
+            match (waiter.access, waiter.upgrade) {
+                (Access::Read, _) => internal.read_to_hold(),
+                (Access::Write, false) => internal.write_to_hold(),
+                (Access::Write, true) => internal.write_to_read(),
+            }
+            self.rc.notify_released()
+        } else {
+            #[cfg(feature = "deadlock-detect")]
+            if let Some(task) = waiter.task {
+                crate::deadlock::stop_waiting(task, self.rc.id())
+            }
+            // 队首的等待者被撤销后，后面的等待者可能可以获得访问权限
+            internal.grant_waiters()
+        }
+    }
+}
+
+impl<T> Drop for Acquire<'_, T> {
+    fn drop(&mut self) {
+        self.cancel()
+    }
+}
+
+impl<T, F: Future> Future for Until<'_, T, F> {
+    type Output = Result<(), F::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // 只有 `until` 被结构化地固定，`acquire` 总是可以移动
+        let this = unsafe { self.get_unchecked_mut() };
+        if Pin::new(&mut this.acquire).poll(cx).is_ready() {
+            return Poll::Ready(Ok(()));
+        }
+        match unsafe { Pin::new_unchecked(&mut this.until) }.poll(cx) {
+            Poll::Ready(output) => {
+                this.acquire.cancel();
+                Poll::Ready(Err(output))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+fn poll<F: Future>(f: Pin<&mut F>) -> Poll<F::Output> {
+    f.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+#[test]
+fn test_fifo_waiters() {
+    let reader = RwRc::new(0);
+    let mut writer = reader.clone();
+    let mut late1 = reader.clone();
+    let mut late2 = reader.clone();
+    writer.release();
+    late1.release();
+    late2.release();
+
+    let mut w = Box::pin(writer.write_async());
+    assert!(poll(w.as_mut()).is_pending());
+    // 先登记的写者在等待，后来的读者排在它后面
+    let mut r1 = Box::pin(late1.read_async());
+    let mut r2 = Box::pin(late2.read_async());
+    assert!(poll(r1.as_mut()).is_pending());
+    assert!(poll(r2.as_mut()).is_pending());
+
+    // 读者释放后先授予写者
+    reader.release();
+    assert!(poll(r1.as_mut()).is_pending());
+    assert!(poll(w.as_mut()).is_ready());
+    drop(w);
+    assert!(matches!(writer.state.get(), RwState::Write));
+
+    // 写者释放后连续的读者一起获得读状态
+    writer.release();
+    assert!(poll(r2.as_mut()).is_ready());
+    assert!(poll(r1.as_mut()).is_ready());
+    drop((r1, r2));
+    assert!(matches!(late1.state.get(), RwState::Read));
+    assert!(matches!(late2.state.get(), RwState::Read));
+    assert!(reader.rc.extras().unwrap().waiters.borrow().is_empty());
+}
+
+#[test]
+fn test_cancel_waiter() {
+    let reader = RwRc::new(0);
+    let mut writer = reader.clone();
+    let mut late = reader.clone();
+    writer.release();
+    late.release();
+
+    {
+        let mut w = Box::pin(writer.write_async());
+        assert!(poll(w.as_mut()).is_pending());
+        let mut r = Box::pin(late.read_async());
+        assert!(poll(r.as_mut()).is_pending());
+        // 撤销队首的写者后，后面的读者立即获得读状态
+        drop(w);
+        assert!(poll(r.as_mut()).is_ready());
+        drop(r);
+    }
+    assert!(matches!(late.state.get(), RwState::Read));
+    late.release();
+
+    {
+        let mut w = Box::pin(writer.write_async());
+        assert!(poll(w.as_mut()).is_pending());
+        // 已经授予但没有被取走的写状态在丢弃时释放
+        reader.release();
+    }
+    assert!(matches!(writer.state.get(), RwState::Hold));
+    assert!(writer.is_writeable());
+    assert!(reader.rc.extras().unwrap().waiters.borrow().is_empty());
+}
+
+#[test]
+fn test_until() {
+    use std::{cell::Cell, rc::Rc};
+
+    /// 在 `fired` 置位后完成的截止条件。
+    struct Deadline(Rc<Cell<bool>>);
+
+    impl Future for Deadline {
+        type Output = &'static str;
+
+        fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<&'static str> {
+            if self.0.get() {
+                Poll::Ready("deadline")
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    let reader = RwRc::new(0);
+    let mut writer = reader.clone();
+    let mut late = reader.clone();
+    writer.release();
+    late.release();
+    let fired = Rc::new(Cell::new(false));
+
+    // 截止后撤销等待，后面的等待者不再被阻挡
+    let mut w = Box::pin(writer.write_async().until(Deadline(fired.clone())));
+    assert!(poll(w.as_mut()).is_pending());
+    let mut r = Box::pin(late.read_async());
+    assert!(poll(r.as_mut()).is_pending());
+    fired.set(true);
+    assert_eq!(poll(w.as_mut()), Poll::Ready(Err("deadline")));
+    assert!(poll(r.as_mut()).is_ready());
+    drop((w, r));
+    assert!(matches!(writer.state.get(), RwState::Hold));
+    assert!(reader.rc.extras().unwrap().waiters.borrow().is_empty());
+    late.release();
+
+    // 截止前获得访问权限
+    reader.release();
+    let mut w = Box::pin(writer.write_async().until(Deadline(fired)));
+    assert_eq!(poll(w.as_mut()), Poll::Ready(Ok(())));
+    drop(w);
+    assert!(matches!(writer.state.get(), RwState::Write));
+}
@@ -1,5 +1,6 @@
-﻿use crate::{Internal, RwRc, RwState};
+use crate::{Internal, RwRc, RwState, trace::node_id};
 use std::{
+    cell::RefCell,
     cmp, fmt,
     hash::Hash,
     rc::{Rc, Weak},
@@ -30,7 +31,7 @@ use std::{
 /// assert!(weak.hold().is_none());
 /// ```
 #[repr(transparent)]
-pub struct RwWeak<T>(Weak<Internal<T>>);
+pub struct RwWeak<T>(pub(super) Weak<Internal<T>>);
 
 impl<T> fmt::Debug for RwWeak<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -40,6 +41,31 @@ impl<T> fmt::Debug for RwWeak<T> {
     }
 }
 
+#[cfg(feature = "defmt")]
+impl<T> defmt::Format for RwWeak<T> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "RwWeak({=usize:#x})",
+            self.0.as_ptr() as *const () as usize
+        )
+    }
+}
+
+/// 共享对象释放后执行的回调，随共享对象的其他部分一起释放时按登记顺序执行。
+///
+/// 作为 [`Internal`] 的字段在共享对象之后释放，执行时共享对象已经被释放，强引用计数已经归零。
+#[derive(Default)]
+pub(super) struct Invalidation(RefCell<Vec<Box<dyn FnOnce()>>>);
+
+impl Drop for Invalidation {
+    fn drop(&mut self) {
+        for f in self.0.get_mut().drain(..) {
+            f()
+        }
+    }
+}
+
 impl<T> RwRc<T> {
     /// 创建一个 [`RwRc<T>`] 的弱引用版本。
     ///
@@ -57,8 +83,30 @@ impl<T> RwRc<T> {
     /// assert_eq!(*weak.hold().unwrap().read(), 10);
     /// ```
     pub fn weak(&self) -> RwWeak<T> {
+        self.rc.check_thread();
         RwWeak(Rc::downgrade(&self.rc))
     }
+
+    /// 创建共享对象，同时返回它的弱引用，副本初始为读状态。
+    ///
+    /// 等价于 [`new`](RwRc::new) 之后调用 [`weak`](RwRc::weak)，
+    /// 只省去单独的调用，不会减少引用计数的操作。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let (rc, weak) = RwRc::new_with_weak(1);
+    /// assert!(weak.hold().is_some());
+    /// drop(rc);
+    /// assert!(weak.hold().is_none());
+    /// ```
+    pub fn new_with_weak(val: T) -> (Self, RwWeak<T>) {
+        let rc = Self::new(val);
+        let weak = RwWeak(Rc::downgrade(&rc.rc));
+        (rc, weak)
+    }
 }
 
 impl<T> Clone for RwWeak<T> {
@@ -94,6 +142,13 @@ impl<T> Ord for RwWeak<T> {
 }
 
 impl<T> RwWeak<T> {
+    /// 共享对象的标识，与 [`RwRc::id`] 相同。
+    ///
+    /// 共享对象释放后仍然返回原来的地址。
+    pub fn id(&self) -> usize {
+        node_id(&self.0)
+    }
+
     /// 尝试将弱引用升级为强引用。
     ///
     /// 如果原始的 [`RwRc<T>`] 已经被释放，则返回 `None`。
@@ -119,11 +174,140 @@ impl<T> RwWeak<T> {
     /// assert!(weak.hold().is_none());
     /// ```
     pub fn hold(&self) -> Option<RwRc<T>> {
-        self.0.upgrade().map(|rc| RwRc {
-            rc,
-            state: RwState::Hold,
+        self.0.upgrade().map(|rc| {
+            #[cfg(feature = "hooks")]
+            RwRc::hook_cloned(&rc);
+            RwRc::from_rc(rc, RwState::Hold)
         })
     }
+
+    /// 登记一个在共享对象失效后执行的回调。
+    ///
+    /// 最后一个强引用释放、共享对象被释放之后，回调按登记顺序各执行一次，
+    /// 此时所有弱引用都已经无法升级，适合立即清理以弱引用为键的登记表。
+    /// 回调在 [`on_last_drop`](RwRc::on_last_drop) 登记的回调之后执行；
+    /// 通过 [`zip`](RwRc::zip) 等方法移出共享对象时也会执行。
+    /// 共享对象已经失效时立即执行回调。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    /// use std::{cell::RefCell, collections::HashMap, rc::Rc};
+    ///
+    /// let registry = Rc::new(RefCell::new(HashMap::new()));
+    /// let rc = RwRc::new("texture");
+    /// let weak = rc.weak();
+    /// registry.borrow_mut().insert(weak.id(), weak.clone());
+    ///
+    /// let (entries, id) = (registry.clone(), weak.id());
+    /// weak.on_invalidated(move || {
+    ///     entries.borrow_mut().remove(&id);
+    /// });
+    /// drop(rc);
+    /// assert!(registry.borrow().is_empty());
+    /// ```
+    pub fn on_invalidated(&self, f: impl FnOnce() + 'static) {
+        match self.0.upgrade() {
+            Some(rc) => {
+                rc.check_thread();
+                let invalidated = &rc.ensure_extras().invalidated;
+                invalidated.0.borrow_mut().push(Box::new(f))
+            }
+            None => f(),
+        }
+    }
+}
+
+/// 占用一份读状态的弱引用，由 [`RwRc::try_weak_read`] 创建。
+///
+/// 与 [`RwWeak<T>`] 一样不阻止共享对象被释放，但在强引用存在期间始终占用一份读状态，
+/// 因此其他副本无法获取写状态。最后一个强引用释放时占用的读状态随之释放，
+/// [`on_last_drop`](RwRc::on_last_drop) 登记的回调仍然可以写入共享对象。
+///
+/// # 示例
+///
+/// ```rust
+/// use rwrc::RwRc;
+///
+/// let mut rc = RwRc::new(1);
+/// rc.release();
+/// let observer = rc.try_weak_read().unwrap();
+///
+/// // 观察者存在期间无法写入，但可以随时读取
+/// assert!(rc.try_write().is_none());
+/// assert_eq!(*observer.hold().unwrap().read(), 1);
+///
+/// // 观察者不阻止共享对象被释放
+/// drop(rc);
+/// assert!(observer.hold().is_none());
+/// ```
+pub struct RwWeakRead<T>(Weak<Internal<T>>);
+
+impl<T> fmt::Debug for RwWeakRead<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RwWeakRead")
+            .field(&format_args!("{:p}", self.0.as_ptr()))
+            .finish()
+    }
+}
+
+impl<T> Drop for RwWeakRead<T> {
+    fn drop(&mut self) {
+        // 共享对象已经释放时占用的读状态也已经释放
+        if let Some(rc) = self.0.upgrade() {
+            rc.check_thread();
+            let pins = &rc.ensure_extras().read_pins;
+            pins.set(pins.get() - 1);
+            rc.read_to_hold();
+            Internal::notify_released(&rc)
+        }
+    }
+}
+
+impl<T> RwRc<T> {
+    /// 尝试创建一个占用一份读状态的弱引用，无法获取读状态时返回 `None`。
+    ///
+    /// 获取读状态时与 [`try_read_global`](Self::try_read_global) 一样考虑读写偏好策略和读者数量上限，
+    /// 不改变此副本的读写状态。
+    pub fn try_weak_read(&self) -> Option<RwWeakRead<T>> {
+        self.rc.check_thread();
+        if !self.hold_to_read() {
+            return None;
+        }
+        let pins = &self.rc.ensure_extras().read_pins;
+        pins.set(pins.get() + 1);
+        Some(RwWeakRead(Rc::downgrade(&self.rc)))
+    }
+}
+
+impl<T> RwWeakRead<T> {
+    /// 共享对象的标识，与 [`RwRc::id`] 相同。
+    pub fn id(&self) -> usize {
+        node_id(&self.0)
+    }
+
+    /// 尝试升级为持有状态的强引用，共享对象已经释放时返回 `None`。
+    ///
+    /// 返回的副本与此弱引用各自占用读写状态。
+    pub fn hold(&self) -> Option<RwRc<T>> {
+        RwWeak(self.0.clone()).hold()
+    }
+
+    /// 不占用读写状态的弱引用。
+    pub fn weak(&self) -> RwWeak<T> {
+        RwWeak(self.0.clone())
+    }
+}
+
+impl<T> Internal<T> {
+    /// 释放所有 [`RwWeakRead`] 占用的读状态，最后一个强引用释放时调用。
+    pub(super) fn unpin_readers(&self) {
+        let Some(extras) = self.extras() else { return };
+        for _ in 0..extras.read_pins.replace(0) {
+            self.read_to_hold()
+        }
+    }
 }
 
 #[test]
@@ -281,3 +465,76 @@ fn test_weak_debug() {
     let weak3 = rc2.weak();
     assert_ne!(format!("{:?}", weak), format!("{:?}", weak3));
 }
+
+#[test]
+fn test_new_with_weak() {
+    let (rc, weak) = RwRc::new_with_weak(vec![1]);
+    assert!(matches!(rc.state.get(), RwState::Read));
+    assert_eq!(weak, rc.weak());
+    // `audit` 特性的登记也持有一个弱引用
+    assert_eq!(Rc::weak_count(&rc.rc), 1 + cfg!(feature = "audit") as usize);
+    assert_eq!(*weak.hold().unwrap().read(), [1]);
+}
+
+#[test]
+fn test_on_invalidated() {
+    use std::cell::Cell;
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let (rc, weak) = RwRc::new_with_weak(0);
+    let order = log.clone();
+    rc.on_last_drop(move |_| order.borrow_mut().push("last drop"));
+    for name in ["first", "second"] {
+        let (log, weak) = (log.clone(), weak.clone());
+        weak.clone().on_invalidated(move || {
+            // 回调执行时弱引用已经无法升级
+            assert!(weak.hold().is_none());
+            log.borrow_mut().push(name)
+        })
+    }
+    drop(rc.clone());
+    assert!(log.borrow().is_empty());
+    drop(rc);
+    assert_eq!(*log.borrow(), ["last drop", "first", "second"]);
+
+    // 已经失效时立即执行
+    let fired = Rc::new(Cell::new(false));
+    let flag = fired.clone();
+    weak.on_invalidated(move || flag.set(true));
+    assert!(fired.get());
+
+    // 移出共享对象时也会执行
+    let a = RwRc::new(1);
+    let flag = fired.clone();
+    fired.set(false);
+    a.weak().on_invalidated(move || flag.set(true));
+    let _ = a.zip(RwRc::new(2)).ok().unwrap();
+    assert!(fired.get());
+}
+
+#[test]
+fn test_weak_read() {
+    let mut rc = RwRc::new(0);
+    let other = rc.clone();
+    other.release();
+    rc.release();
+    let pin = rc.try_weak_read().unwrap();
+    assert!(!other.try_write_global());
+    assert!(other.try_read_global());
+    other.release();
+
+    // 释放后写者可以获取写状态，写状态下无法创建
+    drop(pin);
+    *rc.write() = 1;
+    assert!(rc.try_write_global());
+    assert!(other.try_weak_read().is_none());
+    rc.release();
+
+    // 最后一个强引用释放时回调仍然可以写入
+    let pin = other.try_weak_read().unwrap();
+    assert_eq!(pin.id(), other.id());
+    other.on_last_drop(|val| *val += 1);
+    drop((rc, other));
+    assert!(pin.hold().is_none());
+    assert!(pin.weak().hold().is_none());
+}
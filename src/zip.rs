@@ -0,0 +1,220 @@
+use crate::{Access, LocalRef, LockError, RwRc};
+use std::cmp::Ordering;
+
+/// 合并的结果，失败时返回原来的两个副本。
+type Zipped<T, U> = Result<RwRc<(T, U)>, (RwRc<T>, RwRc<U>)>;
+
+impl<T> RwRc<T> {
+    /// 将两个共享对象合并为一个元组共享对象。
+    ///
+    /// 两个副本都必须是各自共享对象唯一的强引用，才能移出共享对象；否则原样返回两个副本。
+    /// 合并后的共享对象使用此副本的读写偏好策略，初始为读状态。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let a = RwRc::new(1);
+    /// let b = RwRc::new("one");
+    /// let zipped = a.zip(b).ok().unwrap();
+    /// assert_eq!(*zipped.read(), (1, "one"));
+    /// ```
+    pub fn zip<U>(self, other: RwRc<U>) -> Zipped<T, U> {
+        if !self.is_unique() || !other.is_unique() {
+            return Err((self, other));
+        }
+        let preference = self.rc.preference;
+        match (self.try_into_value(), other.try_into_value()) {
+            (Ok(a), Ok(b)) => Ok(RwRc::with_preference((a, b), preference)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// 同时读取两个共享对象，任何一个无法读取时返回 `None`，不会保留另一个的读取。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let a = RwRc::new(1);
+    /// let b = RwRc::new(2);
+    /// let (a, b) = a.read_zip(&b).unwrap();
+    /// assert_eq!(*a + *b, 3);
+    /// ```
+    #[track_caller]
+    pub fn read_zip<'a, U>(
+        &'a self,
+        other: &'a RwRc<U>,
+    ) -> Option<(LocalRef<'a, T>, LocalRef<'a, U>)> {
+        let a = self.try_read()?;
+        let b = other.try_read()?;
+        Some((a, b))
+    }
+
+    /// 在读取两个共享对象的值后比较它们是否相等，任何一个无法读取时返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let a = RwRc::new(1);
+    /// assert_eq!(a.eq_value(&RwRc::new(1)), Some(true));
+    /// ```
+    #[track_caller]
+    pub fn eq_value<U>(&self, other: &RwRc<U>) -> Option<bool>
+    where
+        T: PartialEq<U>,
+    {
+        let (a, b) = self.read_zip(other)?;
+        Some(*a == *b)
+    }
+
+    /// 在读取两个共享对象的值后比较它们的大小，任何一个无法读取时返回 `None`。
+    ///
+    /// 值本身无法比较时返回 `Some(None)`。
+    #[track_caller]
+    pub fn partial_cmp_value<U>(&self, other: &RwRc<U>) -> Option<Option<Ordering>>
+    where
+        T: PartialOrd<U>,
+    {
+        let (a, b) = self.read_zip(other)?;
+        Some(T::partial_cmp(&a, &b))
+    }
+
+    /// 在读取两个共享对象的值后比较它们的大小，任何一个无法读取时返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    /// use std::cmp::Ordering;
+    ///
+    /// let mut keys = vec![RwRc::new("b"), RwRc::new("a")];
+    /// keys.sort_by(|a, b| a.cmp_value(b).unwrap());
+    /// assert_eq!(*keys[0].read(), "a");
+    /// assert_eq!(keys[0].cmp_value(&keys[1]), Some(Ordering::Less));
+    /// ```
+    #[track_caller]
+    pub fn cmp_value(&self, other: &Self) -> Option<Ordering>
+    where
+        T: Ord,
+    {
+        let (a, b) = self.read_zip(other)?;
+        Some(T::cmp(&a, &b))
+    }
+
+    /// 交换两个共享对象的值。
+    ///
+    /// 按共享对象的地址顺序依次获取两者的写状态，任何一个无法获取时返回错误，
+    /// 已经获取的写状态会被撤销，两个副本保持原来的读写状态。
+    /// 两个副本指向同一个共享对象时什么也不做。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rwrc::RwRc;
+    ///
+    /// let mut front = RwRc::new(vec![1]);
+    /// let mut back = RwRc::new(vec![2]);
+    /// front.swap_contents(&mut back).unwrap();
+    /// assert_eq!(*front.read(), [2]);
+    /// assert_eq!(*back.read(), [1]);
+    /// ```
+    pub fn swap_contents(&mut self, other: &mut RwRc<T>) -> Result<(), LockError> {
+        if std::rc::Rc::ptr_eq(&self.rc, &other.rc) {
+            return Ok(());
+        }
+        let (first, second) = if std::rc::Rc::as_ptr(&self.rc) < std::rc::Rc::as_ptr(&other.rc) {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let blocked = LockError::Blocked(Access::Write);
+        let mut a = first.try_write().ok_or(blocked)?;
+        let mut b = second.try_write().ok_or(blocked)?;
+        std::mem::swap(&mut *a, &mut *b);
+        Ok(())
+    }
+
+    /// 判断此副本是否是唯一的强引用。
+    fn is_unique(&self) -> bool {
+        std::rc::Rc::strong_count(&self.rc) == 1
+    }
+}
+
+#[test]
+fn test_zip() {
+    let a = RwRc::new(vec![1]);
+    let b = RwRc::new(String::from("b"));
+    let weak = b.weak();
+
+    // 存在其他副本时原样返回
+    let other = a.clone();
+    let (a, b) = a.zip(b).err().unwrap();
+    drop(other);
+    let zipped = a.zip(b).ok().unwrap();
+    assert_eq!(*zipped.read(), (vec![1], String::from("b")));
+    assert!(weak.hold().is_none());
+}
+
+#[test]
+fn test_read_zip() {
+    let a = RwRc::new(1);
+    let b = RwRc::new(2);
+    a.release();
+    b.release();
+    let writer = b.clone();
+    assert!(writer.try_write_global());
+
+    // 第二个无法读取时第一个的读取也被撤销
+    assert!(a.read_zip(&b).is_none());
+    assert!(a.is_writeable());
+    writer.release();
+    let (ra, rb) = a.read_zip(&b).unwrap();
+    assert_eq!((*ra, *rb), (1, 2));
+}
+
+#[test]
+fn test_swap_contents() {
+    let mut a = RwRc::new(1);
+    let mut b = RwRc::new(2);
+    let reader = b.clone();
+    a.release();
+
+    // 无法获取写状态时两个副本保持原来的状态
+    assert_eq!(
+        a.swap_contents(&mut b),
+        Err(LockError::Blocked(Access::Write))
+    );
+    assert!(a.is_writeable());
+    assert_eq!(*a.read(), 1);
+    drop(reader);
+    a.swap_contents(&mut b).unwrap();
+    assert_eq!((*a.read(), *b.read()), (2, 1));
+    assert!(matches!(a.state.get(), crate::RwState::Hold));
+
+    let mut c = a.clone();
+    a.swap_contents(&mut c).unwrap();
+}
+
+#[test]
+fn test_compare_values() {
+    let a = RwRc::new(1.0);
+    let b = RwRc::new(2.0);
+    assert_eq!(a.eq_value(&b), Some(false));
+    assert_eq!(a.partial_cmp_value(&b), Some(Some(Ordering::Less)));
+    assert_eq!(a.partial_cmp_value(&RwRc::new(f64::NAN)), Some(None));
+
+    // 同一个共享对象的两个副本可以比较
+    let c = RwRc::new(3);
+    assert_eq!(c.cmp_value(&c.clone()), Some(Ordering::Equal));
+
+    // 任何一个被写锁定时返回 None
+    let writer = b.clone();
+    b.release();
+    assert!(writer.try_write_global());
+    assert_eq!(a.eq_value(&b), None);
+}